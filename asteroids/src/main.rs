@@ -102,7 +102,7 @@ async fn main() -> Result<()> {
 
     loop {
         if player_dead_rx.try_recv().is_ok() {
-            world.despawn_many(asteroid().with());
+            world.despawn_many(asteroid().with())?;
 
             create_player().spawn(&mut world);
         }
@@ -104,7 +104,7 @@ fn main() {
     tracing::info!("Trees: {:#?}", world.format_entities(&trees[0..100]));
 
     // ANCHOR_END: batch
-    world.despawn_many(All);
+    world.despawn_many(All).unwrap();
     // ANCHOR: hierarchy
 
     let id = Entity::builder()
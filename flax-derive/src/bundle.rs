@@ -0,0 +1,151 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Attribute, DeriveInput, Error, Expr, Ident, Result};
+
+/// A struct field and the expression of the component it maps to
+struct BundleField<'a> {
+    ident: &'a Ident,
+    component: TokenStream,
+}
+
+impl<'a> BundleField<'a> {
+    fn get(field: &'a syn::Field) -> Result<Self> {
+        let ident = field
+            .ident
+            .as_ref()
+            .ok_or(Error::new(field.span(), "Only named fields are supported"))?;
+
+        let component = match Self::component_attr(&field.attrs)? {
+            Some(expr) => quote! { #expr },
+            None => quote! { #ident() },
+        };
+
+        Ok(Self { ident, component })
+    }
+
+    fn component_attr(input: &[Attribute]) -> Result<Option<Expr>> {
+        let mut res = None;
+
+        for attr in input {
+            if !attr.path().is_ident("bundle") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                // component = position()
+                if meta.path.is_ident("component") {
+                    res = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else {
+                    Err(Error::new(
+                        meta.path.span(),
+                        "Unknown bundle field attribute",
+                    ))
+                }
+            })?;
+        }
+
+        Ok(res)
+    }
+}
+
+pub(crate) fn do_derive_bundle(crate_name: Ident, input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    derive_bundle_struct(&crate_name, &input).unwrap_or_else(|err| err.to_compile_error())
+}
+
+fn derive_bundle_struct(crate_name: &Ident, input: &DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        syn::Data::Struct(data) => match &data.fields {
+            syn::Fields::Named(fields) => fields,
+            syn::Fields::Unnamed(_) => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "Deriving bundle for a tuple struct is not supported",
+                ))
+            }
+            syn::Fields::Unit => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "Deriving bundle for a unit struct is not supported",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Bundles can only be derived for structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .named
+        .iter()
+        .map(BundleField::get)
+        .collect::<Result<Vec<_>>>()?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let field_names = fields.iter().map(|v| v.ident).collect::<Vec<_>>();
+    let components = fields.iter().map(|v| &v.component).collect::<Vec<_>>();
+    let columns = field_names
+        .iter()
+        .map(|v| format_ident!("{v}_column"))
+        .collect::<Vec<_>>();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #crate_name::entity::Bundle for #name #ty_generics #where_clause {
+            fn components() -> #crate_name::__Vec<#crate_name::component::ComponentDesc> {
+                let mut components = #crate_name::__Vec::new();
+                #(components.push(#crate_name::Component::desc(#components));)*
+                components
+            }
+
+            fn append_to(self, builder: &mut #crate_name::EntityBuilder) {
+                #(builder.set(#components, self.#field_names);)*
+            }
+
+            fn into_batch(items: #crate_name::__Vec<Self>) -> #crate_name::BatchSpawn {
+                let mut batch = #crate_name::BatchSpawn::new(items.len());
+                #(let mut #columns = #crate_name::__Vec::with_capacity(items.len());)*
+
+                for item in items {
+                    #(#columns.push(item.#field_names);)*
+                }
+
+                #(
+                    batch
+                        .set(#components, #columns)
+                        .expect("Bundle columns have the same length as the batch");
+                )*
+
+                batch
+            }
+
+            fn remove_from(
+                world: &mut #crate_name::World,
+                id: #crate_name::Entity,
+            ) -> #crate_name::error::Result<Self> {
+                Ok(Self {
+                    #(#field_names: world.remove(id, #components)?,)*
+                })
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for #crate_name::EntityBuilder #where_clause {
+            fn from(bundle: #name #ty_generics) -> Self {
+                let mut builder = #crate_name::EntityBuilder::new();
+                #crate_name::entity::Bundle::append_to(bundle, &mut builder);
+                builder
+            }
+        }
+    })
+}
@@ -1,5 +1,8 @@
 use std::collections::BTreeSet;
 
+mod bundle;
+mod system_param;
+
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::FoundCrate;
@@ -37,6 +40,54 @@ pub fn derive_fetch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     do_derive_fetch(crate_name, input.into()).into()
 }
 
+/// ```rust,ignore
+/// #[derive(Bundle)]
+/// struct Player {
+///     #[bundle(component = position())]
+///     pos: glam::Vec3,
+///     health: f32,
+/// }
+/// ```
+///
+/// Implements `Bundle` and conversion into `EntityBuilder` for a struct.
+///
+/// # Field Attributes
+/// - `component`: the component expression the field maps to.
+///   Defaults to the component function with the same name as the field.
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let crate_name = match proc_macro_crate::crate_name("flax").expect("Failed to get crate name") {
+        FoundCrate::Itself => Ident::new("crate", Span::call_site()),
+        FoundCrate::Name(name) => Ident::new(&name, Span::call_site()),
+    };
+    bundle::do_derive_bundle(crate_name, input.into()).into()
+}
+
+/// ```rust,ignore
+/// #[derive(SystemParam)]
+/// struct PhysicsParams<'w> {
+///     bodies: QueryBorrow<'w, (Mutable<Vec3>, Component<Vec3>)>,
+///     world: &'w World,
+///     steps: Local<'w, usize>,
+/// }
+/// ```
+///
+/// Implements `SystemParam` for a struct of system params, allowing them to be used as a
+/// single function system argument.
+///
+/// The struct must have exactly one lifetime, which is the lifetime of the borrowed params.
+///
+/// Generates `{Name}State`, which holds the state of each param and implements
+/// `SystemAccess`, and `{Name}Data`, which holds the acquired data of each param.
+#[proc_macro_derive(SystemParam)]
+pub fn derive_system_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let crate_name = match proc_macro_crate::crate_name("flax").expect("Failed to get crate name") {
+        FoundCrate::Itself => Ident::new("crate", Span::call_site()),
+        FoundCrate::Name(name) => Ident::new(&name, Span::call_site()),
+    };
+    system_param::do_derive_system_param(crate_name, input.into()).into()
+}
+
 fn do_derive_fetch(crate_name: Ident, input: TokenStream) -> TokenStream {
     let input = match syn::parse2::<DeriveInput>(input) {
         Ok(input) => input,
@@ -0,0 +1,204 @@
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    spanned::Spanned, DeriveInput, Error, GenericParam, Generics, Ident, Lifetime, LifetimeParam,
+    Result, Type,
+};
+
+pub(crate) fn do_derive_system_param(crate_name: Ident, input: TokenStream) -> TokenStream {
+    let input = match syn::parse2::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    derive_system_param_struct(&crate_name, &input).unwrap_or_else(|err| err.to_compile_error())
+}
+
+fn derive_system_param_struct(crate_name: &Ident, input: &DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        syn::Data::Struct(data) => match &data.fields {
+            syn::Fields::Named(fields) => fields,
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "Deriving system param is only supported for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "System params can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut lifetimes = input.generics.lifetimes();
+    let lifetime = match (lifetimes.next(), lifetimes.next()) {
+        (Some(lifetime), None) => &lifetime.lifetime,
+        _ => {
+            return Err(Error::new(
+                input.generics.span(),
+                "A system param must have exactly one lifetime parameter",
+            ))
+        }
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let state_name = format_ident!("{name}State");
+    let data_name = format_ident!("{name}Data");
+
+    let field_names = fields
+        .named
+        .iter()
+        .map(|v| v.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+
+    // The field types with the borrow lifetime erased, as the state and data do not borrow
+    // from the system param
+    let field_types = fields
+        .named
+        .iter()
+        .map(|v| replace_lifetime(v.ty.to_token_stream(), &lifetime.ident))
+        .map(syn::parse2::<Type>)
+        .collect::<Result<Vec<_>>>()?;
+
+    let a_lf = Lifetime::new("'a", Span::call_site());
+    let b_lf = Lifetime::new("'b", Span::call_site());
+
+    // Generics without the lifetime of the param, used for the state
+    let state_generics = Generics {
+        params: input
+            .generics
+            .params
+            .iter()
+            .filter(|v| !matches!(v, GenericParam::Lifetime(_)))
+            .cloned()
+            .collect(),
+        ..input.generics.clone()
+    };
+
+    let data_generics = prepend_lifetime(&a_lf, &state_generics);
+    let borrowed_generics = prepend_lifetime(&b_lf, &data_generics);
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (state_impl, state_ty, _) = state_generics.split_for_impl();
+    let (_, data_ty, _) = data_generics.split_for_impl();
+    let (borrowed_impl, _, _) = borrowed_generics.split_for_impl();
+
+    // The param type with the lifetime replaced by `'b`
+    let borrowed_ty = {
+        let params = input.generics.params.iter().map(|v| match v {
+            GenericParam::Lifetime(_) => b_lf.to_token_stream(),
+            GenericParam::Type(v) => v.ident.to_token_stream(),
+            GenericParam::Const(v) => v.ident.to_token_stream(),
+        });
+        quote! { #name<#(#params),*> }
+    };
+
+    let state_msg = format!("The state of the {name} system param");
+    let data_msg = format!("The data acquired for the {name} system param");
+
+    let param_trait = quote! { #crate_name::system::SystemParam };
+
+    Ok(quote! {
+        #[doc = #state_msg]
+        #vis struct #state_name #state_generics #where_clause {
+            #(#field_names: <#field_types as #param_trait>::State,)*
+        }
+
+        #[doc = #data_msg]
+        #vis struct #data_name #data_generics #where_clause {
+            #(#field_names: <#field_types as #param_trait>::Value<#a_lf>,)*
+        }
+
+        #[automatically_derived]
+        impl #state_impl #crate_name::system::SystemAccess for #state_name #state_ty #where_clause {
+            fn access(&self, world: &#crate_name::World, dst: &mut #crate_name::__Vec<#crate_name::system::Access>) {
+                #(#crate_name::system::SystemAccess::access(&self.#field_names, world, dst);)*
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #param_trait for #name #ty_generics #where_clause {
+            type Value<#a_lf> = #data_name #data_ty;
+            type State = #state_name #state_ty;
+
+            fn init_state(ctx: &#crate_name::system::InitStateContext<'_, '_>) -> Self::State {
+                #state_name {
+                    #(#field_names: <#field_types as #param_trait>::init_state(ctx),)*
+                }
+            }
+
+            fn acquire<#a_lf>(
+                state: &#a_lf mut Self::State,
+                ctx: &#a_lf #crate_name::system::SystemContext<'_, '_, '_>,
+            ) -> Self::Value<#a_lf> {
+                #data_name {
+                    #(#field_names: <#field_types as #param_trait>::acquire(&mut state.#field_names, ctx),)*
+                }
+            }
+
+            fn describe(state: &Self::State, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(stringify!(#name))
+                    #(.field(
+                        stringify!(#field_names),
+                        &#crate_name::system::FmtSystemParam::<#field_types>(&state.#field_names),
+                    ))*
+                    .finish()
+            }
+        }
+
+        #[automatically_derived]
+        impl #borrowed_impl #crate_name::system::AsBorrowed<#b_lf> for #data_name #data_ty #where_clause {
+            type Borrowed = #borrowed_ty;
+
+            fn as_borrowed(&#b_lf mut self) -> Self::Borrowed {
+                #name {
+                    #(#field_names: #crate_name::system::AsBorrowed::as_borrowed(&mut self.#field_names),)*
+                }
+            }
+        }
+    })
+}
+
+fn prepend_lifetime(lifetime: &Lifetime, generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    generics.params.insert(
+        0,
+        GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+    );
+    generics
+}
+
+/// Replaces all occurrences of `lifetime` with `'static`
+fn replace_lifetime(tokens: TokenStream, lifetime: &Ident) -> TokenStream {
+    let mut res = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                res.push(TokenTree::Punct(punct));
+                if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+                    if ident == lifetime {
+                        res.push(TokenTree::Ident(Ident::new("static", ident.span())));
+                        tokens.next();
+                    }
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut new_group = Group::new(
+                    group.delimiter(),
+                    replace_lifetime(group.stream(), lifetime),
+                );
+                new_group.set_span(group.span());
+                res.push(TokenTree::Group(new_group));
+            }
+            token => res.push(token),
+        }
+    }
+
+    res.into_iter().collect()
+}
@@ -31,6 +31,10 @@ impl BatchSpawn {
         self.storage.values().map(|v| v.desc())
     }
 
+    pub(crate) fn storages(&self) -> impl Iterator<Item = &Storage> {
+        self.storage.values()
+    }
+
     /// Returns the number of entities in the batch
     pub fn len(&self) -> usize {
         self.len
@@ -44,12 +48,20 @@ impl BatchSpawn {
 
     /// Set values for a specific component. The number of the items in the iterator
     /// must match the `len` given to the `BatchSpawn`
+    ///
+    /// Components with the [`Unique`](crate::metadata::Unique) or
+    /// [`UniqueValue`](crate::metadata::UniqueValue) constraint are refused, as the entities of a
+    /// batch are not checked against each other or the world.
     pub fn set<T: ComponentValue>(
         &mut self,
         component: Component<T>,
         iter: impl IntoIterator<Item = T>,
     ) -> Result<&mut Self> {
         let desc = component.desc();
+        if desc.unique_constraint().is_some() {
+            return Err(Error::UniqueInBatch(desc));
+        }
+
         let mut storage = Storage::with_capacity(desc, self.len);
 
         for item in iter.into_iter().take(self.len) {
@@ -6,9 +6,13 @@ use core::{
 
 use atomic_refcell::{AtomicRef, AtomicRefMut};
 
-use crate::{component::ComponentValue, Entity};
+use crate::{
+    component::ComponentValue,
+    relation::{PairTicks, SparsePairs},
+    Entity,
+};
 
-use super::{CellData, Changes, Slice, Slot};
+use super::{CellData, Changes, PreviousValues, Slice, Slot};
 
 /// Type safe abstraction over a borrowed cell data
 pub(crate) struct CellMutGuard<'a, T: ?Sized> {
@@ -35,6 +39,7 @@ impl<'a, T: ?Sized> CellMutGuard<'a, T> {
     pub(crate) fn set_modified(&mut self, ids: &[Entity], slots: Slice, tick: u32) {
         // SAFETY: `value` is not accessed in this function
         let data = &mut *self.data;
+        data.record_previous(ids, slots, tick);
         data.set_modified(ids, slots, tick)
     }
 
@@ -100,6 +105,11 @@ impl<'a, T: ?Sized> CellGuard<'a, T> {
         &self.data.changes
     }
 
+    #[inline]
+    pub(crate) fn previous(&self) -> Option<&PreviousValues> {
+        self.data.previous.as_ref()
+    }
+
     #[inline]
     pub(crate) fn get(&self) -> &T {
         unsafe { self.storage.as_ref() }
@@ -120,6 +130,8 @@ pub struct RefMut<'a, T> {
     id: Entity,
     slot: Slot,
     tick: u32,
+    /// The change ticks of the accessed pair of a sparse relation
+    pair_ticks: Option<NonNull<PairTicks>>,
 }
 
 impl<'a, T: ComponentValue> RefMut<'a, T> {
@@ -137,6 +149,33 @@ impl<'a, T: ComponentValue> RefMut<'a, T> {
             id,
             slot,
             tick,
+            pair_ticks: None,
+        })
+    }
+}
+
+impl<'a, T: ComponentValue> RefMut<'a, T> {
+    /// Access the value of the pair to `target` of a sparse relation
+    pub(super) fn new_pair(
+        guard: CellMutGuard<'a, [SparsePairs<T>]>,
+        id: Entity,
+        slot: Slot,
+        target: Entity,
+        tick: u32,
+    ) -> Option<Self> {
+        let mut pair_ticks = None;
+        let guard = guard.filter_map(|v| {
+            let (value, ticks) = v.get_mut(slot)?.get_with_ticks_mut(target)?;
+            pair_ticks = Some(NonNull::from(ticks));
+            Some(value)
+        })?;
+
+        Some(Self {
+            guard,
+            id,
+            slot,
+            tick,
+            pair_ticks,
         })
     }
 }
@@ -159,9 +198,15 @@ impl<'a, T> Deref for RefMut<'a, T> {
 impl<'a, T> DerefMut for RefMut<'a, T> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.guard
-            .data
-            .set_modified(&[self.id], Slice::single(self.slot), self.tick);
+        let data = &mut *self.guard.data;
+        data.record_previous(&[self.id], Slice::single(self.slot), self.tick);
+        data.set_modified(&[self.id], Slice::single(self.slot), self.tick);
+
+        if let Some(mut ticks) = self.pair_ticks {
+            // SAFETY: the pairs are exclusively borrowed by the guard and are disjoint from the
+            // value
+            unsafe { ticks.as_mut().modified = self.tick }
+        }
 
         self.guard.get_mut()
     }
@@ -12,6 +12,8 @@ use itertools::Itertools;
 use crate::{
     component::{ComponentDesc, ComponentKey, ComponentValue},
     events::{EventData, EventSubscriber},
+    metadata::{sparse_storage, track_previous},
+    relation::{SparsePairs, SparseTargets},
     writer::ComponentUpdater,
     Component, Entity,
 };
@@ -24,6 +26,7 @@ pub type Slot = usize;
 mod batch;
 mod changes;
 mod guard;
+mod previous;
 mod slice;
 mod storage;
 
@@ -33,6 +36,7 @@ pub use slice::*;
 pub use storage::Storage;
 
 pub use guard::*;
+pub(crate) use previous::PreviousValues;
 
 #[derive(Debug, Clone)]
 /// Holds information of a single component storage buffer
@@ -93,9 +97,23 @@ pub(crate) struct CellData {
     pub(crate) changes: Changes,
     subscribers: Vec<Arc<dyn EventSubscriber>>,
     pub(crate) key: ComponentKey,
+    /// Present if the component tracks its previous values
+    pub(crate) previous: Option<PreviousValues>,
 }
 
 impl CellData {
+    /// Retains the current values of the specified slots if the component tracks its previous
+    /// values.
+    ///
+    /// Must be called *before* the values are written to.
+    /// **Note**: `ids` must be the slice of entities pointed to by `slice`
+    pub(crate) fn record_previous(&mut self, ids: &[Entity], slots: Slice, change_tick: u32) {
+        debug_assert_eq!(ids.len(), slots.len());
+        if let Some(previous) = &mut self.previous {
+            previous.record(&self.storage, ids, slots, change_tick);
+        }
+    }
+
     /// Sets the specified entities and slots as modified and invokes subscribers
     /// **Note**: `ids` must be the slice of entities pointed to by `slice`
     pub(crate) fn set_modified(&mut self, ids: &[Entity], slots: Slice, change_tick: u32) {
@@ -158,19 +176,28 @@ impl Cell {
                 changes: Changes::new(),
                 subscribers: Vec::new(),
                 key: desc.key,
+                previous: desc
+                    .meta_ref()
+                    .get(track_previous())
+                    .cloned()
+                    .map(PreviousValues::new),
             }),
             desc,
         }
     }
 
     /// Moves a slot in the cell to another cell and slot while migrating all changes.
-    fn move_to(&mut self, slot: Slot, dst: &mut Self, dst_slot: Slot) {
+    fn move_to(&mut self, id: Entity, slot: Slot, dst: &mut Self, dst_slot: Slot) {
         let data = self.data.get_mut();
 
         let last = data.storage.len() - 1;
 
         let dst = dst.data.get_mut();
 
+        if let Some(previous) = &mut data.previous {
+            previous.move_to(id, &mut dst.previous);
+        }
+
         data.storage.swap_remove(slot, |p| unsafe {
             dst.storage.extend(p, 1);
         });
@@ -184,11 +211,17 @@ impl Cell {
     }
 
     /// Moves all slots to another cell
-    fn move_all(&mut self, dst: &mut Self, dst_start: Slot) {
+    fn move_all(&mut self, ids: &[Entity], dst: &mut Self, dst_start: Slot) {
         let data = self.data.get_mut();
 
         let dst = dst.data.get_mut();
 
+        if let Some(previous) = &mut data.previous {
+            for &id in ids {
+                previous.move_to(id, &mut dst.previous);
+            }
+        }
+
         debug_assert_eq!(dst.storage.len(), dst_start);
         unsafe { dst.storage.append(&mut data.storage) }
 
@@ -203,9 +236,13 @@ impl Cell {
     }
 
     /// Move a slot out of the cell by swapping with the last
-    fn take(&mut self, slot: Slot, mut on_move: impl FnMut(ComponentDesc, *mut u8)) {
+    fn take(&mut self, id: Entity, slot: Slot, mut on_move: impl FnMut(ComponentDesc, *mut u8)) {
         let data = self.data.get_mut();
 
+        if let Some(previous) = &mut data.previous {
+            previous.remove(id);
+        }
+
         let last = data.storage.len() - 1;
 
         data.storage.swap_remove(slot, |p| on_move(self.desc, p));
@@ -218,6 +255,10 @@ impl Cell {
 
         data.storage.clear();
         data.changes.clear();
+
+        if let Some(previous) = &mut data.previous {
+            previous.clear();
+        }
     }
 
     /// Drain the values in the cell.
@@ -226,6 +267,10 @@ impl Cell {
         let storage = mem::replace(&mut data.storage, Storage::new(self.desc));
         data.changes.clear();
 
+        if let Some(previous) = &mut data.previous {
+            previous.clear();
+        }
+
         storage
     }
 
@@ -361,6 +406,51 @@ impl Archetype {
         self.components.contains_key(&component)
     }
 
+    /// Returns true if the entity at `slot` has `component`, including pairs of sparse relations
+    pub(crate) fn has_at(&self, slot: Slot, component: ComponentKey) -> bool {
+        if self.has(component) {
+            return true;
+        }
+
+        match (component.target, self.sparse_targets(component.id)) {
+            (Some(target), Some(targets)) => targets.get(slot).binary_search(&target).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Returns the targets of the pairs of the sparse `relation` for each slot
+    pub(crate) fn sparse_targets(&self, relation: Entity) -> Option<SparseTargets<'_>> {
+        let cell = self.cell(ComponentKey::new(relation, None))?;
+        if !cell.desc.meta_ref().has(sparse_storage()) {
+            return None;
+        }
+
+        // Safety: the cell stores sparse pairs
+        Some(unsafe { SparseTargets::new(cell.data.borrow()) })
+    }
+
+    /// Returns true if the archetype has any pairs of `relation`, dense or sparse
+    pub(crate) fn has_relation(&self, relation: Entity) -> bool {
+        self.relations_like(relation).next().is_some() || self.sparse_targets(relation).is_some()
+    }
+
+    /// Borrows the pairs of the sparse `relation`
+    pub(crate) fn borrow_sparse<T: ComponentValue>(
+        &self,
+        relation: Entity,
+    ) -> Option<CellGuard<'_, [SparsePairs<T>]>> {
+        let cell = self.cell(ComponentKey::new(relation, None))?;
+        cell.desc.is::<SparsePairs<T>>().then(|| cell.borrow())
+    }
+
+    /// Returns the cell of the pairs of the sparse relation of `component`, along with the target
+    fn sparse_cell<T: ComponentValue>(&self, component: Component<T>) -> Option<(&Cell, Entity)> {
+        let target = component.key().target?;
+        let cell = self.cell(ComponentKey::new(component.key().id, None))?;
+
+        cell.desc.is::<SparsePairs<T>>().then_some((cell, target))
+    }
+
     pub(crate) fn incoming(&self, component: ComponentKey) -> Option<ArchetypeId> {
         self.incoming.get(&component).copied()
     }
@@ -455,8 +545,12 @@ impl Archetype {
         component: Component<T>,
         tick: u32,
     ) -> Option<RefMut<T>> {
-        self.cell(component.key())?
-            .get_mut(self.entities[slot], slot, tick)
+        let Some(cell) = self.cell(component.key()) else {
+            let (cell, target) = self.sparse_cell(component)?;
+            return RefMut::new_pair(cell.borrow_mut(), self.entities[slot], slot, target, tick);
+        };
+
+        cell.get_mut(self.entities[slot], slot, tick)
     }
 
     /// Get a component from the entity at `slot`
@@ -468,7 +562,20 @@ impl Archetype {
     ) -> Result<Option<RefMut<T>>, BorrowMutError> {
         let cell = match self.cell(component.key()) {
             Some(v) => v,
-            None => return Ok(None),
+            None => {
+                let Some((cell, target)) = self.sparse_cell(component) else {
+                    return Ok(None);
+                };
+
+                let data = cell.data.try_borrow_mut()?;
+                return Ok(RefMut::new_pair(
+                    CellMutGuard::new(data),
+                    self.entities[slot],
+                    slot,
+                    target,
+                    tick,
+                ));
+            }
         };
 
         Ok(cell.get_mut(self.entities[slot], slot, tick))
@@ -487,6 +594,16 @@ impl Archetype {
 
         let data = cell.data.get_mut();
 
+        if slot >= data.storage.len() {
+            return None;
+        }
+
+        data.record_previous(
+            &self.entities[slot..=slot],
+            Slice::single(slot),
+            change_tick,
+        );
+
         let value = unsafe { data.storage.at_mut(slot)? };
         let value = (modify)(value);
 
@@ -523,7 +640,13 @@ impl Archetype {
         slot: Slot,
         component: Component<T>,
     ) -> Option<AtomicRef<T>> {
-        let cell = self.cell(component.key())?;
+        let Some(cell) = self.cell(component.key()) else {
+            let (cell, target) = self.sparse_cell(component)?;
+            return AtomicRef::filter_map(cell.data.borrow(), |v| {
+                v.storage.downcast_ref::<SparsePairs<T>>()[slot].get(target)
+            });
+        };
+
         unsafe { cell.get(slot) }
     }
 
@@ -535,7 +658,15 @@ impl Archetype {
     ) -> Result<Option<AtomicRef<T>>, BorrowError> {
         let cell = match self.cell(component.key()) {
             Some(v) => v,
-            None => return Ok(None),
+            None => {
+                let Some((cell, target)) = self.sparse_cell(component) else {
+                    return Ok(None);
+                };
+
+                return Ok(AtomicRef::filter_map(cell.data.try_borrow()?, |v| {
+                    v.storage.downcast_ref::<SparsePairs<T>>()[slot].get(target)
+                }));
+            }
         };
         unsafe { cell.try_get(slot) }
     }
@@ -675,12 +806,12 @@ impl Archetype {
             let dst_cell = dst.cell_mut(key);
 
             if let Some(dst_cell) = dst_cell {
-                cell.move_to(slot, dst_cell, dst_slot);
+                cell.move_to(id, slot, dst_cell, dst_slot);
             } else {
                 // Notify the subscribers that the component was removed
                 data.set_removed(&[id], Slice::single(slot));
 
-                cell.take(slot, &mut on_drop);
+                cell.take(id, slot, &mut on_drop);
             }
         }
 
@@ -713,7 +844,7 @@ impl Archetype {
             // data.on_event(&self.entities, Slice::single(slot), EventKind::Removed);
             data.set_removed(&[id], Slice::single(slot));
 
-            cell.take(slot, &mut on_move)
+            cell.take(id, slot, &mut on_move)
         }
 
         self.remove_slot(slot)
@@ -761,7 +892,7 @@ impl Archetype {
 
             if let Some(dst) = dst_cell {
                 assert_eq!(data.storage.len(), len);
-                cell.move_all(dst, dst_slots.start);
+                cell.move_all(&entities, dst, dst_slots.start);
                 // let dst_changes = dst.changes.get_mut();
 
                 // // Move the changes of all slots
@@ -0,0 +1,62 @@
+use alloc::{boxed::Box, collections::BTreeMap};
+use core::any::Any;
+
+use crate::{metadata::TrackPrevious, Entity};
+
+use super::{Slice, Storage};
+
+struct Recorded {
+    tick: u32,
+    value: Box<dyn Any + Send + Sync>,
+}
+
+/// Holds the values of a cell from before they were last modified.
+///
+/// Values are keyed by entity rather than slot so that they survive swap removals and moves
+/// between archetypes.
+pub(crate) struct PreviousValues {
+    track: TrackPrevious,
+    values: BTreeMap<Entity, Recorded>,
+}
+
+impl PreviousValues {
+    pub(crate) fn new(track: TrackPrevious) -> Self {
+        Self {
+            track,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Stores the current value of each slot, unless it was already stored this tick.
+    ///
+    /// Must be called *before* the storage is written to.
+    pub(crate) fn record(&mut self, storage: &Storage, ids: &[Entity], slots: Slice, tick: u32) {
+        for (&id, slot) in ids.iter().zip(slots.iter()) {
+            if self.values.get(&id).map(|v| v.tick) == Some(tick) {
+                continue;
+            }
+
+            let value = (self.track.clone_slot)(storage, slot);
+            self.values.insert(id, Recorded { tick, value });
+        }
+    }
+
+    pub(crate) fn get<T: 'static>(&self, id: Entity) -> Option<&T> {
+        self.values.get(&id)?.value.downcast_ref::<T>()
+    }
+
+    /// Moves the previous value of `id` to another cell
+    pub(crate) fn move_to(&mut self, id: Entity, dst: &mut Option<Self>) {
+        if let (Some(value), Some(dst)) = (self.values.remove(&id), dst) {
+            dst.values.insert(id, value);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: Entity) {
+        self.values.remove(&id);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+    }
+}
@@ -143,6 +143,15 @@ impl Storage {
         }
     }
 
+    #[inline(always)]
+    pub(crate) unsafe fn at(&self, slot: Slot) -> Option<*const u8> {
+        if slot >= self.len {
+            None
+        } else {
+            Some(self.data.as_ptr().add(self.desc.size() * slot))
+        }
+    }
+
     #[inline(always)]
     pub(crate) unsafe fn extend(&mut self, src: *mut u8, len: usize) {
         self.reserve(len);
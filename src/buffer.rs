@@ -258,6 +258,13 @@ impl ComponentBuffer {
         self.entries.values().map(|v| &v.0)
     }
 
+    /// Returns the components in the buffer along with a pointer to their values
+    pub(crate) fn iter_dyn(&self) -> impl Iterator<Item = (ComponentDesc, *const u8)> + '_ {
+        self.entries
+            .values()
+            .map(|&(desc, offset)| (desc, unsafe { self.storage.at(offset) }))
+    }
+
     /// Remove a component from the component buffer
     pub fn remove<T: ComponentValue>(&mut self, component: Component<T>) -> Option<T> {
         let (_, offset) = self.entries.remove(&component.key())?;
@@ -342,6 +349,24 @@ impl ComponentBuffer {
         self.entries.is_empty()
     }
 
+    /// Moves the pairs of [`Sparse`](crate::metadata::Sparse) relations into a separate buffer
+    pub(crate) fn take_sparse(&mut self) -> ComponentBuffer {
+        let mut sparse = ComponentBuffer::new();
+
+        unsafe {
+            self.retain(|desc, src| {
+                if metadata::sparse_relation(desc).is_some() {
+                    sparse.set_dyn(desc, src);
+                    false
+                } else {
+                    true
+                }
+            })
+        }
+
+        sparse
+    }
+
     /// Retains only the components specified by the predicate
     /// If the closure returns true the element is removed and **not** dropped at the end of
     /// collection
@@ -403,6 +428,21 @@ impl MultiComponentBuffer {
         offset
     }
 
+    /// Moves a type erased value into the buffer
+    pub unsafe fn push_dyn(&mut self, desc: ComponentDesc, value: *mut u8) -> Offset {
+        let offset = self.storage.allocate(desc.layout());
+        self.storage.write_dyn(offset, desc, value);
+
+        let old = self.drops.insert(offset, desc.vtable.drop);
+
+        assert!(old.is_none());
+        offset
+    }
+
+    pub unsafe fn at(&self, offset: Offset) -> *const u8 {
+        self.storage.at(offset)
+    }
+
     pub unsafe fn take_dyn(&mut self, offset: Offset) -> *mut u8 {
         self.drops.remove(&offset).unwrap();
         self.storage.at_mut(offset)
@@ -1,19 +1,29 @@
-use core::fmt;
+mod par;
+mod validate;
 
-use alloc::{boxed::Box, format, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    mem,
+};
+
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 use anyhow::Context;
 
 use crate::{
     buffer::MultiComponentBuffer,
     component::{ComponentDesc, ComponentValue},
     writer::{MissingDyn, SingleComponentWriter, WriteDedupDyn},
-    BatchSpawn, Component, Entity, EntityBuilder, World,
+    BatchSpawn, Component, Entity, EntityBuilder, Error, World,
 };
 
+pub use par::ParCmd;
+
+use self::validate::Validator;
+
 type DeferFn = Box<dyn Fn(&mut World) -> anyhow::Result<()> + Send + Sync>;
 
 /// A recorded action to be applied to the world.
-enum Command {
+pub(crate) enum Command {
     /// Spawn a new entity
     Spawn(EntityBuilder),
     AppendTo(EntityBuilder, Entity),
@@ -50,6 +60,98 @@ enum Command {
     Defer(DeferFn),
 }
 
+impl Command {
+    /// Applies the command to the world, taking the inserted values from `inserts`
+    fn apply(self, world: &mut World, inserts: &mut MultiComponentBuffer) -> anyhow::Result<()> {
+        match self {
+            Command::Spawn(mut entity) => {
+                entity
+                    .try_spawn(world)
+                    .map_err(|v| v.into_anyhow())
+                    .context("Failed to spawn entity")?;
+            }
+            Command::SpawnAt(mut entity, id) => {
+                entity
+                    .spawn_at(world, id)
+                    .map_err(|v| v.into_anyhow())
+                    .context("Failed to spawn entity")?;
+            }
+            Command::AppendTo(mut entity, id) => {
+                entity
+                    .append_to(world, id)
+                    .map_err(|v| v.into_anyhow())
+                    .context("Failed to append to entity")?;
+            }
+            Command::SpawnBatch(mut batch) => {
+                batch.spawn(world);
+            }
+            Command::SpawnBatchAt(mut batch, ids) => {
+                batch
+                    .spawn_at(world, &ids)
+                    .map_err(|v| v.into_anyhow())
+                    .context("Failed to spawn entity")?;
+            }
+            Command::Set { id, desc, offset } => unsafe {
+                let value = inserts.take_dyn(offset);
+                world
+                    .set_dyn(id, desc, value)
+                    .map_err(|v| v.into_anyhow())
+                    .with_context(|| format!("Failed to set component {}", desc.name()))?;
+            },
+            Command::SetDedup {
+                id,
+                desc,
+                offset,
+                cmp,
+            } => unsafe {
+                let value = inserts.take_dyn(offset);
+                world
+                    .ensure_unique(Some(id), [(desc, value.cast_const())])
+                    .and_then(|_| {
+                        world.set_with_writer(
+                            id,
+                            SingleComponentWriter::new(desc, WriteDedupDyn { value, cmp }),
+                        )
+                    })
+                    .map_err(|v| v.into_anyhow())
+                    .with_context(|| format!("Failed to set component {}", desc.name()))?;
+            },
+            Command::SetMissing { id, desc, offset } => unsafe {
+                let value = inserts.take_dyn(offset);
+                let missing = world.location(id).is_ok_and(|loc| {
+                    !world
+                        .archetypes
+                        .get(loc.arch_id)
+                        .has_at(loc.slot, desc.key())
+                });
+
+                if missing {
+                    world
+                        .ensure_unique(Some(id), [(desc, value.cast_const())])
+                        .map_err(|v| v.into_anyhow())
+                        .with_context(|| format!("Failed to set component {}", desc.name()))?;
+                }
+
+                world
+                    .set_with_writer(id, SingleComponentWriter::new(desc, MissingDyn { value }))
+                    .map_err(|v| v.into_anyhow())
+                    .with_context(|| format!("Failed to set component {}", desc.name()))?;
+            },
+            Command::Despawn(id) => world
+                .despawn(id)
+                .map_err(|v| v.into_anyhow())
+                .context("Failed to despawn entity")?,
+            Command::Remove { id, desc } => world
+                .remove_dyn(id, desc)
+                .map_err(|v| v.into_anyhow())
+                .with_context(|| format!("Failed to remove component {}", desc.name()))?,
+            Command::Defer(func) => func(world).context("Failed to execute deferred function")?,
+        }
+
+        Ok(())
+    }
+}
+
 impl fmt::Debug for Command {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -99,6 +201,52 @@ impl fmt::Debug for Command {
     }
 }
 
+/// A command which would fail when applied, as reported by [`CommandBuffer::apply_atomic`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    /// The index of the command in the order it was recorded
+    pub index: usize,
+    /// Why the command would fail
+    pub kind: CommandErrorKind,
+}
+
+/// The reason a command failed validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommandErrorKind {
+    /// The command would fail with the given error
+    Error(Error),
+    /// A deferred function can not be validated before it is executed
+    Deferred,
+    /// The command passed validation but failed when applied.
+    ///
+    /// The commands before it have been applied to the world.
+    Failed(String),
+}
+
+impl From<Error> for CommandErrorKind {
+    fn from(value: Error) -> Self {
+        Self::Error(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CommandError {}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            CommandErrorKind::Error(err) => write!(f, "Command {}: {err}", self.index),
+            CommandErrorKind::Deferred => write!(
+                f,
+                "Command {}: Deferred functions can not be applied atomically",
+                self.index
+            ),
+            CommandErrorKind::Failed(err) => write!(f, "Command {}: {err}", self.index),
+        }
+    }
+}
+
 /// Records commands into the world.
 /// Allows insertion and removal of components when the world is not available
 /// mutably, such as in systems or during iteration.
@@ -106,6 +254,7 @@ impl fmt::Debug for Command {
 pub struct CommandBuffer {
     inserts: MultiComponentBuffer,
     commands: Vec<Command>,
+    par: ParCmd,
 }
 
 impl fmt::Debug for CommandBuffer {
@@ -264,76 +413,76 @@ impl CommandBuffer {
         self
     }
 
+    /// Returns a queue which can record commands from multiple threads at once.
+    ///
+    /// The recorded commands are moved into this commandbuffer when it is applied.
+    pub fn par(&self) -> &ParCmd {
+        &self.par
+    }
+
+    /// Moves all commands from `other` into this commandbuffer, after the already recorded
+    /// commands.
+    pub fn append(&mut self, other: &mut CommandBuffer) -> &mut Self {
+        other.merge_par();
+
+        for cmd in other.commands.drain(..) {
+            unsafe { self.push_moved(cmd, &mut other.inserts) }
+        }
+
+        other.inserts.clear();
+        self
+    }
+
+    /// Moves the commands recorded through [`Self::par`] into the main command list
+    fn merge_par(&mut self) {
+        let mut par = mem::take(&mut self.par);
+        par.merge_into(self);
+        self.par = par;
+    }
+
+    /// Push a command recorded in another commandbuffer, moving any inserted value into this
+    /// buffer
+    ///
+    /// # Safety
+    /// `inserts` must be the buffer `cmd` was recorded with
+    unsafe fn push_moved(&mut self, cmd: Command, inserts: &mut MultiComponentBuffer) {
+        let cmd = match cmd {
+            Command::Set { id, desc, offset } => Command::Set {
+                id,
+                desc,
+                offset: self.inserts.push_dyn(desc, inserts.take_dyn(offset)),
+            },
+            Command::SetDedup {
+                id,
+                desc,
+                offset,
+                cmp,
+            } => Command::SetDedup {
+                id,
+                desc,
+                offset: self.inserts.push_dyn(desc, inserts.take_dyn(offset)),
+                cmp,
+            },
+            Command::SetMissing { id, desc, offset } => Command::SetMissing {
+                id,
+                desc,
+                offset: self.inserts.push_dyn(desc, inserts.take_dyn(offset)),
+            },
+            cmd => cmd,
+        };
+
+        self.commands.push(cmd);
+    }
+
     /// Applies all contents of the command buffer to the world.
     /// The commandbuffer is cleared and can be reused.
+    ///
+    /// Commands recorded through [`Self::par`] are applied after all other commands.
     pub fn apply(&mut self, world: &mut World) -> anyhow::Result<()> {
+        self.merge_par();
+
         for cmd in self.commands.drain(..) {
-            match cmd {
-                Command::Spawn(mut entity) => {
-                    entity.spawn(world);
-                }
-                Command::SpawnAt(mut entity, id) => {
-                    entity
-                        .spawn_at(world, id)
-                        .map_err(|v| v.into_anyhow())
-                        .context("Failed to spawn entity")?;
-                }
-                Command::AppendTo(mut entity, id) => {
-                    entity
-                        .append_to(world, id)
-                        .map_err(|v| v.into_anyhow())
-                        .context("Failed to append to entity")?;
-                }
-                Command::SpawnBatch(mut batch) => {
-                    batch.spawn(world);
-                }
-                Command::SpawnBatchAt(mut batch, ids) => {
-                    batch
-                        .spawn_at(world, &ids)
-                        .map_err(|v| v.into_anyhow())
-                        .context("Failed to spawn entity")?;
-                }
-                Command::Set { id, desc, offset } => unsafe {
-                    let value = self.inserts.take_dyn(offset);
-                    world
-                        .set_dyn(id, desc, value)
-                        .map_err(|v| v.into_anyhow())
-                        .with_context(|| format!("Failed to set component {}", desc.name()))?;
-                },
-                Command::SetDedup {
-                    id,
-                    desc,
-                    offset,
-                    cmp,
-                } => unsafe {
-                    let value = self.inserts.take_dyn(offset);
-                    world
-                        .set_with_writer(
-                            id,
-                            SingleComponentWriter::new(desc, WriteDedupDyn { value, cmp }),
-                        )
-                        .map_err(|v| v.into_anyhow())
-                        .with_context(|| format!("Failed to set component {}", desc.name()))?;
-                },
-                Command::SetMissing { id, desc, offset } => unsafe {
-                    let value = self.inserts.take_dyn(offset);
-                    world
-                        .set_with_writer(id, SingleComponentWriter::new(desc, MissingDyn { value }))
-                        .map_err(|v| v.into_anyhow())
-                        .with_context(|| format!("Failed to set component {}", desc.name()))?;
-                },
-                Command::Despawn(id) => world
-                    .despawn(id)
-                    .map_err(|v| v.into_anyhow())
-                    .context("Failed to despawn entity")?,
-                Command::Remove { id, desc } => world
-                    .remove_dyn(id, desc)
-                    .map_err(|v| v.into_anyhow())
-                    .with_context(|| format!("Failed to remove component {}", desc.name()))?,
-                Command::Defer(func) => {
-                    func(world).context("Failed to execute deferred function")?
-                }
-            }
+            cmd.apply(world, &mut self.inserts)?;
         }
 
         self.inserts.clear();
@@ -341,11 +490,83 @@ impl CommandBuffer {
         Ok(())
     }
 
+    /// Applies either all or none of the commands to the world.
+    ///
+    /// Every command is validated against the current state of the world before anything is
+    /// applied, such as modifying a despawned entity, removing a missing component, or spawning at
+    /// an occupied id. If any command would fail the world is left unchanged and all failing
+    /// commands are returned.
+    ///
+    /// Validation follows the metadata of the affected components, such as replacing the pairs
+    /// of an [`Exclusive`](crate::metadata::Exclusive) relation, [`Unique`](crate::metadata::Unique)
+    /// values, and the [`OnTargetDespawn`](crate::metadata::OnTargetDespawn) policy of relations,
+    /// including those held by entities and children spawned by earlier commands. Validated
+    /// commands therefore apply without failing.
+    ///
+    /// Deferred functions can not be validated ahead of time and are always rejected.
+    ///
+    /// Should a validated command still fail, which indicates a mismatch between validation and
+    /// application, the remaining commands are discarded and the failure is returned as
+    /// [`CommandErrorKind::Failed`].
+    ///
+    /// The commandbuffer is cleared regardless of the outcome.
+    pub fn apply_atomic(&mut self, world: &mut World) -> Result<(), Vec<CommandError>> {
+        world.flush_reserved();
+        self.merge_par();
+
+        let reserve = match Validator::new(world, &self.inserts).validate(&self.commands) {
+            Ok(v) => v,
+            Err(errors) => {
+                self.clear();
+                return Err(errors);
+            }
+        };
+
+        // Prevent new entities from taking the ids of later spawns
+        for id in reserve {
+            world.reserve_at(id).expect("Entity id is vacant");
+        }
+
+        let mut result = Ok(());
+        for (index, cmd) in self.commands.drain(..).enumerate() {
+            if let Err(err) = cmd.apply(world, &mut self.inserts) {
+                result = Err(alloc::vec![CommandError {
+                    index,
+                    kind: CommandErrorKind::Failed(format!("{err:#}")),
+                }]);
+                break;
+            }
+        }
+
+        self.clear();
+        result
+    }
+
+    /// Remove a component from `id` using a type erased component
+    pub(crate) fn remove_dyn(&mut self, id: Entity, desc: ComponentDesc) -> &mut Self {
+        self.commands.push(Command::Remove { id, desc });
+        self
+    }
+
+    /// Returns the recorded commands in order
+    pub(crate) fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Returns a pointer to a value inserted by a set command
+    ///
+    /// # Safety
+    /// The offset must belong to a command in this buffer
+    pub(crate) unsafe fn inserted(&self, offset: usize) -> *const u8 {
+        self.inserts.at(offset)
+    }
+
     /// Clears all values in the component buffer but keeps allocations around.
     /// Is automatically called for [`Self::apply`].
     pub fn clear(&mut self) {
         self.inserts.clear();
-        self.commands.clear()
+        self.commands.clear();
+        self.par.clear();
     }
 }
 
@@ -0,0 +1,141 @@
+use core::{fmt, mem, ops::Range};
+
+use alloc::{boxed::Box, vec::Vec};
+use atomic_refcell::AtomicRefCell;
+use once_cell::sync::OnceCell;
+
+use crate::{util::spin_lock, CommandBuffer, Entity};
+
+/// Commands recorded by a single thread
+#[derive(Default)]
+struct Queue {
+    cmd: CommandBuffer,
+    /// The commands recorded for each key, in the order they were recorded
+    segments: Vec<(Entity, Range<usize>)>,
+    /// Reused between recordings to avoid allocating for each key
+    spare: CommandBuffer,
+}
+
+/// A command queue which can be written to from multiple threads at once, such as inside
+/// [`QueryBorrow::par_for_each`](crate::query::QueryBorrow::par_for_each).
+///
+/// Each thread records into its own queue. Commands are recorded for a *key*, usually the entity
+/// being processed, and the queues are merged in the order of the keys when the owning
+/// [`CommandBuffer`] is applied. This makes the resulting order independent of how the work was
+/// distributed between threads.
+///
+/// Commands recorded for the same key on different threads are not guaranteed to keep a
+/// consistent order.
+#[derive(Default)]
+pub struct ParCmd {
+    queues: OnceCell<Box<[AtomicRefCell<Queue>]>>,
+}
+
+impl fmt::Debug for ParCmd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParCmd").finish_non_exhaustive()
+    }
+}
+
+impl ParCmd {
+    /// Record commands for `key` into the current thread's queue.
+    ///
+    /// The commands are applied after all commands already recorded directly in the owning
+    /// commandbuffer, ordered by `key`.
+    pub fn record<R>(&self, key: Entity, func: impl FnOnce(&mut CommandBuffer) -> R) -> R {
+        let queue = self.queue();
+
+        // The queue is not held while executing `func`, which may itself record commands.
+        let mut cmd = mem::take(&mut spin_lock(queue).spare);
+        let ret = func(&mut cmd);
+
+        let mut queue = spin_lock(queue);
+        let start = queue.cmd.commands.len();
+        queue.cmd.append(&mut cmd);
+        let end = queue.cmd.commands.len();
+
+        if start != end {
+            queue.segments.push((key, start..end));
+        }
+
+        queue.spare = cmd;
+        ret
+    }
+
+    /// Returns the queue for the current thread
+    fn queue(&self) -> &AtomicRefCell<Queue> {
+        let queues = self.queues.get_or_init(|| {
+            (0..thread_count())
+                .map(|_| AtomicRefCell::new(Queue::default()))
+                .collect()
+        });
+
+        &queues[thread_index() % queues.len()]
+    }
+
+    /// Moves all recorded commands into `dst` ordered by key
+    pub(crate) fn merge_into(&mut self, dst: &mut CommandBuffer) {
+        let Some(queues) = self.queues.get_mut() else {
+            return;
+        };
+
+        let mut segments = Vec::new();
+        let mut commands = Vec::with_capacity(queues.len());
+        for (index, queue) in queues.iter_mut().enumerate() {
+            let queue = queue.get_mut();
+            segments.extend(
+                queue
+                    .segments
+                    .drain(..)
+                    .map(|(key, range)| (key, index, range)),
+            );
+
+            commands.push(queue.cmd.commands.drain(..).map(Some).collect::<Vec<_>>());
+        }
+
+        // Stable sort preserves the recording order for each key
+        segments.sort_by_key(|&(key, _, _)| key);
+
+        for (_, index, range) in segments {
+            let src = &mut queues[index].get_mut().cmd;
+            for cmd in &mut commands[index][range] {
+                let cmd = cmd.take().expect("Command is only moved once");
+                unsafe { dst.push_moved(cmd, &mut src.inserts) }
+            }
+        }
+
+        for queue in queues.iter_mut() {
+            queue.get_mut().cmd.clear();
+        }
+    }
+
+    /// Discards all recorded commands
+    pub(crate) fn clear(&mut self) {
+        for queue in self.queues.get_mut().into_iter().flat_map(|v| v.iter_mut()) {
+            let queue = queue.get_mut();
+            queue.cmd.clear();
+            queue.segments.clear();
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn thread_count() -> usize {
+    // Threads outside of the pool use the first queue
+    rayon::current_num_threads() + 1
+}
+
+#[cfg(feature = "rayon")]
+fn thread_index() -> usize {
+    rayon::current_thread_index().map(|v| v + 1).unwrap_or(0)
+}
+
+#[cfg(not(feature = "rayon"))]
+fn thread_count() -> usize {
+    1
+}
+
+#[cfg(not(feature = "rayon"))]
+fn thread_index() -> usize {
+    0
+}
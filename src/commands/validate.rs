@@ -0,0 +1,512 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use crate::{
+    buffer::MultiComponentBuffer,
+    component::{ComponentDesc, ComponentKey},
+    components::component_info,
+    entity::{EntityIndex, EntityKind, DEFAULT_GEN},
+    error::{MissingComponent, Result},
+    metadata::{exclusive, on_target_despawn, TargetDespawnPolicy, UniquePolicy},
+    Entity, EntityBuilder, Error, World,
+};
+
+use super::{Command, CommandError, CommandErrorKind};
+
+/// Kind of the placeholder ids given to entities spawned without a known id.
+///
+/// Not used by any entity in the world.
+const ANONYMOUS: EntityKind = EntityKind::from_bits_retain(1 << 15);
+
+/// The predicted state of an entity slot
+enum SlotState {
+    Vacant,
+    Alive {
+        id: Entity,
+        components: BTreeSet<ComponentKey>,
+        /// The id was reserved but not yet spawned, which allows spawning at it
+        reserved: bool,
+    },
+    /// Occupied by an entity which id is not known until the commands are applied
+    Unknown,
+}
+
+/// A value of a unique component set by a command
+struct Claim {
+    id: Entity,
+    desc: ComponentDesc,
+    value: *const u8,
+}
+
+/// Predicts the outcome of applying a list of commands without modifying the world.
+pub(super) struct Validator<'a> {
+    world: &'a World,
+    inserts: &'a MultiComponentBuffer,
+    slots: BTreeMap<(EntityKind, EntityIndex), SlotState>,
+    /// Indices freed by despawning, in the order they will be reused by new entities
+    freed: BTreeMap<EntityKind, Vec<EntityIndex>>,
+    despawned: BTreeSet<Entity>,
+    /// Explicit ids which are vacant in the world and need to be reserved before applying
+    reserve: BTreeSet<Entity>,
+    claims: Vec<Claim>,
+    /// Relations set by commands, which may not yet be known to the world
+    relations: BTreeMap<Entity, ComponentDesc>,
+    /// Number of entities spawned without a known id
+    anonymous: EntityIndex,
+}
+
+impl<'a> Validator<'a> {
+    pub(super) fn new(world: &'a World, inserts: &'a MultiComponentBuffer) -> Self {
+        Self {
+            world,
+            inserts,
+            slots: BTreeMap::new(),
+            freed: BTreeMap::new(),
+            despawned: BTreeSet::new(),
+            reserve: BTreeSet::new(),
+            claims: Vec::new(),
+            relations: BTreeMap::new(),
+            anonymous: 0,
+        }
+    }
+
+    /// Validates all commands, returning the ids which must be reserved before applying.
+    pub(super) fn validate(
+        mut self,
+        commands: &[Command],
+    ) -> core::result::Result<BTreeSet<Entity>, Vec<CommandError>> {
+        let errors: Vec<_> = commands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cmd)| {
+                self.validate_command(cmd)
+                    .err()
+                    .map(|kind| CommandError { index, kind })
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(self.reserve)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_command(&mut self, cmd: &Command) -> core::result::Result<(), CommandErrorKind> {
+        match cmd {
+            Command::Spawn(builder)
+            | Command::SpawnAt(builder, _)
+            | Command::AppendTo(builder, _) => self.record_relations(builder.components().copied()),
+            Command::SpawnBatchAt(batch, _) => self.record_relations(batch.components()),
+            Command::Set { desc, .. }
+            | Command::SetDedup { desc, .. }
+            | Command::SetMissing { desc, .. } => self.record_relations([*desc]),
+            _ => {}
+        }
+
+        match cmd {
+            Command::Spawn(builder) => {
+                self.spawn(builder.spawn_count());
+
+                let id = self.anonymous(builder.components().copied());
+                self.spawn_builder(id, builder)?;
+            }
+            Command::SpawnAt(builder, id) => {
+                self.spawn_at(*id, builder.components())?;
+                self.spawn(builder.spawn_count() - 1);
+                self.spawn_builder(*id, builder)?;
+            }
+            Command::AppendTo(builder, id) => {
+                for &desc in builder.components() {
+                    self.insert(*id, desc)?;
+                }
+
+                self.spawn(builder.spawn_count() - 1);
+                self.spawn_builder(*id, builder)?;
+            }
+            Command::SpawnBatch(batch) => {
+                self.spawn(batch.len());
+
+                // Only relations affect later commands, through the despawn policy of the target
+                if batch.components().any(|v| v.key().is_relation()) {
+                    for _ in 0..batch.len() {
+                        self.anonymous(batch.components());
+                    }
+                }
+            }
+            Command::SpawnBatchAt(batch, ids) => {
+                if ids.len() != batch.len() {
+                    return Err(Error::IncompleteBatch.into());
+                }
+
+                let components: Vec<_> = batch.components().collect();
+                for &id in ids {
+                    self.spawn_at(id, components.iter())?;
+                }
+            }
+            Command::Set { id, desc, offset }
+            | Command::SetDedup {
+                id, desc, offset, ..
+            } => {
+                self.alive(*id)?;
+                self.claim_unique(*id, *desc, unsafe { self.inserts.at(*offset) })?;
+                self.insert(*id, *desc)?;
+            }
+            Command::SetMissing { id, desc, offset } => {
+                if !self.alive(*id)?.contains(&desc.key()) {
+                    self.claim_unique(*id, *desc, unsafe { self.inserts.at(*offset) })?;
+                    self.insert(*id, *desc)?;
+                }
+            }
+            Command::Despawn(id) => self.despawn(*id)?,
+            Command::Remove { id, desc } => self.remove(*id, *desc)?,
+            Command::Defer(_) => return Err(CommandErrorKind::Deferred),
+        }
+
+        Ok(())
+    }
+
+    fn slot(&mut self, id: Entity) -> &mut SlotState {
+        let world = self.world;
+        self.slots
+            .entry((id.kind(), id.index()))
+            .or_insert_with(|| match world.reconstruct(id.index(), id.kind()) {
+                Some(current) => {
+                    let loc = world.location(current).unwrap();
+                    let arch = world.archetypes.get(loc.arch_id);
+
+                    let mut components: BTreeSet<_> = arch.components().keys().copied().collect();
+
+                    // Pairs of sparse relations are stored in a single column
+                    for key in arch.components().keys().filter(|v| !v.is_relation()) {
+                        if let Some(targets) = arch.sparse_targets(key.id) {
+                            components.extend(
+                                targets
+                                    .get(loc.slot)
+                                    .iter()
+                                    .map(|&target| ComponentKey::new(key.id, Some(target))),
+                            );
+                        }
+                    }
+
+                    SlotState::Alive {
+                        id: current,
+                        components,
+                        reserved: world.is_reserved(current),
+                    }
+                }
+                None => SlotState::Vacant,
+            })
+    }
+
+    /// Returns the components of an alive entity
+    fn alive(&mut self, id: Entity) -> Result<&mut BTreeSet<ComponentKey>> {
+        let slot = self.slot(id);
+
+        // Static entities are lazily spawned on first use
+        if matches!(slot, SlotState::Vacant) && id.is_static() {
+            *slot = SlotState::Alive {
+                id,
+                components: BTreeSet::new(),
+                reserved: false,
+            };
+        }
+
+        match slot {
+            SlotState::Alive {
+                id: current,
+                components,
+                ..
+            } if *current == id => Ok(components),
+            _ => Err(Error::NoSuchEntity(id)),
+        }
+    }
+
+    /// Adds a component to an alive entity, replacing other pairs of an exclusive relation
+    fn insert(&mut self, id: Entity, desc: ComponentDesc) -> Result<()> {
+        let key = desc.key();
+        let components = self.alive(id)?;
+
+        if key.is_relation() && desc.meta_ref().has(exclusive()) {
+            components.retain(|v| v.id != key.id || *v == key);
+        }
+
+        components.insert(key);
+        Ok(())
+    }
+
+    fn spawn_at<'c>(
+        &mut self,
+        id: Entity,
+        components: impl Iterator<Item = &'c ComponentDesc>,
+    ) -> Result<()> {
+        let vacant = match self.slot(id) {
+            SlotState::Vacant => true,
+            SlotState::Alive { reserved: true, .. } => false,
+            SlotState::Alive { id: current, .. } => return Err(Error::EntityOccupied(*current)),
+            SlotState::Unknown => return Err(Error::EntityOccupied(id)),
+        };
+
+        // Spawning at a specific index takes it from the free list
+        let freed = self.freed.entry(id.kind()).or_default();
+        if let Some(pos) = freed.iter().position(|&v| v == id.index()) {
+            freed.swap_remove(pos);
+        } else if vacant {
+            // Vacant in the world, which must be reserved to not be taken by a new entity
+            // before this command is applied
+            self.reserve.insert(id);
+        }
+
+        *self.slot(id) = SlotState::Alive {
+            id,
+            components: components.map(|v| v.key()).collect(),
+            reserved: false,
+        };
+
+        Ok(())
+    }
+
+    /// Predicts an entity spawned without a known id, such as by [`Command::Spawn`] or as a child.
+    ///
+    /// The entity is given a placeholder id which only exists in the validator.
+    fn anonymous(&mut self, components: impl IntoIterator<Item = ComponentDesc>) -> Entity {
+        let id = Entity::from_parts(self.anonymous, DEFAULT_GEN, ANONYMOUS);
+        self.anonymous += 1;
+
+        let mut keys = BTreeSet::new();
+        for desc in components {
+            self.record_relations([desc]);
+            keys.insert(desc.key());
+        }
+
+        self.slots.insert(
+            (id.kind(), id.index()),
+            SlotState::Alive {
+                id,
+                components: keys,
+                reserved: false,
+            },
+        );
+
+        id
+    }
+
+    /// Claims the unique values of a builder spawned or appended to `id`, and spawns its
+    /// children.
+    fn spawn_builder(&mut self, id: Entity, builder: &EntityBuilder) -> Result<()> {
+        for (desc, value) in builder.buffer().iter_dyn() {
+            self.claim_unique(id, desc, value)?;
+        }
+
+        for (relation, child) in builder.children(id) {
+            let child_id = self.anonymous(child.components().copied());
+            self.record_relations([relation]);
+            self.insert(child_id, relation)?;
+            self.spawn_builder(child_id, child)?;
+        }
+
+        Ok(())
+    }
+
+    /// Spawns `count` new entities, which may reuse previously despawned indices
+    fn spawn(&mut self, count: usize) {
+        let kind = EntityKind::empty();
+        for _ in 0..count {
+            match self.freed.get_mut(&kind).and_then(|v| v.pop()) {
+                Some(index) => {
+                    self.slots.insert((kind, index), SlotState::Unknown);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn despawn(&mut self, id: Entity) -> Result<()> {
+        self.alive(id)?;
+
+        // Subjects which are despawned along with the target, according to the predicted
+        // relations rather than those currently in the world
+        let mut despawned = alloc::vec![id];
+
+        let mut index = 0;
+        while let Some(&target) = despawned.get(index) {
+            index += 1;
+
+            for subject in self.subjects(target) {
+                let Ok(components) = self.alive(subject) else {
+                    continue;
+                };
+
+                let relations: Vec<_> = components
+                    .iter()
+                    .filter(|v| v.target() == Some(target))
+                    .copied()
+                    .collect();
+
+                for key in relations {
+                    let Some(desc) = self.relation_desc(key) else {
+                        continue;
+                    };
+
+                    let policy = desc
+                        .meta_ref()
+                        .get(on_target_despawn())
+                        .copied()
+                        .unwrap_or_default();
+
+                    match policy {
+                        TargetDespawnPolicy::Remove => {}
+                        TargetDespawnPolicy::DespawnSubject => {
+                            if !despawned.contains(&subject) {
+                                despawned.push(subject);
+                            }
+                        }
+                        TargetDespawnPolicy::Error => return Err(Error::TargetInUse(target, desc)),
+                        TargetDespawnPolicy::Panic => {
+                            panic!(
+                                "Attempt to despawn {target} which is the target of relation {desc:?}"
+                            )
+                        }
+                    }
+                }
+            }
+        }
+
+        for id in despawned {
+            if self.alive(id).is_err() {
+                continue;
+            }
+
+            self.slots
+                .insert((id.kind(), id.index()), SlotState::Vacant);
+            if id.kind() != ANONYMOUS {
+                self.freed.entry(id.kind()).or_default().push(id.index());
+            }
+            self.despawned.insert(id);
+        }
+
+        Ok(())
+    }
+
+    fn remove(&mut self, id: Entity, desc: ComponentDesc) -> Result<()> {
+        let key = desc.key();
+
+        // Relations targeting a despawned entity are removed with it
+        let target_despawned = key
+            .target()
+            .is_some_and(|target| self.despawned.contains(&target));
+
+        let components = self.alive(id)?;
+        if target_despawned || !components.remove(&key) {
+            return Err(Error::MissingComponent(MissingComponent { id, desc }));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entities which may hold a relation targeting `target`, either in the world or
+    /// as set by previous commands.
+    fn subjects(&self, target: Entity) -> BTreeSet<Entity> {
+        let world = self.world;
+
+        let mut subjects: BTreeSet<_> = world
+            .archetypes
+            .index
+            .find_relation_targets(target)
+            .into_iter()
+            .flat_map(|v| v.keys())
+            .flat_map(|&arch_id| world.archetypes.get(arch_id).entities().iter().copied())
+            .collect();
+
+        subjects.extend(world.sparse_subjects(target).map(|(_, id)| id));
+
+        subjects.extend(self.slots.values().filter_map(|slot| match slot {
+            SlotState::Alive { id, components, .. }
+                if components.iter().any(|v| v.target() == Some(target)) =>
+            {
+                Some(*id)
+            }
+            _ => None,
+        }));
+
+        subjects
+    }
+
+    fn record_relations(&mut self, descs: impl IntoIterator<Item = ComponentDesc>) {
+        for desc in descs {
+            if desc.key().is_relation() {
+                self.relations.entry(desc.key().id).or_insert(desc);
+            }
+        }
+    }
+
+    /// Returns the descriptor of the relation `key`, with the target of `key`
+    fn relation_desc(&self, key: ComponentKey) -> Option<ComponentDesc> {
+        self.relations
+            .get(&key.id)
+            .copied()
+            .or_else(|| self.world.get(key.id, component_info()).ok().map(|v| *v))
+            .map(|v| ComponentDesc { key, ..v })
+    }
+
+    /// Returns true if `id` is predicted to have the component `key`
+    fn holds(&mut self, id: Entity, key: ComponentKey) -> bool {
+        self.alive(id).is_ok_and(|v| v.contains(&key))
+    }
+
+    /// Resolves the unique constraint of `desc` against the values held in the world and the
+    /// values set by previous commands.
+    fn claim_unique(&mut self, id: Entity, desc: ComponentDesc, value: *const u8) -> Result<()> {
+        let Some(&constraint) = desc.unique_constraint() else {
+            return Ok(());
+        };
+
+        let key = desc.key();
+
+        let mut conflicts: Vec<_> = unsafe {
+            self.world
+                .unique_conflicts(Some(id), desc, &constraint, value)
+        }
+        .into_iter()
+        // Values which are replaced by a previous command are compared through the claim
+        .filter(|&holder| {
+            !self
+                .claims
+                .iter()
+                .any(|v| v.id == holder && v.desc.key() == key)
+        })
+        .collect();
+
+        conflicts.extend(
+            self.claims
+                .iter()
+                .filter(|v| {
+                    v.desc.key() == key
+                        && v.id != id
+                        && unsafe { constraint.conflicts(value, v.value) }
+                })
+                .map(|v| v.id),
+        );
+
+        conflicts.retain(|&holder| self.holds(holder, key));
+
+        if constraint.policy == UniquePolicy::Error {
+            if let Some(&holder) = conflicts.first() {
+                let holder = Some(holder).filter(|v| v.kind() != ANONYMOUS);
+                return Err(Error::NotUnique(desc, holder));
+            }
+        }
+
+        // The stolen values are removed from their holders
+        for holder in conflicts {
+            self.alive(holder)?.remove(&key);
+        }
+
+        self.claims.retain(|v| v.id != id || v.desc.key() != key);
+        self.claims.push(Claim { id, desc, value });
+
+        Ok(())
+    }
+}
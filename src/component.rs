@@ -20,7 +20,7 @@ use crate::{
     entity::EntityKind,
     fetch::MaybeMut,
     filter::{ChangeFilter, With, WithRelation, Without, WithoutRelation},
-    metadata::Metadata,
+    metadata::{Metadata, UniqueConstraint},
     relation::RelationExt,
     vtable::{ComponentVTable, UntypedVTable},
     Entity, Mutable,
@@ -453,6 +453,17 @@ impl ComponentDesc {
     pub(crate) fn meta_ref(&self) -> &ComponentBuffer {
         self.vtable.meta.get_ref(*self)
     }
+
+    /// Returns the [`unique`](crate::metadata::unique) constraint of the component, if any
+    #[inline]
+    pub(crate) fn unique_constraint(&self) -> Option<&UniqueConstraint> {
+        self.vtable.meta.unique(*self)
+    }
+
+    /// Returns the metadata attached to the component, such as [`Debuggable`](crate::Debuggable)
+    pub fn metadata(&self) -> &ComponentBuffer {
+        self.meta_ref()
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,7 @@
 use alloc::string::String;
 
 use crate::component;
+use crate::metadata::{despawn, OnTargetDespawn};
 use crate::Exclusive;
 
 use crate::component::ComponentDesc;
@@ -19,7 +20,16 @@ component! {
     ///
     /// Only one parent can exist for an entity. Adding a second relationship will override the
     /// existing one, effectively moving the subtree.
-    pub child_of(parent): () => [ Debuggable, Exclusive ],
+    ///
+    /// Despawning the parent despawns the children as well.
+    pub child_of(parent): () => [ Debuggable, Exclusive, OnTargetDespawn<despawn::DespawnSubject> ],
+
+    /// Inheritance relation, declaring the target as a prototype of the subject.
+    ///
+    /// Fetches transformed using [`FetchExt::inherited`](crate::FetchExt::inherited) fall back to
+    /// the components of the prototype, recursively, when the instance lacks them. Setting a
+    /// component on the instance overrides the inherited value.
+    pub is_a(prototype): () => [ Debuggable ],
 
     /// Contains type erased metadata.
     ///
@@ -27,6 +37,11 @@ component! {
     /// This is the basis of the reflection provided by flax
     pub component_info: ComponentDesc => [ Debuggable ],
 
+    /// Excludes an entity from queries without removing any of its components.
+    ///
+    /// See [`World::disable`](crate::World::disable)
+    pub disabled: () => [ Debuggable ],
+
     /// Added automatically to all STATIC entities
     pub is_static: () => [ Debuggable ],
 
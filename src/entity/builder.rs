@@ -1,6 +1,6 @@
 use crate::{
     buffer::ComponentBuffer,
-    component::{ComponentDesc, ComponentValue},
+    component::{dummy, ComponentDesc, ComponentKey, ComponentValue},
     error::Result,
     relation::RelationExt,
     CommandBuffer, Component, Entity, World,
@@ -10,6 +10,8 @@ use alloc::{boxed::Box, vec::Vec};
 type ModifyFunc = Box<dyn FnOnce(Entity, &mut EntityBuilder) + Send + Sync>;
 struct Child {
     builder: EntityBuilder,
+    /// The relation set on the child, with a placeholder target
+    relation: ComponentDesc,
     modify: ModifyFunc,
 }
 
@@ -20,9 +22,9 @@ impl core::fmt::Debug for Child {
 }
 
 impl Child {
-    fn spawn(mut self, world: &mut World, parent: Entity) -> Entity {
+    fn spawn(mut self, world: &mut World, parent: Entity) -> Result<Entity> {
         (self.modify)(parent, &mut self.builder);
-        self.builder.spawn(world)
+        self.builder.try_spawn(world)
     }
 }
 
@@ -128,6 +130,7 @@ impl EntityBuilder {
     ) -> &mut Self {
         self.children.push(Child {
             builder: other.into(),
+            relation: relation.of(dummy()).desc(),
             modify: Box::new(move |parent, builder| {
                 builder.set(relation.of(parent), value);
             }),
@@ -148,15 +151,31 @@ impl EntityBuilder {
     ///
     /// Clears the builder and allows it to be used again, reusing the builder
     /// will reuse the inner storage, even for different components.
+    ///
+    /// # Panics
+    /// If a [`Unique`](crate::metadata::Unique) constraint refuses the entity. See
+    /// [`Self::try_spawn`].
     pub fn spawn(&mut self, world: &mut World) -> Entity {
+        match self.try_spawn(world) {
+            Ok(id) => id,
+            Err(err) => panic!("Failed to spawn entity: {err}"),
+        }
+    }
+
+    /// See: [`Self::spawn`]
+    ///
+    /// Fails if a [`Unique`](crate::metadata::Unique) or
+    /// [`UniqueValue`](crate::metadata::UniqueValue) constraint refuses the entity or any of its
+    /// children.
+    pub fn try_spawn(&mut self, world: &mut World) -> Result<Entity> {
         profile_function!();
-        let id = world.spawn_with(&mut self.buffer);
+        let id = world.spawn_with(&mut self.buffer)?;
 
-        self.children.drain(..).for_each(|child| {
-            child.spawn(world, id);
-        });
+        for child in self.children.drain(..) {
+            child.spawn(world, id)?;
+        }
 
-        id
+        Ok(id)
     }
 
     /// See: [`Self::spawn`]
@@ -167,9 +186,9 @@ impl EntityBuilder {
     pub fn spawn_at(&mut self, world: &mut World, id: Entity) -> Result<Entity> {
         let (id, _) = world.spawn_at_with(id, &mut self.buffer)?;
 
-        self.children.drain(..).for_each(|child| {
-            child.spawn(world, id);
-        });
+        for child in self.children.drain(..) {
+            child.spawn(world, id)?;
+        }
 
         Ok(id)
     }
@@ -181,9 +200,9 @@ impl EntityBuilder {
         profile_function!();
         world.set_with(id, &mut self.buffer)?;
 
-        self.children.drain(..).for_each(|child| {
-            child.spawn(world, id);
-        });
+        for child in self.children.drain(..) {
+            child.spawn(world, id)?;
+        }
 
         Ok(id)
     }
@@ -198,6 +217,41 @@ impl EntityBuilder {
         self.buffer.len()
     }
 
+    /// Returns the components in the builder
+    pub(crate) fn components(&self) -> impl Iterator<Item = &ComponentDesc> {
+        self.buffer.components()
+    }
+
+    pub(crate) fn buffer(&self) -> &ComponentBuffer {
+        &self.buffer
+    }
+
+    /// Returns the attached children along with the relation they will have to `parent`
+    pub(crate) fn children(
+        &self,
+        parent: Entity,
+    ) -> impl Iterator<Item = (ComponentDesc, &EntityBuilder)> {
+        self.children.iter().map(move |child| {
+            let key = ComponentKey::new(child.relation.key().id, Some(parent));
+            (
+                ComponentDesc {
+                    key,
+                    ..child.relation
+                },
+                &child.builder,
+            )
+        })
+    }
+
+    /// Returns the number of entities the builder will spawn, including attached children
+    pub(crate) fn spawn_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(|v| v.builder.spawn_count())
+            .sum::<usize>()
+    }
+
     /// Returns true if the builder does not contain any components
     #[must_use]
     #[inline]
@@ -0,0 +1,51 @@
+use alloc::vec::Vec;
+
+use crate::{component::ComponentDesc, error::Result, BatchSpawn, Entity, EntityBuilder, World};
+
+/// A set of components which are spawned and removed together, represented as a struct.
+///
+/// Usually implemented using `#[derive(Bundle)]`, where each field maps to a component.
+///
+/// ```rust,ignore
+/// component! {
+///     health: f32,
+///     position: (f32, f32),
+/// }
+///
+/// #[derive(Bundle)]
+/// struct Player {
+///     health: f32,
+///     #[bundle(component = position())]
+///     pos: (f32, f32),
+/// }
+///
+/// let mut world = World::new();
+/// let id = EntityBuilder::from(Player { health: 100.0, pos: (1.0, 2.0) }).spawn(&mut world);
+///
+/// let player: Player = world.remove_bundle(id).unwrap();
+/// assert_eq!(player.health, 100.0);
+/// assert!(!world.has(id, position()));
+/// ```
+///
+/// Fields without an attribute use the component function of the same name.
+pub trait Bundle: Sized {
+    /// Returns the components of the bundle
+    fn components() -> Vec<ComponentDesc>;
+
+    /// Sets the components of the bundle in the builder
+    fn append_to(self, builder: &mut EntityBuilder);
+
+    /// Creates a batch containing a column for each component of the bundle
+    fn into_batch(items: Vec<Self>) -> BatchSpawn;
+
+    /// Removes the components of the bundle from an entity.
+    ///
+    /// Prefer [`World::remove_bundle`], which does not remove anything if a component is missing.
+    fn remove_from(world: &mut World, id: Entity) -> Result<Self>;
+}
+
+impl<B: Bundle> From<Vec<B>> for BatchSpawn {
+    fn from(items: Vec<B>) -> Self {
+        B::into_batch(items)
+    }
+}
@@ -1,4 +1,5 @@
 mod builder;
+mod bundle;
 mod store;
 
 use core::fmt;
@@ -6,6 +7,7 @@ use core::num::NonZeroU16;
 use core::sync::atomic::{AtomicU32, Ordering};
 
 pub use builder::*;
+pub use bundle::Bundle;
 pub(crate) use store::*;
 
 use crate::EntityIds;
@@ -69,10 +69,11 @@ impl<'a> EntityRefMut<'a> {
     /// Check if the entity currently has the specified component without
     /// borrowing.
     pub fn has<T: ComponentValue>(&self, component: Component<T>) -> bool {
+        let loc = self.loc();
         self.world
             .archetypes
-            .get(self.loc().arch_id)
-            .has(component.key())
+            .get(loc.arch_id)
+            .has_at(loc.slot, component.key())
     }
 
     /// Updates a component in place
@@ -156,6 +157,7 @@ impl<'a> EntityRefMut<'a> {
 
     /// Set a component for the entity
     pub fn set<T: ComponentValue>(&mut self, component: Component<T>, value: T) -> Option<T> {
+        self.ensure_unique(component, &value);
         self.set_with_writer(SingleComponentWriter::new(
             component.desc(),
             Replace::new(value),
@@ -167,6 +169,10 @@ impl<'a> EntityRefMut<'a> {
     ///
     /// Does not disturb or generate a change event if the component is present
     pub fn set_missing<T: ComponentValue>(&mut self, component: Component<T>, value: T) -> bool {
+        if !self.has(component) {
+            self.ensure_unique(component, &value);
+        }
+
         self.set_with_writer(SingleComponentWriter::new(
             component.desc(),
             Missing { value },
@@ -178,6 +184,7 @@ impl<'a> EntityRefMut<'a> {
     ///
     /// Does not trigger a modification event if the value is the same
     pub fn set_dedup<T: ComponentValue + PartialEq>(&mut self, component: Component<T>, value: T) {
+        self.ensure_unique(component, &value);
         self.set_with_writer(SingleComponentWriter::new(
             component.desc(),
             WriteDedup::new(value),
@@ -196,6 +203,20 @@ impl<'a> EntityRefMut<'a> {
         self
     }
 
+    /// Resolves the unique constraint of `component` before setting it.
+    ///
+    /// Panics if the constraint refuses the value.
+    fn ensure_unique<T: ComponentValue>(&mut self, component: Component<T>, value: &T) {
+        unsafe {
+            self.world
+                .ensure_unique(
+                    Some(self.id),
+                    [(component.desc(), (value as *const T).cast())],
+                )
+                .unwrap()
+        }
+    }
+
     /// Set a component for the entity
     pub(crate) fn set_with_writer<W: EntityWriter>(&mut self, writer: W) -> W::Output {
         let (loc, res) = self.world.set_with_writer(self.id, writer).unwrap();
@@ -368,7 +389,7 @@ impl<'a> EntityRef<'a> {
     /// Check if the entity currently has the specified component without
     /// borrowing.
     pub fn has<T: ComponentValue>(&self, component: Component<T>) -> bool {
-        self.arch.has(component.key())
+        self.arch.has_at(self.loc.slot, component.key())
     }
 
     /// Updates a component in place
@@ -25,6 +25,15 @@ pub struct VacantEntry<'a, T: ComponentValue> {
 impl<'a, T: ComponentValue> VacantEntry<'a, T> {
     /// Insert a value into the entry, returning a mutable reference to it
     pub fn insert(self, value: T) -> RefMut<'a, T> {
+        unsafe {
+            self.world
+                .ensure_unique(
+                    Some(self.id),
+                    [(self.component.desc(), (&value as *const T).cast())],
+                )
+                .unwrap()
+        };
+
         let (loc, _) = self
             .world
             .set_with_writer(
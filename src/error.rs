@@ -18,6 +18,21 @@ pub enum Error {
     IncompleteBatch,
     /// Attempt to spawn entity with occupied entity id
     EntityOccupied(Entity),
+    /// Attempt to despawn the target of a relation with the
+    /// [`TargetDespawnPolicy::Error`](crate::metadata::TargetDespawnPolicy::Error) policy
+    TargetInUse(Entity, ComponentDesc),
+    /// Attempt to set a component with the [`Unique`](crate::metadata::Unique) or
+    /// [`UniqueValue`](crate::metadata::UniqueValue) constraint which is already held by another
+    /// entity.
+    ///
+    /// The holder is `None` if it is yet to be spawned by the same command buffer.
+    NotUnique(ComponentDesc, Option<Entity>),
+    /// Attempt to spawn a component with the [`Unique`](crate::metadata::Unique) or
+    /// [`UniqueValue`](crate::metadata::UniqueValue) constraint in a batch
+    UniqueInBatch(ComponentDesc),
+    /// Two distinct components with the same name were registered where components are
+    /// identified by name, such as for serialization.
+    NameConflict(ComponentDesc, ComponentDesc),
 }
 
 impl Error {
@@ -78,6 +93,26 @@ impl Display for Error {
             Error::EntityOccupied(current) => {
                 write!(f, "Attempt to spawn new entity occupied id {current}")
             }
+            Error::TargetInUse(target, desc) => write!(
+                f,
+                "Attempt to despawn {target} which is the target of relation {desc:?}"
+            ),
+            Error::NotUnique(desc, Some(holder)) => write!(
+                f,
+                "Attempt to set unique component {desc:?} which is already held by {holder}"
+            ),
+            Error::NotUnique(desc, None) => write!(
+                f,
+                "Attempt to set unique component {desc:?} which is already held by a spawned entity"
+            ),
+            Error::UniqueInBatch(desc) => {
+                write!(f, "Attempt to spawn unique component {desc:?} in a batch")
+            }
+            Error::NameConflict(a, b) => write!(
+                f,
+                "Components {a:?} and {b:?} can not both be registered as {:?}",
+                a.name()
+            ),
         }
     }
 }
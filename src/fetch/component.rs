@@ -1,6 +1,9 @@
 use atomic_refcell::AtomicRef;
 
-use crate::{archetype::Slot, component::ComponentValue, system::AccessKind, util::Ptr, Component};
+use crate::{
+    archetype::Slot, component::ComponentValue, metadata::assert_not_sparse_pair,
+    system::AccessKind, util::Ptr, Component,
+};
 
 use super::{read_only::RandomFetch, *};
 
@@ -80,6 +83,7 @@ where
     }
 
     fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        assert_not_sparse_pair(self.desc());
         searcher.add_required(self.key())
     }
 }
@@ -5,6 +5,7 @@ use core::fmt::{self, Formatter};
 use crate::{
     archetype::{Archetype, CellMutGuard, Slice},
     component::ComponentValue,
+    metadata::assert_not_sparse_pair,
     system::{Access, AccessKind},
     util::PtrMut,
     Component, Fetch, FetchItem,
@@ -66,6 +67,7 @@ where
     }
 
     fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        assert_not_sparse_pair(self.0.desc());
         searcher.add_required(self.0.key())
     }
 }
@@ -10,8 +10,8 @@ use super::{
     cloned::Cloned,
     copied::Copied,
     opt::{Opt, OptOr},
-    source::{FetchSource, FromRelation, Traverse},
-    transform::Added,
+    source::{FetchSource, FromRelation, SharedValue, Traverse},
+    transform::{Added, Inherited, Previous},
     Map, Modified, Satisfied, Source, TransformFetch,
 };
 
@@ -159,6 +159,18 @@ pub trait FetchExt: Sized {
             },
         )
     }
+
+    /// Resolves the fetch from each entity itself, or from its shared values if the entity does
+    /// not match.
+    ///
+    /// This is how components with the [`Shared`](crate::metadata::Shared) metadata are queried,
+    /// as plain component fetches only yield the values held by the entities themselves.
+    ///
+    /// See: [`World::set_shared`](crate::World::set_shared)
+    fn shared(self) -> Source<Self, SharedValue> {
+        Source::new(self, SharedValue)
+    }
+
     /// Transform the fetch into a fetch where each constituent part tracks and yields for
     /// modification events.
     ///
@@ -190,6 +202,38 @@ pub trait FetchExt: Sized {
     {
         self.transform_fetch(Added)
     }
+    /// Transform the fetch into a fetch which yields the value from before the most recent
+    /// modification, or `None` if the value has not been modified.
+    ///
+    /// The component must have the [`TrackPrevious`](crate::metadata::TrackPrevious) metadata.
+    ///
+    /// Use together with [`Self::modified`] to only visit the entities which changed.
+    fn previous(self) -> <Self as TransformFetch<Previous>>::Output
+    where
+        Self: TransformFetch<Previous>,
+    {
+        self.transform_fetch(Previous)
+    }
+
+    /// Transform the fetch into a fetch where each constituent part falls back to the prototypes
+    /// of the entity when the entity itself lacks the component.
+    ///
+    /// Prototypes are declared using the [`is_a`](crate::components::is_a) relation and are
+    /// followed recursively. A component set on the instance overrides the inherited value.
+    ///
+    /// Each part is resolved separately, so `(a(), b()).inherited()` may yield `a` from the
+    /// instance and `b` from its prototype. Cycles of `is_a` relations are followed only once.
+    ///
+    /// Only read only fetches can be inherited, as there is no copy-on-write of the prototype's
+    /// value. To modify an inherited value for a single instance, set the component on the
+    /// instance first, which then overrides the prototype.
+    fn inherited(self) -> <Self as TransformFetch<Inherited>>::Output
+    where
+        Self: TransformFetch<Inherited>,
+    {
+        self.transform_fetch(Inherited)
+    }
+
     /// Map each item of the query to another type using the provided function.
     fn map<F, T>(self, func: F) -> Map<Self, F>
     where
@@ -204,7 +248,7 @@ pub trait FetchExt: Sized {
     where
         F: for<'x> Fetch<'x>,
     {
-        Filtered::new(self, filter, true)
+        Filtered::new(self, filter, true, true)
     }
 }
 
@@ -0,0 +1,925 @@
+use core::{
+    fmt::{self, Formatter},
+    iter::Copied,
+    slice,
+};
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    archetype::{Archetype, ArchetypeId, CellGuard, Slice, Slot},
+    component::{ComponentKey, ComponentValue},
+    relation::RelationExt,
+    system::{Access, AccessKind},
+    Component, Entity, Fetch, FetchItem, World,
+};
+
+use super::{FetchAccessData, FetchPrepareData, PreparedFetch};
+
+/// An ancestor of the entities in an archetype
+#[derive(Debug, Clone, Copy)]
+struct Ancestor {
+    id: Entity,
+    arch_id: ArchetypeId,
+    slot: Slot,
+    /// The archetype which holds the relation to this ancestor
+    subject: ArchetypeId,
+}
+
+/// Follows the first target of `relation` from `arch` to the root, nearest first.
+///
+/// As all entities in an archetype share the same relations the chain is the same for every entity
+/// in the archetype.
+fn ancestor_chain(world: &World, arch_id: ArchetypeId, relation: Entity) -> Vec<Ancestor> {
+    let mut chain = Vec::new();
+    let mut subject = arch_id;
+
+    loop {
+        let arch = world.archetypes.get(subject);
+        let Some((key, _)) = arch.relations_like(relation).next() else {
+            break;
+        };
+
+        let id = key.target.unwrap();
+
+        // Cyclic hierarchy
+        if chain.iter().any(|v: &Ancestor| v.id == id) {
+            break;
+        }
+
+        // The target may have been despawned without detaching the relation
+        let Ok(loc) = world.location(id) else {
+            break;
+        };
+
+        chain.push(Ancestor {
+            id,
+            arch_id: loc.arch_id,
+            slot: loc.slot,
+            subject,
+        });
+
+        subject = loc.arch_id;
+    }
+
+    chain
+}
+
+/// Reads the relation from each archetype in the chain
+fn chain_access(chain: &[Ancestor], relation: Entity, dst: &mut Vec<Access>) {
+    dst.extend(chain.iter().map(|v| Access {
+        kind: AccessKind::Archetype {
+            id: v.subject,
+            component: ComponentKey::new(relation, Some(v.id)),
+        },
+        mutable: false,
+    }));
+}
+
+/// Yields the ancestors of each entity by following the first target of a relation, starting with
+/// the direct parent.
+///
+/// The ancestors are resolved once per archetype. See [`ancestors`].
+#[derive(Debug, Clone)]
+pub struct Ancestors {
+    relation: Entity,
+}
+
+/// Yields the ancestors of each entity along `relation`, nearest first.
+///
+/// For relations with multiple targets, such as non-[`Exclusive`](crate::metadata::Exclusive)
+/// relations, the first target is followed.
+pub fn ancestors<T: ComponentValue>(relation: impl RelationExt<T>) -> Ancestors {
+    Ancestors {
+        relation: relation.id(),
+    }
+}
+
+impl Ancestors {
+    /// Yield the value of `component` for each ancestor which has it, nearest first.
+    pub fn component<U: ComponentValue>(self, component: Component<U>) -> AncestorComponents<U> {
+        AncestorComponents {
+            relation: self.relation,
+            component,
+        }
+    }
+}
+
+impl<'w> Fetch<'w> for Ancestors {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedAncestors;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let ids = ancestor_chain(data.world, data.arch_id, self.relation)
+            .into_iter()
+            .map(|v| v.id)
+            .collect();
+
+        Some(PreparedAncestors { ids })
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        let chain = ancestor_chain(data.world, data.arch_id, self.relation);
+        chain_access(&chain, self.relation, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "ancestors({})", self.relation)
+    }
+}
+
+impl<'q> FetchItem<'q> for Ancestors {
+    type Item = AncestorsIter<'q>;
+}
+
+/// Iterates the ancestors of an entity, nearest first
+pub type AncestorsIter<'a> = Copied<slice::Iter<'a, Entity>>;
+
+#[doc(hidden)]
+pub struct PreparedAncestors {
+    ids: Vec<Entity>,
+}
+
+impl<'q> PreparedFetch<'q> for PreparedAncestors {
+    type Item = AncestorsIter<'q>;
+    type Chunk = &'q [Entity];
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, _: Slice) -> Self::Chunk {
+        &self.ids
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        chunk.iter().copied()
+    }
+}
+
+/// Yields the value of a component for each ancestor which has it.
+///
+/// See: [`Ancestors::component`]
+#[derive(Debug, Clone)]
+pub struct AncestorComponents<T: ComponentValue> {
+    relation: Entity,
+    component: Component<T>,
+}
+
+impl<'w, T: ComponentValue> Fetch<'w> for AncestorComponents<T> {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedAncestorComponents<'w, T>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let borrows = ancestor_chain(data.world, data.arch_id, self.relation)
+            .into_iter()
+            .filter_map(|v| {
+                let arch = data.world.archetypes.get(v.arch_id);
+                Some((v.id, v.slot, arch.borrow(self.component.key())?))
+            })
+            .collect();
+
+        Some(PreparedAncestorComponents { borrows })
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        let chain = ancestor_chain(data.world, data.arch_id, self.relation);
+        chain_access(&chain, self.relation, dst);
+
+        dst.extend(
+            chain
+                .iter()
+                .filter(|v| {
+                    data.world
+                        .archetypes
+                        .get(v.arch_id)
+                        .has(self.component.key())
+                })
+                .map(|v| Access {
+                    kind: AccessKind::Archetype {
+                        id: v.arch_id,
+                        component: self.component.key(),
+                    },
+                    mutable: false,
+                }),
+        );
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "ancestors({}).{}", self.relation, self.component.name())
+    }
+}
+
+impl<'q, T: ComponentValue> FetchItem<'q> for AncestorComponents<T> {
+    type Item = AncestorComponentsIter<'q, T>;
+}
+
+#[doc(hidden)]
+pub struct PreparedAncestorComponents<'w, T> {
+    borrows: Vec<(Entity, Slot, CellGuard<'w, [T]>)>,
+}
+
+impl<'w, 'q, T: ComponentValue> PreparedFetch<'q> for PreparedAncestorComponents<'w, T> {
+    type Item = AncestorComponentsIter<'q, T>;
+    type Chunk = AncestorsBatch<'q, T>;
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, _: Slice) -> Self::Chunk {
+        AncestorsBatch {
+            borrows: &self.borrows,
+        }
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        AncestorComponentsIter {
+            borrows: chunk.borrows.iter(),
+        }
+    }
+}
+
+pub struct AncestorsBatch<'a, T> {
+    borrows: &'a [(Entity, Slot, CellGuard<'a, [T]>)],
+}
+
+/// Iterates the ancestors of an entity which have the component, nearest first
+pub struct AncestorComponentsIter<'a, T> {
+    borrows: slice::Iter<'a, (Entity, Slot, CellGuard<'a, [T]>)>,
+}
+
+impl<'a, T> Iterator for AncestorComponentsIter<'a, T> {
+    type Item = (Entity, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, slot, borrow) = self.borrows.next()?;
+        Some((*id, &borrow.get()[*slot]))
+    }
+}
+
+/// Yields the number of ancestors of each entity, where roots have a depth of `0`.
+///
+/// The depth is resolved once per archetype. See [`depth`].
+#[derive(Debug, Clone)]
+pub struct Depth {
+    relation: Entity,
+}
+
+/// Yields the depth of each entity along `relation`, following the first target.
+pub fn depth<T: ComponentValue>(relation: impl RelationExt<T>) -> Depth {
+    Depth {
+        relation: relation.id(),
+    }
+}
+
+impl<'w> Fetch<'w> for Depth {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedDepth;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        Some(PreparedDepth(
+            ancestor_chain(data.world, data.arch_id, self.relation).len(),
+        ))
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        let chain = ancestor_chain(data.world, data.arch_id, self.relation);
+        chain_access(&chain, self.relation, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "depth({})", self.relation)
+    }
+}
+
+impl<'q> FetchItem<'q> for Depth {
+    type Item = usize;
+}
+
+#[doc(hidden)]
+pub struct PreparedDepth(usize);
+
+impl<'q> PreparedFetch<'q> for PreparedDepth {
+    type Item = usize;
+    type Chunk = usize;
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, _: Slice) -> Self::Chunk {
+        self.0
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        *chunk
+    }
+}
+
+/// Yields the root of the hierarchy each entity belongs to, or the entity itself if it is a root.
+///
+/// The root is resolved once per archetype. See [`root`].
+#[derive(Debug, Clone)]
+pub struct Root {
+    relation: Entity,
+}
+
+/// Yields the root of each entity along `relation`, following the first target.
+pub fn root<T: ComponentValue>(relation: impl RelationExt<T>) -> Root {
+    Root {
+        relation: relation.id(),
+    }
+}
+
+impl<'w> Fetch<'w> for Root {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedRoot<'w>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let root = ancestor_chain(data.world, data.arch_id, self.relation)
+            .last()
+            .map(|v| v.id);
+
+        Some(PreparedRoot {
+            root,
+            entities: data.arch.entities(),
+        })
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        let chain = ancestor_chain(data.world, data.arch_id, self.relation);
+        chain_access(&chain, self.relation, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "root({})", self.relation)
+    }
+}
+
+impl<'q> FetchItem<'q> for Root {
+    type Item = Entity;
+}
+
+#[doc(hidden)]
+pub struct PreparedRoot<'w> {
+    root: Option<Entity>,
+    entities: &'w [Entity],
+}
+
+impl<'w, 'q> PreparedFetch<'q> for PreparedRoot<'w> {
+    type Item = Entity;
+    type Chunk = (Option<Entity>, slice::Iter<'q, Entity>);
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, slots: Slice) -> Self::Chunk {
+        (self.root, self.entities[slots.as_range()].iter())
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        let id = *chunk.1.next().unwrap();
+        chunk.0.unwrap_or(id)
+    }
+}
+
+/// Depth first walk of the descendants of an entity
+struct DescendantsWalk<'a> {
+    world: &'a World,
+    relation: Entity,
+    max_depth: usize,
+    /// The remaining slots of each visited archetype and their depth
+    stack: Vec<(usize, ArchetypeId, &'a Archetype, Slice)>,
+}
+
+impl<'a> DescendantsWalk<'a> {
+    fn new(world: &'a World, relation: Entity, max_depth: usize, id: Entity) -> Self {
+        let mut walk = Self {
+            world,
+            relation,
+            max_depth,
+            stack: Vec::new(),
+        };
+
+        walk.push_children(id, 1);
+        walk
+    }
+
+    fn push_children(&mut self, id: Entity, depth: usize) {
+        if depth > self.max_depth {
+            return;
+        }
+
+        let archetypes = &self.world.archetypes;
+        let children = archetypes
+            .index
+            .find(ComponentKey::new(self.relation, Some(id)))
+            .into_iter()
+            .flat_map(|v| v.keys());
+
+        for &arch_id in children {
+            let arch = archetypes.get(arch_id);
+            self.stack.push((depth, arch_id, arch, arch.slots()));
+        }
+    }
+}
+
+impl<'a> Iterator for DescendantsWalk<'a> {
+    type Item = (ArchetypeId, Slot, Entity);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, arch_id, arch, slots) = self.stack.last_mut()?;
+            if slots.is_empty() {
+                self.stack.pop();
+                continue;
+            }
+
+            let slot = slots.start;
+            slots.start += 1;
+
+            let (depth, arch_id) = (*depth, *arch_id);
+            let id = arch.entities[slot];
+            self.push_children(id, depth + 1);
+
+            return Some((arch_id, slot, id));
+        }
+    }
+}
+
+/// Yields the descendants of each entity in depth first order.
+///
+/// See [`descendants`].
+#[derive(Debug, Clone)]
+pub struct Descendants {
+    relation: Entity,
+    max_depth: usize,
+}
+
+/// Yields the descendants of each entity along `relation` in depth first order, up to and
+/// including `max_depth` levels below the entity.
+///
+/// Use `usize::MAX` to visit all descendants. Unlike [`ancestors`], the descendants are resolved
+/// for each entity as they are iterated.
+pub fn descendants<T: ComponentValue>(
+    relation: impl RelationExt<T>,
+    max_depth: usize,
+) -> Descendants {
+    Descendants {
+        relation: relation.id(),
+        max_depth,
+    }
+}
+
+impl Descendants {
+    /// Yield the value of `component` for each descendant which has it
+    pub fn component<U: ComponentValue>(self, component: Component<U>) -> DescendantComponents<U> {
+        DescendantComponents {
+            relation: self.relation,
+            max_depth: self.max_depth,
+            component,
+        }
+    }
+}
+
+/// Reads the relation in all archetypes which may contain descendants
+fn descendants_access(world: &World, relation: Entity, dst: &mut Vec<Access>) {
+    let archetypes = world.archetypes.index.find_relation(relation);
+    for &arch_id in archetypes.into_iter().flat_map(|v| v.keys()) {
+        let arch = world.archetypes.get(arch_id);
+        dst.extend(arch.relations_like(relation).map(|(&key, _)| Access {
+            kind: AccessKind::Archetype {
+                id: arch_id,
+                component: key,
+            },
+            mutable: false,
+        }));
+    }
+}
+
+impl<'w> Fetch<'w> for Descendants {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedDescendants<'w>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        Some(PreparedDescendants {
+            world: data.world,
+            relation: self.relation,
+            max_depth: self.max_depth,
+            entities: data.arch.entities(),
+        })
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        descendants_access(data.world, self.relation, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "descendants({})", self.relation)
+    }
+}
+
+impl<'q> FetchItem<'q> for Descendants {
+    type Item = DescendantsIter<'q>;
+}
+
+#[doc(hidden)]
+pub struct PreparedDescendants<'w> {
+    world: &'w World,
+    relation: Entity,
+    max_depth: usize,
+    entities: &'w [Entity],
+}
+
+impl<'w, 'q> PreparedFetch<'q> for PreparedDescendants<'w> {
+    type Item = DescendantsIter<'q>;
+    type Chunk = (&'q PreparedDescendants<'q>, slice::Iter<'q, Entity>);
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, slots: Slice) -> Self::Chunk {
+        (self, self.entities[slots.as_range()].iter())
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        let (prepared, ids) = chunk;
+        let id = *ids.next().unwrap();
+
+        DescendantsIter {
+            walk: DescendantsWalk::new(prepared.world, prepared.relation, prepared.max_depth, id),
+        }
+    }
+}
+
+/// Iterates the descendants of an entity in depth first order
+pub struct DescendantsIter<'a> {
+    walk: DescendantsWalk<'a>,
+}
+
+impl<'a> Iterator for DescendantsIter<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walk.next().map(|(_, _, id)| id)
+    }
+}
+
+/// Yields the value of a component for each descendant which has it.
+///
+/// See: [`Descendants::component`]
+#[derive(Debug, Clone)]
+pub struct DescendantComponents<T: ComponentValue> {
+    relation: Entity,
+    max_depth: usize,
+    component: Component<T>,
+}
+
+impl<'w, T: ComponentValue> Fetch<'w> for DescendantComponents<T> {
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedDescendantComponents<'w, T>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        // Any archetype with the relation may contain descendants
+        let archetypes = data.world.archetypes.index.find_relation(self.relation);
+        let borrows = archetypes
+            .into_iter()
+            .flat_map(|v| v.keys())
+            .filter_map(|&arch_id| {
+                let arch = data.world.archetypes.get(arch_id);
+                Some((arch_id, arch.borrow(self.component.key())?))
+            })
+            .collect();
+
+        Some(PreparedDescendantComponents {
+            descendants: PreparedDescendants {
+                world: data.world,
+                relation: self.relation,
+                max_depth: self.max_depth,
+                entities: data.arch.entities(),
+            },
+            borrows,
+        })
+    }
+
+    fn filter_arch(&self, _: FetchAccessData) -> bool {
+        true
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        descendants_access(data.world, self.relation, dst);
+
+        let archetypes = data.world.archetypes.index.find_relation(self.relation);
+        dst.extend(
+            archetypes
+                .into_iter()
+                .flat_map(|v| v.keys())
+                .filter(|&&arch_id| data.world.archetypes.get(arch_id).has(self.component.key()))
+                .map(|&arch_id| Access {
+                    kind: AccessKind::Archetype {
+                        id: arch_id,
+                        component: self.component.key(),
+                    },
+                    mutable: false,
+                }),
+        );
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "descendants({}).{}",
+            self.relation,
+            self.component.name()
+        )
+    }
+}
+
+impl<'q, T: ComponentValue> FetchItem<'q> for DescendantComponents<T> {
+    type Item = DescendantComponentsIter<'q, T>;
+}
+
+#[doc(hidden)]
+pub struct PreparedDescendantComponents<'w, T> {
+    descendants: PreparedDescendants<'w>,
+    borrows: BTreeMap<ArchetypeId, CellGuard<'w, [T]>>,
+}
+
+impl<'w, 'q, T: ComponentValue> PreparedFetch<'q> for PreparedDescendantComponents<'w, T> {
+    type Item = DescendantComponentsIter<'q, T>;
+    type Chunk = (
+        &'q PreparedDescendantComponents<'q, T>,
+        slice::Iter<'q, Entity>,
+    );
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, slots: Slice) -> Self::Chunk {
+        (self, self.descendants.entities[slots.as_range()].iter())
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        let (prepared, ids) = chunk;
+        let id = *ids.next().unwrap();
+        let descendants = &prepared.descendants;
+
+        DescendantComponentsIter {
+            walk: DescendantsWalk::new(
+                descendants.world,
+                descendants.relation,
+                descendants.max_depth,
+                id,
+            ),
+            borrows: &prepared.borrows,
+        }
+    }
+}
+
+/// Iterates the descendants of an entity which have the component in depth first order
+pub struct DescendantComponentsIter<'a, T> {
+    walk: DescendantsWalk<'a>,
+    borrows: &'a BTreeMap<ArchetypeId, CellGuard<'a, [T]>>,
+}
+
+impl<'a, T> Iterator for DescendantComponentsIter<'a, T> {
+    type Item = (Entity, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let borrows = self.borrows;
+        self.walk.find_map(|(arch_id, slot, id)| {
+            let borrow = borrows.get(&arch_id)?;
+            Some((id, &borrow.get()[slot]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::{
+        components::{child_of, name},
+        entity_ids, FetchExt, Query,
+    };
+
+    use super::*;
+
+    component! {
+        a: i32,
+    }
+
+    //     root
+    //      |
+    //   *--*--*
+    //   |     |
+    //   b     c
+    //   |
+    //   d
+    fn spawn_tree(world: &mut World) -> [Entity; 4] {
+        let root = Entity::builder()
+            .set(name(), "root".into())
+            .set(a(), 1)
+            .spawn(world);
+
+        let b = Entity::builder()
+            .set(name(), "b".into())
+            .set_default(child_of(root))
+            .spawn(world);
+
+        let c = Entity::builder()
+            .set(name(), "c".into())
+            .set(a(), 3)
+            .set_default(child_of(root))
+            .spawn(world);
+
+        let d = Entity::builder()
+            .set(name(), "d".into())
+            .set(a(), 4)
+            .set_default(child_of(b))
+            .spawn(world);
+
+        [root, b, c, d]
+    }
+
+    #[test]
+    fn ancestors_depth_root() {
+        let mut world = World::new();
+        let [root_id, b, c, d] = spawn_tree(&mut world);
+
+        let mut query = Query::new((
+            entity_ids(),
+            ancestors(child_of),
+            depth(child_of),
+            root(child_of),
+        ));
+
+        let items = query
+            .borrow(&world)
+            .iter()
+            .map(|(id, ancestors, depth, root)| (id, ancestors.collect_vec(), depth, root))
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(
+            items,
+            [
+                (root_id, vec![], 0, root_id),
+                (b, vec![root_id], 1, root_id),
+                (c, vec![root_id], 1, root_id),
+                (d, vec![b, root_id], 2, root_id),
+            ]
+        );
+
+        let mut query =
+            Query::new((name().cloned(), ancestors(child_of).component(a()))).filter(a().with());
+
+        let items = query
+            .borrow(&world)
+            .iter()
+            .map(|(name, values)| (name, values.map(|(_, &v)| v).collect_vec()))
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(
+            items,
+            [
+                ("c".into(), vec![1]),
+                ("d".into(), vec![1]),
+                ("root".into(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn descendants_fetch() {
+        let mut world = World::new();
+        let [root, b, c, d] = spawn_tree(&mut world);
+
+        let mut query = Query::new((entity_ids(), descendants(child_of, usize::MAX)));
+
+        let items = query
+            .borrow(&world)
+            .iter()
+            .map(|(id, descendants)| (id, descendants.sorted().collect_vec()))
+            .sorted()
+            .collect_vec();
+
+        let mut all = vec![b, c, d];
+        all.sort();
+        assert_eq!(items, [(root, all), (b, vec![d]), (c, vec![]), (d, vec![])]);
+
+        let mut query = Query::new((entity_ids(), descendants(child_of, 1)));
+        let items = query
+            .borrow(&world)
+            .iter()
+            .filter(|v| v.0 == root)
+            .flat_map(|v| v.1)
+            .sorted()
+            .collect_vec();
+
+        let mut children = vec![b, c];
+        children.sort();
+        assert_eq!(items, children);
+
+        let mut query = Query::new((
+            entity_ids(),
+            descendants(child_of, usize::MAX).component(a()),
+        ));
+
+        let items = query
+            .borrow(&world)
+            .iter()
+            .filter(|v| v.0 == root)
+            .flat_map(|v| v.1)
+            .map(|(id, &v)| (id, v))
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(items, [(c, 3), (d, 4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "borrows a mutably while also accessing it")]
+    fn descendants_mutable_conflict() {
+        let mut world = World::new();
+        spawn_tree(&mut world);
+
+        let mut query = Query::new((
+            a().as_mut(),
+            descendants(child_of, usize::MAX).component(a()),
+        ));
+
+        query.borrow(&world).for_each(|_| {});
+    }
+
+    #[test]
+    fn hierarchy_access() {
+        let mut world = World::new();
+        let [root, b, _, d] = spawn_tree(&mut world);
+
+        let loc = world.location(d).unwrap();
+        let arch = world.archetypes.get(loc.arch_id);
+        let data = FetchAccessData {
+            world: &world,
+            arch,
+            arch_id: loc.arch_id,
+        };
+
+        let mut access = Vec::new();
+        ancestors(child_of).component(a()).access(data, &mut access);
+
+        let b_loc = world.location(b).unwrap();
+        assert!(access.contains(&Access {
+            kind: AccessKind::Archetype {
+                id: loc.arch_id,
+                component: child_of(b).key(),
+            },
+            mutable: false,
+        }));
+
+        assert!(access.contains(&Access {
+            kind: AccessKind::Archetype {
+                id: b_loc.arch_id,
+                component: child_of(root).key(),
+            },
+            mutable: false,
+        }));
+
+        let root_loc = world.location(root).unwrap();
+        assert!(access.contains(&Access {
+            kind: AccessKind::Archetype {
+                id: root_loc.arch_id,
+                component: a().key(),
+            },
+            mutable: false,
+        }));
+
+        // `b` does not have `a`
+        assert!(!access.contains(&Access {
+            kind: AccessKind::Archetype {
+                id: b_loc.arch_id,
+                component: a().key(),
+            },
+            mutable: false,
+        }));
+    }
+}
@@ -5,9 +5,11 @@ mod component_mut;
 mod copied;
 mod entity_ref;
 mod ext;
+mod hierarchy;
 mod map;
 mod maybe_mut;
 mod opt;
+mod previous;
 mod read_only;
 mod relations;
 mod relations_mut;
@@ -32,15 +34,24 @@ pub use component::*;
 pub use component_mut::*;
 pub use entity_ref::*;
 pub use ext::FetchExt;
+pub use hierarchy::{
+    ancestors, depth, descendants, root, AncestorComponents, AncestorComponentsIter, Ancestors,
+    AncestorsIter, Depth, DescendantComponents, DescendantComponentsIter, Descendants,
+    DescendantsIter, Root,
+};
 pub use map::Map;
 pub use maybe_mut::{MaybeMut, MutGuard};
 pub use opt::*;
+pub use previous::PreviousComponent;
 pub use read_only::*;
-pub use relations::{nth_relation, relations_like, NthRelation, Relations, RelationsIter};
+pub use relations::{
+    any_target, nth_relation, relations_like, targeting, AnyTarget, NthRelation, Relations,
+    RelationsIter, Targeting, TargetingChange,
+};
 pub use relations_mut::{relations_like_mut, RelationsIterMut, RelationsMut};
 pub use satisfied::Satisfied;
 pub use source::Source;
-pub use transform::{Added, Modified, TransformFetch};
+pub use transform::{Added, Inherited, Modified, Previous, TransformFetch};
 
 #[doc(hidden)]
 pub struct FmtQuery<'r, Q>(pub &'r Q);
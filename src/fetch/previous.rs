@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Formatter};
+
+use crate::{
+    archetype::{CellGuard, PreviousValues, Slice, Slot},
+    component::ComponentValue,
+    system::Access,
+    util::Ptr,
+    Component, Entity, Fetch, FetchItem,
+};
+
+use super::{FetchAccessData, FetchPrepareData, PreparedFetch, RandomFetch};
+
+/// Yields the value of a component from before it was last modified.
+///
+/// Requires the component to have the [`TrackPrevious`](crate::metadata::TrackPrevious)
+/// metadata, otherwise `None` is always yielded.
+///
+/// See: [`FetchExt::previous`](crate::FetchExt::previous)
+#[derive(Debug, Clone)]
+pub struct PreviousComponent<T>(pub(crate) Component<T>);
+
+impl<'q, T: ComponentValue> FetchItem<'q> for PreviousComponent<T> {
+    type Item = Option<&'q T>;
+}
+
+impl<'w, T> Fetch<'w> for PreviousComponent<T>
+where
+    T: ComponentValue,
+{
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedPrevious<'w, T>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let guard = data.arch.borrow::<T>(self.0.key())?;
+
+        Some(PreparedPrevious {
+            guard,
+            entities: data.arch.entities(),
+        })
+    }
+
+    fn filter_arch(&self, data: FetchAccessData) -> bool {
+        self.0.filter_arch(data)
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        self.0.access(data, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "previous {}", self.0.name())
+    }
+
+    fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        self.0.searcher(searcher)
+    }
+}
+
+#[doc(hidden)]
+pub struct PreparedPrevious<'w, T> {
+    guard: CellGuard<'w, [T]>,
+    entities: &'w [Entity],
+}
+
+#[doc(hidden)]
+pub struct Batch<'q> {
+    previous: Option<&'q PreviousValues>,
+    ids: Ptr<'q, Entity>,
+}
+
+impl<'w, 'q, T: ComponentValue> PreparedFetch<'q> for PreparedPrevious<'w, T> {
+    type Item = Option<&'q T>;
+    type Chunk = Batch<'q>;
+
+    const HAS_FILTER: bool = false;
+
+    unsafe fn create_chunk(&'q mut self, slots: Slice) -> Self::Chunk {
+        Batch {
+            previous: self.guard.previous(),
+            ids: Ptr::new(self.entities[slots.as_range()].as_ptr()),
+        }
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        let id = *chunk.ids.as_ref();
+        chunk.ids.advance(1);
+        chunk.previous?.get(id)
+    }
+}
+
+impl<'w, 'q, T: ComponentValue> RandomFetch<'q> for PreparedPrevious<'w, T> {
+    unsafe fn fetch_shared(&'q self, slot: Slot) -> Self::Item {
+        self.guard.previous()?.get(self.entities[slot])
+    }
+
+    unsafe fn fetch_shared_chunk(chunk: &Self::Chunk, slot: Slot) -> Self::Item {
+        chunk.previous?.get(*chunk.ids.add(slot).as_ref())
+    }
+}
@@ -6,9 +6,10 @@ use core::{
 use alloc::vec::Vec;
 
 use crate::{
-    archetype::{CellGuard, Slot},
-    component::ComponentValue,
-    relation::{Relation, RelationExt},
+    archetype::{CellGuard, ChangeKind, Slice, Slot},
+    component::{dummy, ComponentKey, ComponentValue},
+    filter::ChangeCursor,
+    relation::{Relation, RelationExt, SparsePairs, SparsePairsIter},
     system::{Access, AccessKind},
     Entity, Fetch, FetchItem,
 };
@@ -39,7 +40,9 @@ where
                 .collect()
         };
 
-        Some(PreparedRelations { borrows })
+        let sparse = data.arch.borrow_sparse(self.relation.id());
+
+        Some(PreparedRelations { borrows, sparse })
     }
 
     fn filter_arch(&self, _: FetchAccessData) -> bool {
@@ -57,6 +60,7 @@ where
         });
 
         dst.extend(val);
+        dst.extend(sparse_access(self.relation, data));
     }
 
     fn describe(&self, f: &mut Formatter) -> fmt::Result {
@@ -64,6 +68,21 @@ where
     }
 }
 
+/// Access to the pairs column of a sparse relation
+fn sparse_access<T: ComponentValue>(
+    relation: Relation<T>,
+    data: FetchAccessData,
+) -> Option<Access> {
+    let key = relation.sparse_pairs().key();
+    (relation.is_sparse() && data.arch.has(key)).then_some(Access {
+        kind: AccessKind::Archetype {
+            id: data.arch_id,
+            component: key,
+        },
+        mutable: false,
+    })
+}
+
 impl<'q, T: ComponentValue> FetchItem<'q> for Relations<T> {
     type Item = RelationsIter<'q, T>;
 }
@@ -71,10 +90,12 @@ impl<'q, T: ComponentValue> FetchItem<'q> for Relations<T> {
 #[doc(hidden)]
 pub struct PreparedRelations<'a, T> {
     borrows: Vec<(Entity, CellGuard<'a, [T]>)>,
+    sparse: Option<CellGuard<'a, [SparsePairs<T>]>>,
 }
 
 pub struct Batch<'a, T> {
     borrows: &'a [(Entity, CellGuard<'a, [T]>)],
+    sparse: Option<&'a [SparsePairs<T>]>,
     slot: Slot,
 }
 
@@ -91,6 +112,7 @@ where
     unsafe fn create_chunk(&'q mut self, slice: crate::archetype::Slice) -> Self::Chunk {
         Batch {
             borrows: &self.borrows,
+            sparse: self.sparse.as_ref().map(|v| v.get()),
             slot: slice.start,
         }
     }
@@ -101,6 +123,7 @@ where
 
         RelationsIter {
             borrows: chunk.borrows.iter(),
+            sparse: chunk.sparse.map(|v| v[slot].iter()),
             slot,
         }
     }
@@ -109,6 +132,7 @@ where
 /// Iterates the relation targets and data for the yielded query item
 pub struct RelationsIter<'a, T> {
     borrows: slice::Iter<'a, (Entity, CellGuard<'a, [T]>)>,
+    sparse: Option<SparsePairsIter<'a, T>>,
     slot: Slot,
 }
 
@@ -116,9 +140,12 @@ impl<'a, T> Iterator for RelationsIter<'a, T> {
     type Item = (Entity, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (id, borrow) = self.borrows.next()?;
-        let borrow = &borrow.get()[self.slot];
-        Some((*id, borrow))
+        if let Some((id, borrow)) = self.borrows.next() {
+            let borrow = &borrow.get()[self.slot];
+            return Some((*id, borrow));
+        }
+
+        self.sparse.as_mut()?.next()
     }
 }
 
@@ -131,6 +158,297 @@ pub fn relations_like<T: ComponentValue>(relation: impl RelationExt<T>) -> Relat
     }
 }
 
+/// Access all relations of the specified kind, matching only entities which have at least one
+/// such relation.
+///
+/// Combine with [`entity_ids`](crate::entity_ids) to iterate `(subject, target, value)`
+/// triples:
+///
+/// ```rust
+/// # use flax::{*, components::child_of};
+/// # let mut world = World::new();
+/// let mut query = Query::new((entity_ids(), child_of.any_target()));
+/// for (subject, targets) in &mut query.borrow(&world) {
+///     for (target, value) in targets {
+///         // ...
+///     }
+/// }
+/// ```
+pub fn any_target<T: ComponentValue>(relation: impl RelationExt<T>) -> AnyTarget<T> {
+    AnyTarget {
+        relations: Relations {
+            relation: relation.as_relation(),
+        },
+    }
+}
+
+/// Yields all relations of a specified kind for entities which have at least one
+#[derive(Debug, Clone)]
+pub struct AnyTarget<T: ComponentValue> {
+    relations: Relations<T>,
+}
+
+impl<'w, T> Fetch<'w> for AnyTarget<T>
+where
+    T: ComponentValue,
+{
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedRelations<'w, T>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        self.relations.prepare(data)
+    }
+
+    fn filter_arch(&self, data: FetchAccessData) -> bool {
+        data.arch.has_relation(self.relations.relation.id())
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        self.relations.access(data, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}(*)", self.relations.relation)
+    }
+
+    fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        let relation = &self.relations.relation;
+        if relation.is_sparse() {
+            searcher.add_required(relation.sparse_pairs().key())
+        } else {
+            searcher.add_required(ComponentKey::new(relation.id, Some(dummy())))
+        }
+    }
+}
+
+impl<'q, T: ComponentValue> FetchItem<'q> for AnyTarget<T> {
+    type Item = RelationsIter<'q, T>;
+}
+
+/// Access the relation of the specified kind to `target`, yielding the target and the value.
+///
+/// Unlike using [`RelationExt::of`] directly, the target is known to the fetch.
+pub fn targeting<T: ComponentValue>(relation: impl RelationExt<T>, target: Entity) -> Targeting<T> {
+    Targeting {
+        relation: relation.as_relation(),
+        target,
+    }
+}
+
+/// Yields the relation to a specific target
+#[derive(Debug, Clone)]
+pub struct Targeting<T: ComponentValue> {
+    relation: Relation<T>,
+    target: Entity,
+}
+
+impl<'w, T> Fetch<'w> for Targeting<T>
+where
+    T: ComponentValue,
+{
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedNthRelation<'w, T>;
+
+    fn prepare(&self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let borrow = if self.relation.is_sparse() {
+            PairBorrow::Target(self.target, data.arch.borrow_sparse(self.relation.id)?)
+        } else {
+            let borrow = data.arch.borrow::<T>(self.relation.of(self.target).key())?;
+            PairBorrow::Dense(self.target, borrow)
+        };
+
+        Some(PreparedNthRelation { borrow })
+    }
+
+    fn filter_arch(&self, data: FetchAccessData) -> bool {
+        data.arch.has(self.key())
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        if self.relation.is_sparse() {
+            dst.extend(sparse_access(self.relation, data))
+        } else {
+            self.relation.of(self.target).access(data, dst)
+        }
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.relation.of(self.target))
+    }
+
+    fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        searcher.add_required(self.key())
+    }
+}
+
+impl<T: ComponentValue> Targeting<T> {
+    /// The pair, or the pairs column of a sparse relation
+    fn key(&self) -> crate::component::ComponentKey {
+        if self.relation.is_sparse() {
+            self.relation.sparse_pairs().key()
+        } else {
+            self.relation.of(self.target).key()
+        }
+    }
+}
+
+impl<'q, T: ComponentValue> FetchItem<'q> for Targeting<T> {
+    type Item = (Entity, &'q T);
+}
+
+/// Yields the relation to a specific target if the pair was changed since the query last ran.
+///
+/// Pairs of [`Sparse`](crate::metadata::Sparse) relations track their changes individually, so
+/// only the subjects whose pair to `target` changed are yielded.
+///
+/// See: [`Targeting`]
+#[derive(Debug, Clone)]
+pub struct TargetingChange<T: ComponentValue> {
+    targeting: Targeting<T>,
+    kind: ChangeKind,
+}
+
+impl<T: ComponentValue> TargetingChange<T> {
+    pub(crate) fn new(targeting: Targeting<T>, kind: ChangeKind) -> Self {
+        Self { targeting, kind }
+    }
+}
+
+impl<'w, T> Fetch<'w> for TargetingChange<T>
+where
+    T: ComponentValue,
+{
+    const MUTABLE: bool = false;
+
+    type Prepared = PreparedTargetingChange<'w, T>;
+
+    fn prepare(&'w self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
+        let inner = self.targeting.prepare(data)?;
+
+        // Make sure to enable modification tracking if it is actively used
+        if let PairBorrow::Dense(_, borrow) = &inner.borrow {
+            if self.kind.is_modified() {
+                borrow.changes().set_track_modified()
+            }
+        }
+
+        Some(PreparedTargetingChange {
+            inner,
+            kind: self.kind,
+            old_tick: data.old_tick,
+            cursor: ChangeCursor::new(data.old_tick),
+        })
+    }
+
+    fn filter_arch(&self, data: FetchAccessData) -> bool {
+        self.targeting.filter_arch(data)
+    }
+
+    fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        self.targeting.access(data, dst)
+    }
+
+    fn describe(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} ", self.kind)?;
+        self.targeting.describe(f)
+    }
+
+    fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        self.targeting.searcher(searcher)
+    }
+}
+
+impl<'q, T: ComponentValue> FetchItem<'q> for TargetingChange<T> {
+    type Item = (Entity, &'q T);
+}
+
+#[doc(hidden)]
+pub struct PreparedTargetingChange<'a, T> {
+    inner: PreparedNthRelation<'a, T>,
+    kind: ChangeKind,
+    old_tick: u32,
+    cursor: ChangeCursor,
+}
+
+impl<'a, T> PreparedTargetingChange<'a, T> {
+    /// Returns true if the pair of a sparse relation in `slot` has changed
+    fn pair_changed(&self, pairs: &[SparsePairs<T>], target: Entity, slot: Slot) -> bool {
+        let Some(ticks) = pairs[slot].ticks(target) else {
+            return false;
+        };
+
+        match self.kind {
+            ChangeKind::Modified => ticks.modified > self.old_tick,
+            ChangeKind::Added => ticks.added > self.old_tick,
+            ChangeKind::Removed => false,
+        }
+    }
+}
+
+impl<'w, 'q, T: ComponentValue> RandomFetch<'q> for PreparedTargetingChange<'w, T> {
+    unsafe fn fetch_shared(&'q self, slot: Slot) -> Self::Item {
+        self.inner.fetch_shared(slot)
+    }
+
+    unsafe fn fetch_shared_chunk(chunk: &Self::Chunk, slot: Slot) -> Self::Item {
+        PreparedNthRelation::fetch_shared_chunk(chunk, slot)
+    }
+}
+
+impl<'w, 'q, T> PreparedFetch<'q> for PreparedTargetingChange<'w, T>
+where
+    T: ComponentValue,
+{
+    type Item = (Entity, &'q T);
+
+    type Chunk = NthBatch<'q, T>;
+
+    const HAS_FILTER: bool = true;
+
+    unsafe fn filter_slots(&mut self, slots: Slice) -> Slice {
+        let (target, pairs) = match &self.inner.borrow {
+            PairBorrow::Dense(_, borrow) => {
+                let changes = borrow.changes().get(self.kind);
+                return match self.cursor.find_slice(changes.as_slice(), slots) {
+                    Some(cur) => cur
+                        .intersect(&slots)
+                        .unwrap_or(Slice::new(slots.end, slots.end)),
+                    None => Slice::new(slots.end, slots.end),
+                };
+            }
+            PairBorrow::Target(target, borrow) => (*target, borrow.get()),
+            PairBorrow::Nth(..) => unreachable!("targeting borrows a specific target"),
+        };
+
+        // Find the first slot whose pair has changed
+        let first = slots
+            .iter()
+            .position(|slot| self.pair_changed(pairs, target, slot))
+            .unwrap_or(slots.len());
+
+        let count = slots
+            .iter()
+            .skip(first)
+            .take_while(|&slot| self.pair_changed(pairs, target, slot))
+            .count();
+
+        Slice {
+            start: slots.start + first,
+            end: slots.start + first + count,
+        }
+    }
+
+    unsafe fn create_chunk(&'q mut self, slice: crate::archetype::Slice) -> Self::Chunk {
+        self.inner.create_chunk(slice)
+    }
+
+    unsafe fn fetch_next(chunk: &mut Self::Chunk) -> Self::Item {
+        PreparedNthRelation::fetch_next(chunk)
+    }
+}
+
 /// Access the nth relation of the specified kind.
 ///
 /// This is useful for [`Exclusive`](crate::metadata::Exclusive) relations where there is only one parent
@@ -160,7 +478,14 @@ where
     type Prepared = PreparedNthRelation<'w, T>;
 
     fn prepare(&self, data: FetchPrepareData<'w>) -> Option<Self::Prepared> {
-        let borrow =
+        if self.relation.is_sparse() {
+            let borrow = data.arch.borrow_sparse(self.relation.id)?;
+            return Some(PreparedNthRelation {
+                borrow: PairBorrow::Nth(self.n, borrow),
+            });
+        }
+
+        let (target, borrow) =
             data.arch
                 .relations_like(self.relation.id)
                 .nth(self.n)
@@ -168,10 +493,16 @@ where
                     (desc.target.unwrap(), data.arch.cells()[cell_index].borrow())
                 })?;
 
-        Some(PreparedNthRelation { borrow })
+        Some(PreparedNthRelation {
+            borrow: PairBorrow::Dense(target, borrow),
+        })
     }
 
     fn filter_arch(&self, data: FetchAccessData) -> bool {
+        if self.relation.is_sparse() {
+            return data.arch.has(self.relation.sparse_pairs().key());
+        }
+
         data.arch
             .relations_like(self.relation.id)
             .nth(self.n)
@@ -179,6 +510,11 @@ where
     }
 
     fn access(&self, data: FetchAccessData, dst: &mut Vec<Access>) {
+        if self.relation.is_sparse() {
+            dst.extend(sparse_access(self.relation, data));
+            return;
+        }
+
         let relation = self.relation.id;
         let val = data
             .arch
@@ -202,14 +538,11 @@ where
 
 impl<'w, 'q, T: ComponentValue> RandomFetch<'q> for PreparedNthRelation<'w, T> {
     unsafe fn fetch_shared(&'q self, slot: Slot) -> Self::Item {
-        let value = &self.borrow.1.get()[slot];
-        (self.borrow.0, value)
+        self.borrow.get(slot).expect("Slot was not filtered")
     }
 
     unsafe fn fetch_shared_chunk(chunk: &Self::Chunk, slot: Slot) -> Self::Item {
-        let (id, borrow) = &*chunk.borrow;
-
-        (*id, &borrow.get()[slot])
+        (*chunk.borrow).get(slot).expect("Slot was not filtered")
     }
 }
 
@@ -219,11 +552,30 @@ impl<'q, T: ComponentValue> FetchItem<'q> for NthRelation<T> {
 
 #[doc(hidden)]
 pub struct PreparedNthRelation<'a, T> {
-    borrow: (Entity, CellGuard<'a, [T]>),
+    borrow: PairBorrow<'a, T>,
+}
+
+enum PairBorrow<'a, T> {
+    /// The column of a single pair
+    Dense(Entity, CellGuard<'a, [T]>),
+    /// The nth pair of each subject of a sparse relation
+    Nth(usize, CellGuard<'a, [SparsePairs<T>]>),
+    /// The pair to the target of each subject of a sparse relation
+    Target(Entity, CellGuard<'a, [SparsePairs<T>]>),
+}
+
+impl<'a, T> PairBorrow<'a, T> {
+    fn get(&self, slot: Slot) -> Option<(Entity, &T)> {
+        match self {
+            PairBorrow::Dense(target, borrow) => Some((*target, &borrow.get()[slot])),
+            PairBorrow::Nth(n, borrow) => borrow.get()[slot].nth(*n),
+            PairBorrow::Target(target, borrow) => Some((*target, borrow.get()[slot].get(*target)?)),
+        }
+    }
 }
 
 pub struct NthBatch<'a, T> {
-    borrow: *const (Entity, CellGuard<'a, [T]>),
+    borrow: *const PairBorrow<'a, T>,
     slot: Slot,
 }
 
@@ -235,8 +587,33 @@ where
 
     type Chunk = NthBatch<'q, T>;
 
+    // Dense pairs unconditionally yield, which is what optional queries rely on, while subjects
+    // of sparse relations lacking the pair are skipped by `filter_slots`
     const HAS_FILTER: bool = false;
 
+    unsafe fn filter_slots(&mut self, slots: Slice) -> Slice {
+        if let PairBorrow::Dense(..) = self.borrow {
+            return slots;
+        }
+
+        // Find the first slot which has the pair
+        let first = slots
+            .iter()
+            .position(|slot| self.borrow.get(slot).is_some())
+            .unwrap_or(slots.len());
+
+        let count = slots
+            .iter()
+            .skip(first)
+            .take_while(|&slot| self.borrow.get(slot).is_some())
+            .count();
+
+        Slice {
+            start: slots.start + first,
+            end: slots.start + first + count,
+        }
+    }
+
     unsafe fn create_chunk(&'q mut self, slice: crate::archetype::Slice) -> Self::Chunk {
         NthBatch {
             borrow: &self.borrow,
@@ -248,9 +625,6 @@ where
         let slot = chunk.slot;
         chunk.slot += 1;
 
-        let (id, borrow) = unsafe { &*chunk.borrow };
-
-        let borrow = &borrow.get()[slot];
-        (*id, borrow)
+        unsafe { (*chunk.borrow).get(slot) }.expect("Slot was not filtered")
     }
 }
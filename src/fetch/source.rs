@@ -1,11 +1,11 @@
 use core::{fmt::Debug, marker::PhantomData};
 
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, vec::Vec};
 
 use crate::{
     archetype::{Archetype, ArchetypeId, Slice, Slot},
     system::Access,
-    Entity, Fetch, FetchItem,
+    ArchetypeSearcher, Entity, Fetch, FetchItem,
 };
 
 use super::{FetchAccessData, FetchPrepareData, PreparedFetch, RandomFetch};
@@ -18,6 +18,9 @@ pub trait FetchSource {
     ) -> Option<(ArchetypeId, &'a Archetype, Option<Slot>)>;
 
     fn describe(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result;
+
+    /// Narrows down the archetypes which may resolve the fetch
+    fn searcher<'w, Q: Fetch<'w>>(&self, _fetch: &Q, _searcher: &mut ArchetypeSearcher) {}
 }
 
 /// Selects the fetch value from the first target of the specified relation
@@ -79,6 +82,52 @@ impl FetchSource for Entity {
     }
 }
 
+/// Selects the fetch value from the entity itself, or the shared values of the entity
+///
+/// See: [`World::set_shared`](crate::World::set_shared)
+pub struct SharedValue;
+
+impl FetchSource for SharedValue {
+    fn resolve<'a, 'w, Q: Fetch<'w>>(
+        &self,
+        fetch: &Q,
+        data: FetchAccessData<'a>,
+    ) -> Option<(ArchetypeId, &'a Archetype, Option<Slot>)> {
+        if fetch.filter_arch(data) {
+            return Some((data.arch_id, data.arch, None));
+        }
+
+        data.world.shared_values(data.arch).find_map(|id| {
+            let loc = data.world.location(id).ok()?;
+            let arch = data.world.archetypes.get(loc.arch_id);
+
+            fetch
+                .filter_arch(FetchAccessData {
+                    arch,
+                    arch_id: loc.arch_id,
+                    ..data
+                })
+                .then_some((loc.arch_id, arch, Some(loc.slot)))
+        })
+    }
+
+    fn describe(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "shared")
+    }
+
+    fn searcher<'w, Q: Fetch<'w>>(&self, fetch: &Q, searcher: &mut ArchetypeSearcher) {
+        let mut inner = ArchetypeSearcher::default();
+        fetch.searcher(&mut inner);
+
+        // Shared values are only stored for plain components
+        for key in inner.required.into_iter().chain(inner.shared) {
+            if key.target.is_none() {
+                searcher.add_required_or_shared(key)
+            }
+        }
+    }
+}
+
 /// Traverse the edges of a relation recursively to find the first entity which matches the fetch
 pub struct Traverse {
     pub(crate) relation: Entity,
@@ -90,8 +139,14 @@ fn traverse_resolve<'a, 'w, Q: Fetch<'w>>(
     data: FetchAccessData<'a>,
 ) -> Option<(ArchetypeId, &'a Archetype, Option<Slot>)> {
     let mut stack = Vec::new();
+    // Whether an archetype matches does not depend on the slot, and cycles are only visited once
+    let mut visited = BTreeSet::new();
     stack.push((data.arch_id, None));
     while let Some((arch_id, slot)) = stack.pop() {
+        if !visited.insert(arch_id) {
+            continue;
+        }
+
         let data = FetchAccessData {
             arch_id,
             arch: data.world.archetypes.get(arch_id),
@@ -191,6 +246,10 @@ where
         self.source.resolve(&self.fetch, data).is_some()
     }
 
+    fn searcher(&self, searcher: &mut ArchetypeSearcher) {
+        self.source.searcher(&self.fetch, searcher)
+    }
+
     fn describe(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.fetch.describe(f)?;
         write!(f, "(")?;
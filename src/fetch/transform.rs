@@ -1,10 +1,13 @@
 use crate::{
     archetype::ChangeKind,
     component::ComponentValue,
+    components::is_a,
     filter::{ChangeFilter, Filtered, NoEntities, Union},
     Component, EntityIds, FetchExt, Mutable,
 };
 
+use super::{source::Traverse, PreviousComponent, Source, Targeting, TargetingChange};
+
 /// Allows transforming a fetch into another.
 ///
 /// For example transforming a tuple or struct fetch into a modified filtering fetch.
@@ -32,6 +35,20 @@ impl<T: ComponentValue> TransformFetch<Added> for Component<T> {
     }
 }
 
+impl<T: ComponentValue> TransformFetch<Previous> for Component<T> {
+    type Output = PreviousComponent<T>;
+    fn transform_fetch(self, _: Previous) -> Self::Output {
+        PreviousComponent(self)
+    }
+}
+
+impl<T: ComponentValue> TransformFetch<Inherited> for Component<T> {
+    type Output = Source<Self, Traverse>;
+    fn transform_fetch(self, _: Inherited) -> Self::Output {
+        self.traverse(is_a)
+    }
+}
+
 impl<T: ComponentValue> TransformFetch<Modified> for Mutable<T> {
     type Output = Filtered<Self, NoEntities>;
     fn transform_fetch(self, _: Modified) -> Self::Output {
@@ -46,6 +63,20 @@ impl<T: ComponentValue> TransformFetch<Added> for Mutable<T> {
     }
 }
 
+impl<T: ComponentValue> TransformFetch<Modified> for Targeting<T> {
+    type Output = TargetingChange<T>;
+    fn transform_fetch(self, _: Modified) -> Self::Output {
+        TargetingChange::new(self, ChangeKind::Modified)
+    }
+}
+
+impl<T: ComponentValue> TransformFetch<Added> for Targeting<T> {
+    type Output = TargetingChange<T>;
+    fn transform_fetch(self, _: Added) -> Self::Output {
+        TargetingChange::new(self, ChangeKind::Added)
+    }
+}
+
 impl TransformFetch<Modified> for EntityIds {
     type Output = Filtered<Self, NoEntities>;
     fn transform_fetch(self, _: Modified) -> Self::Output {
@@ -68,6 +99,15 @@ pub struct Modified;
 #[derive(Debug, Clone, Copy)]
 pub struct Added;
 
+/// Marker for a fetch which has been transformed to yield the value before the last modification.
+#[derive(Debug, Clone, Copy)]
+pub struct Previous;
+
+/// Marker for a fetch which has been transformed to fall back to the components of the
+/// [`is_a`] prototype.
+#[derive(Debug, Clone, Copy)]
+pub struct Inherited;
+
 macro_rules! tuple_impl {
     ($($idx: tt => $ty: ident),*) => {
         impl<$($ty: TransformFetch<Modified>,)*> TransformFetch<Modified> for ($($ty,)*) {
@@ -83,6 +123,13 @@ macro_rules! tuple_impl {
                 Union(($(self.$idx.transform_fetch(method),)*))
             }
         }
+
+        impl<$($ty: TransformFetch<Inherited>,)*> TransformFetch<Inherited> for ($($ty,)*) {
+            type Output = ($($ty::Output,)*);
+            fn transform_fetch(self, method: Inherited) -> Self::Output {
+                ($(self.$idx.transform_fetch(method),)*)
+            }
+        }
     };
 }
 
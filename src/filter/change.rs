@@ -5,6 +5,7 @@ use itertools::Itertools;
 use crate::archetype::{CellGuard, Change, Slot};
 use crate::component::ComponentValue;
 use crate::fetch::{FetchAccessData, FetchPrepareData, PreparedFetch, RandomFetch};
+use crate::metadata::assert_not_sparse_pair;
 use crate::system::Access;
 use crate::util::Ptr;
 use crate::{
@@ -90,18 +91,19 @@ where
     }
 
     fn searcher(&self, searcher: &mut crate::ArchetypeSearcher) {
+        assert_not_sparse_pair(self.component.desc());
         searcher.add_required(self.component.key())
     }
 }
 
-struct ChangeCursor {
+pub(crate) struct ChangeCursor {
     cursor: usize,
     old_tick: u32,
     cur: Option<Slice>,
 }
 
 impl ChangeCursor {
-    fn new(old_tick: u32) -> Self {
+    pub(crate) fn new(old_tick: u32) -> Self {
         Self {
             cursor: 0,
             old_tick,
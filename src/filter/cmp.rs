@@ -7,7 +7,7 @@
 //! want :P.
 
 use core::{
-    any::type_name,
+    any::{type_name, Any},
     cmp::Ordering,
     fmt::{self, Debug},
 };
@@ -20,11 +20,18 @@ use crate::{
         FetchAccessData, FetchPrepareData, FmtQuery, PreparedFetch, RandomFetch, TransformFetch,
     },
     system::Access,
-    Fetch, FetchItem,
+    ArchetypeSearcher, Fetch, FetchItem,
 };
 
 trait CmpMethod<L> {
     fn compare(&self, lhs: L) -> bool;
+
+    /// Returns the value which must be equal for the comparison to succeed, if any.
+    ///
+    /// Used to look up the value in a component index.
+    fn equal_value(&self) -> Option<&dyn Any> {
+        None
+    }
 }
 
 #[doc(hidden)]
@@ -88,10 +95,15 @@ where
 impl<L, R> CmpMethod<L> for Equal<R>
 where
     L: for<'x> PartialEq<&'x R>,
+    R: 'static,
 {
     fn compare(&self, lhs: L) -> bool {
         lhs.eq(&&self.0)
     }
+
+    fn equal_value(&self) -> Option<&dyn Any> {
+        Some(&self.0)
+    }
 }
 
 impl<T, F> CmpMethod<T> for F
@@ -135,6 +147,7 @@ where
         Some(PreparedCmp {
             fetch: self.fetch.prepare(data)?,
             method: &self.method,
+            skip: !self.may_match(data),
         })
     }
 
@@ -156,9 +169,41 @@ where
     }
 }
 
+impl<F, M> Cmp<F, M> {
+    /// Uses the index of the compared component, if any, to determine if any entity in the
+    /// archetype may satisfy the comparison.
+    fn may_match<'w, L>(&self, data: FetchPrepareData) -> bool
+    where
+        F: Fetch<'w>,
+        M: CmpMethod<L>,
+    {
+        let Some(value) = self.method.equal_value() else {
+            return true;
+        };
+
+        if data.world.indices.is_empty() {
+            return true;
+        }
+
+        let mut searcher = ArchetypeSearcher::default();
+        self.fetch.searcher(&mut searcher);
+
+        let [key] = searcher.required[..] else {
+            return true;
+        };
+
+        match data.world.indices.get(&key) {
+            Some(index) => index.may_contain(data.world, data.arch_id, value),
+            None => true,
+        }
+    }
+}
+
 pub struct PreparedCmp<'w, F, M> {
     fetch: F,
     method: &'w M,
+    /// No entities in the archetype match according to the component index
+    skip: bool,
 }
 
 impl<'w, 'q, F, M> RandomFetch<'q> for PreparedCmp<'w, F, M>
@@ -186,6 +231,10 @@ where
 
     #[inline]
     unsafe fn filter_slots(&mut self, slots: Slice) -> Slice {
+        if self.skip {
+            return Slice::new(slots.end, slots.end);
+        }
+
         let slots = self.fetch.filter_slots(slots);
 
         let mut cmp = |slot: Slot| {
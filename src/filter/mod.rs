@@ -14,12 +14,13 @@ use core::{
 use crate::{
     archetype::{Archetype, Slice, Slot},
     component::ComponentKey,
-    components::component_info,
+    components::{component_info, disabled},
     fetch::{FetchAccessData, FetchPrepareData, PreparedFetch},
     system::Access,
     ArchetypeSearcher, Entity, Fetch, FetchItem,
 };
 
+pub(crate) use change::ChangeCursor;
 pub use change::ChangeFilter;
 pub use cmp::{Cmp, Equal, Greater, GreaterEq, Less, LessEq};
 pub(crate) use constant::NoEntities;
@@ -70,14 +71,21 @@ pub struct Filtered<Q, F> {
     pub(crate) fetch: Q,
     pub(crate) filter: F,
     pub(crate) include_components: bool,
+    pub(crate) include_disabled: bool,
 }
 
 impl<Q, F> Filtered<Q, F> {
-    pub(crate) fn new(fetch: Q, filter: F, include_components: bool) -> Self {
+    pub(crate) fn new(
+        fetch: Q,
+        filter: F,
+        include_components: bool,
+        include_disabled: bool,
+    ) -> Self {
         Self {
             fetch,
             filter,
             include_components,
+            include_disabled,
         }
     }
 }
@@ -105,6 +113,7 @@ where
             fetch: self.fetch.prepare(data)?,
             filter: self.filter.prepare(data)?,
             include_components: self.include_components,
+            include_disabled: self.include_disabled,
         })
     }
 
@@ -113,6 +122,7 @@ where
         self.fetch.filter_arch(data)
             && self.filter.filter_arch(data)
             && (!data.arch.has(component_info().key()) || self.include_components)
+            && (!data.arch.has(disabled().key()) || self.include_disabled)
     }
 
     #[inline]
@@ -430,7 +440,7 @@ impl<'w> Fetch<'w> for WithRelation {
 
 impl StaticFilter for WithRelation {
     fn filter_static(&self, arch: &Archetype) -> bool {
-        arch.relations_like(self.relation).next().is_some()
+        arch.has_relation(self.relation)
     }
 }
 
@@ -468,7 +478,7 @@ impl<'a> Fetch<'a> for WithoutRelation {
 
 impl StaticFilter for WithoutRelation {
     fn filter_static(&self, arch: &Archetype) -> bool {
-        arch.relations_like(self.relation).next().is_none()
+        !arch.has_relation(self.relation)
     }
 }
 
@@ -23,6 +23,7 @@ where
 
         let mut query = Query::new(())
             .with_components()
+            .include_disabled()
             .filter(self.filter.by_ref());
 
         let mut query = query.borrow(self.world);
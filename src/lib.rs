@@ -242,6 +242,10 @@ pub mod relation;
 /// System execution
 pub mod schedule;
 
+#[cfg(feature = "registry")]
+/// Enumerate the components declared in the program
+pub mod registry;
+
 #[cfg(feature = "serde")]
 /// Allows for efficient serialization and deserialization of the world and the
 /// entities therein
@@ -259,7 +263,7 @@ mod writer;
 pub use archetype::{BatchSpawn, RefMut};
 pub use commands::CommandBuffer;
 pub use component::Component;
-pub use entity::{entity_ids, Entity, EntityBuilder};
+pub use entity::{entity_ids, Bundle, Entity, EntityBuilder};
 pub use entity_ref::{EntityRef, EntityRefMut};
 pub use entry::{Entry, OccupiedEntry, VacantEntry};
 pub use error::Error;
@@ -267,11 +271,13 @@ pub use fetch::{
     relations_like, EntityIds, Fetch, FetchExt, FetchItem, Mutable, Opt, OptOr, Relations,
 };
 
-pub use metadata::{Debuggable, Exclusive};
+pub use metadata::{
+    Debuggable, EntityMapper, Exclusive, Indexed, MapEntities, TrackPrevious, Unique, UniqueValue,
+};
 
 pub use query::{
-    Children, Dfs, DfsBorrow, DfsIter, EntityBorrow, EntityQuery, Planar, Query, QueryBorrow,
-    QueryIter, ResourceBorrow, Topo,
+    Bfs, BfsBorrow, Children, Dfs, DfsBorrow, DfsIter, EntityBorrow, EntityQuery, Planar, Query,
+    QueryBorrow, QueryIter, ResourceBorrow, Topo,
 };
 pub use relation::RelationExt;
 pub use schedule::{Schedule, ScheduleBuilder, SystemInfo};
@@ -287,9 +293,14 @@ pub(crate) use vtable::ComponentVTable;
 #[cfg(feature = "derive")]
 pub use flax_derive::*;
 
+#[doc(hidden)]
+pub use alloc::vec::Vec as __Vec;
 #[doc(hidden)]
 pub use generic_static::StaticTypeMap as __StaticTypeMap;
 #[doc(hidden)]
+#[cfg(feature = "registry")]
+pub use inventory as __inventory;
+#[doc(hidden)]
 pub use once_cell::sync::OnceCell as __OnceCell;
 
 #[doc(hidden)]
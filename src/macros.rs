@@ -124,16 +124,20 @@ macro_rules! component {
     };
 
     // Component
-    ($(#[$outer:meta])* $vis: vis $name: ident: $ty: ty $(=> [$($metadata: ty),*])?, $($rest:tt)*) => {
+    //
+    // The metadata is matched as tokens, so that the `Registered` metadata can be detected
+    ($(#[$outer:meta])* $vis: vis $name: ident: $ty: ty $(=> [$($metadata: tt)*])?, $($rest:tt)*) => {
         $(#[$outer])*
         $vis fn $name() -> $crate::Component<$ty> {
             use $crate::entity::EntityKind;
 
             static COMPONENT_ID: ::core::sync::atomic::AtomicU32 = ::core::sync::atomic::AtomicU32::new($crate::entity::EntityIndex::MAX);
-            static VTABLE: &$crate::vtable::ComponentVTable<$ty> = $crate::component_vtable!($name: $ty $(=> [$($metadata),*])?);
+            static VTABLE: &$crate::vtable::ComponentVTable<$ty> = $crate::component_vtable!($name: $ty $(=> [$($metadata)*])?);
             $crate::Component::static_init(&COMPONENT_ID, EntityKind::COMPONENT, VTABLE)
         }
 
+        $crate::__register_component!($name, [$($($metadata)*)?]);
+
         $crate::component!{ $($rest)* }
     };
 
@@ -268,6 +272,30 @@ macro_rules! component_vtable {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "registry")]
+/// Collects the component into the [`ComponentRegistry`](crate::registry::ComponentRegistry) if
+/// the metadata contains `Registered`
+macro_rules! __register_component {
+    ($name: ident, [Registered $($rest: tt)*]) => {
+        $crate::__inventory::submit! {
+            $crate::registry::RegistryEntry::new(|| $name().desc())
+        }
+    };
+    ($name: ident, [$head: tt $($rest: tt)*]) => {
+        $crate::__register_component!($name, [$($rest)*]);
+    };
+    ($name: ident, []) => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "registry"))]
+macro_rules! __register_component {
+    ($name: ident, [$($metadata: tt)*]) => {};
+}
+
 #[cfg(feature = "puffin")]
 macro_rules! profile_function {
     ($($tt: tt)*) => (
@@ -0,0 +1,256 @@
+use alloc::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    sync::Arc,
+    vec::Vec,
+};
+use atomic_refcell::AtomicRefCell;
+use core::{any::Any, mem};
+
+use crate::{
+    archetype::{Archetype, ArchetypeId, Storage},
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentKey, ComponentValue},
+    events::{EventData, EventSubscriber},
+    util::spin_lock,
+    Entity, World,
+};
+
+use super::Metadata;
+
+component! {
+    /// Maintains an index from the values of a component to the entities which have them.
+    ///
+    /// See: [`Indexed`]
+    pub indexed: Indexed,
+}
+
+/// Maintains an index from each value of a component to the entities which have it.
+///
+/// This allows looking up entities by value using [`World::find_by`], and is used to skip
+/// archetypes without any matching entities when filtering a query using
+/// [`eq`](crate::FetchExt::eq).
+///
+/// ```rust
+/// # use flax::{*, metadata::Indexed};
+/// component! {
+///     team: u32 => [ Indexed ],
+/// }
+///
+/// let mut world = World::new();
+/// let id = Entity::builder().set(team(), 3).spawn(&mut world);
+///
+/// assert_eq!(world.find_by(team(), &3), [id]);
+/// ```
+///
+/// The index is kept up to date through the same events as an
+/// [`EventSubscriber`]. Modified values are re-indexed lazily on the next lookup or query
+/// filter.
+///
+/// **Note**: Indexing relations is not supported.
+#[derive(Clone)]
+pub struct Indexed {
+    pub(crate) create: CreateIndex,
+}
+
+/// Creates the index and the subscriber which keeps it up to date
+pub(crate) type CreateIndex = fn(ComponentKey) -> (Arc<dyn ValueIndex>, Arc<dyn EventSubscriber>);
+
+impl<T> Metadata<T> for Indexed
+where
+    T: ComponentValue + Ord + Clone,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            indexed(),
+            Indexed {
+                create: |key| {
+                    let index = Arc::new(ComponentIndex::<T>::new(key));
+                    (index.clone(), index)
+                },
+            },
+        );
+    }
+}
+
+/// Type erased access to a [`ComponentIndex`]
+pub(crate) trait ValueIndex: Send + Sync {
+    /// Returns all entities which have `value`.
+    ///
+    /// Returns `None` if `value` is not of the indexed type.
+    fn find(&self, world: &World, value: &dyn Any) -> Option<Vec<Entity>>;
+
+    /// Returns all entities which have the type erased `value`.
+    ///
+    /// # Safety
+    /// `value` must point to a valid value of the indexed type
+    unsafe fn find_raw(&self, world: &World, value: *const u8) -> Vec<Entity>;
+
+    /// Returns true if any entity in the archetype may have a value equal to `value`.
+    ///
+    /// Returns true if `value` is not of the indexed type.
+    fn may_contain(&self, world: &World, arch_id: ArchetypeId, value: &dyn Any) -> bool;
+}
+
+struct IndexInner<T> {
+    entities: BTreeMap<T, BTreeSet<Entity>>,
+    values: BTreeMap<Entity, T>,
+    /// Entities whose value was modified since they were last indexed
+    dirty: BTreeSet<Entity>,
+}
+
+impl<T: Ord + Clone> IndexInner<T> {
+    fn insert(&mut self, id: Entity, value: T) {
+        self.remove(id);
+        self.entities.entry(value.clone()).or_default().insert(id);
+        self.values.insert(id, value);
+    }
+
+    fn remove(&mut self, id: Entity) {
+        self.dirty.remove(&id);
+
+        if let Some(value) = self.values.remove(&id) {
+            if let Entry::Occupied(mut slot) = self.entities.entry(value) {
+                slot.get_mut().remove(&id);
+                if slot.get().is_empty() {
+                    slot.remove();
+                }
+            }
+        }
+    }
+
+    fn invalidate(&mut self, id: Entity) {
+        self.remove(id);
+        self.dirty.insert(id);
+    }
+}
+
+impl<T: ComponentValue + Ord + Clone> IndexInner<T> {
+    /// Re-indexes the modified values.
+    ///
+    /// Values which are currently borrowed mutably remain dirty.
+    fn refresh(&mut self, world: &World, key: ComponentKey) {
+        if self.dirty.is_empty() {
+            return;
+        }
+
+        for id in mem::take(&mut self.dirty) {
+            let Ok(loc) = world.location(id) else {
+                continue;
+            };
+
+            let Some(cell) = world.archetypes.get(loc.arch_id).cell(key) else {
+                continue;
+            };
+
+            // SAFETY: the cell is of type `T`
+            match unsafe { cell.try_get::<T>(loc.slot) } {
+                Ok(Some(value)) => {
+                    let value = value.clone();
+                    self.insert(id, value);
+                }
+                Ok(None) => {}
+                Err(_) => {
+                    self.dirty.insert(id);
+                }
+            }
+        }
+    }
+}
+
+/// Maps the values of a component to the entities which have them
+pub(crate) struct ComponentIndex<T> {
+    key: ComponentKey,
+    inner: AtomicRefCell<IndexInner<T>>,
+}
+
+impl<T: ComponentValue + Ord + Clone> ComponentIndex<T> {
+    fn new(key: ComponentKey) -> Self {
+        Self {
+            key,
+            inner: AtomicRefCell::new(IndexInner {
+                entities: BTreeMap::new(),
+                values: BTreeMap::new(),
+                dirty: BTreeSet::new(),
+            }),
+        }
+    }
+}
+
+impl<T: ComponentValue + Ord + Clone> ValueIndex for ComponentIndex<T> {
+    fn find(&self, world: &World, value: &dyn Any) -> Option<Vec<Entity>> {
+        let value = value.downcast_ref::<T>()?;
+
+        let mut inner = spin_lock(&self.inner);
+        inner.refresh(world, self.key);
+
+        Some(
+            inner
+                .entities
+                .get(value)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect(),
+        )
+    }
+
+    unsafe fn find_raw(&self, world: &World, value: *const u8) -> Vec<Entity> {
+        self.find(world, &*value.cast::<T>()).unwrap_or_default()
+    }
+
+    fn may_contain(&self, world: &World, arch_id: ArchetypeId, value: &dyn Any) -> bool {
+        let Some(value) = value.downcast_ref::<T>() else {
+            return true;
+        };
+
+        let mut inner = spin_lock(&self.inner);
+        inner.refresh(world, self.key);
+
+        let mut candidates = inner
+            .entities
+            .get(value)
+            .into_iter()
+            .flatten()
+            .chain(&inner.dirty);
+
+        candidates.any(|&id| world.location(id).map(|v| v.arch_id) == Ok(arch_id))
+    }
+}
+
+impl<T: ComponentValue + Ord + Clone> EventSubscriber for ComponentIndex<T> {
+    fn on_added(&self, storage: &Storage, event: &EventData) {
+        let values = storage.downcast_ref::<T>();
+        let mut inner = spin_lock(&self.inner);
+
+        for (&id, slot) in event.ids.iter().zip(event.slots.iter()) {
+            inner.insert(id, values[slot].clone());
+        }
+    }
+
+    fn on_modified(&self, event: &EventData) {
+        // The new values are not part of the event, and are re-indexed on the next lookup
+        let mut inner = spin_lock(&self.inner);
+        for &id in event.ids {
+            inner.invalidate(id);
+        }
+    }
+
+    fn on_removed(&self, _: &Storage, event: &EventData) {
+        let mut inner = spin_lock(&self.inner);
+        for &id in event.ids {
+            inner.remove(id);
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn matches_arch(&self, arch: &Archetype) -> bool {
+        arch.has(self.key)
+    }
+
+    fn matches_component(&self, desc: ComponentDesc) -> bool {
+        desc.key == self.key
+    }
+}
@@ -0,0 +1,87 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    archetype::Storage,
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+    Entity,
+};
+
+use super::Metadata;
+
+component! {
+    /// Allows rewriting the entity ids contained in a component value.
+    ///
+    /// See: [`EntityMapper`]
+    pub entity_mapper: EntityMapper,
+}
+
+/// A value which holds references to other entities.
+///
+/// Implement this for component types which store an [`Entity`] and attach the [`EntityMapper`]
+/// metadata to the component to keep the references valid when the entities are assigned new
+/// ids, such as through [`World::merge_with`](crate::World::merge_with).
+pub trait MapEntities {
+    /// Replace each contained entity with the result of `map`
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity);
+}
+
+impl MapEntities for Entity {
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity) {
+        *self = map(*self);
+    }
+}
+
+impl<T: MapEntities> MapEntities for Option<T> {
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity) {
+        if let Some(v) = self {
+            v.map_entities(map)
+        }
+    }
+}
+
+impl<T: MapEntities> MapEntities for Box<T> {
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity) {
+        (**self).map_entities(map)
+    }
+}
+
+impl<T: MapEntities> MapEntities for Vec<T> {
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity) {
+        self.iter_mut().for_each(|v| v.map_entities(map))
+    }
+}
+
+impl<T: MapEntities, const N: usize> MapEntities for [T; N] {
+    fn map_entities(&mut self, map: &mut dyn FnMut(Entity) -> Entity) {
+        self.iter_mut().for_each(|v| v.map_entities(map))
+    }
+}
+
+/// Rewrites the entity ids contained in a component using [`MapEntities`] when the entities of
+/// the world are migrated.
+#[derive(Clone)]
+pub struct EntityMapper {
+    pub(crate) map_ptr: unsafe fn(*mut u8, &mut dyn FnMut(Entity) -> Entity),
+    pub(crate) map_storage: fn(&mut Storage, &mut dyn FnMut(Entity) -> Entity),
+}
+
+impl<T> Metadata<T> for EntityMapper
+where
+    T: ComponentValue + MapEntities,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            entity_mapper(),
+            EntityMapper {
+                map_ptr: |ptr, map| unsafe { (*ptr.cast::<T>()).map_entities(map) },
+                map_storage: |storage, map| {
+                    storage
+                        .downcast_mut::<T>()
+                        .iter_mut()
+                        .for_each(|v| v.map_entities(map))
+                },
+            },
+        );
+    }
+}
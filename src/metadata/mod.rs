@@ -5,10 +5,26 @@ use crate::{
 };
 
 mod debuggable;
+mod indexed;
+mod map_entities;
+mod on_target_despawn;
+mod registered;
 mod relation;
+mod shared;
+mod sparse;
+mod track_previous;
+mod uniqueness;
 
 pub use debuggable::*;
+pub use indexed::*;
+pub use map_entities::*;
+pub use on_target_despawn::*;
+pub use registered::*;
 pub use relation::*;
+pub use shared::*;
+pub use sparse::*;
+pub use track_previous::*;
+pub use uniqueness::*;
 
 /// Additional data that can attach itself to a component
 ///
@@ -0,0 +1,91 @@
+use core::marker::PhantomData;
+
+use crate::{
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+};
+
+use super::Metadata;
+
+component! {
+    /// Determines what happens to the subjects of a relation when the target is despawned.
+    ///
+    /// See: [`OnTargetDespawn`]
+    pub on_target_despawn: TargetDespawnPolicy,
+}
+
+/// What happens to the subjects of a relation when the target is despawned.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TargetDespawnPolicy {
+    /// Remove the relation from the subjects
+    #[default]
+    Remove,
+    /// Despawn the subjects, applying the policies of their own relations in turn
+    DespawnSubject,
+    /// Refuse to despawn the target while it has subjects
+    Error,
+    /// Panic when despawning a target which has subjects
+    Panic,
+}
+
+/// Attaches a [`TargetDespawnPolicy`] to a relation, which is applied whenever a target of the
+/// relation is despawned, regardless of whether that happens through [`World::despawn`],
+/// [`World::despawn_many`], or a [`CommandBuffer`].
+///
+/// ```rust
+/// # use flax::{component, metadata::{OnTargetDespawn, despawn}};
+/// component! {
+///     owned_by(owner): () => [ OnTargetDespawn<despawn::DespawnSubject> ],
+/// }
+/// ```
+///
+/// [`World::despawn`]: crate::World::despawn
+/// [`World::despawn_many`]: crate::World::despawn_many
+/// [`CommandBuffer`]: crate::CommandBuffer
+pub struct OnTargetDespawn<P>(PhantomData<P>);
+
+impl<T, P> Metadata<T> for OnTargetDespawn<P>
+where
+    T: ComponentValue,
+    P: despawn::DespawnPolicy,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(on_target_despawn(), P::POLICY);
+    }
+}
+
+/// Type level policies for [`OnTargetDespawn`]
+pub mod despawn {
+    use super::TargetDespawnPolicy;
+
+    /// A type level [`TargetDespawnPolicy`]
+    pub trait DespawnPolicy {
+        /// The policy to attach
+        const POLICY: TargetDespawnPolicy;
+    }
+
+    /// See [`TargetDespawnPolicy::Remove`]
+    pub struct Remove;
+    /// See [`TargetDespawnPolicy::DespawnSubject`]
+    pub struct DespawnSubject;
+    /// See [`TargetDespawnPolicy::Error`]
+    pub struct Error;
+    /// See [`TargetDespawnPolicy::Panic`]
+    pub struct Panic;
+
+    impl DespawnPolicy for Remove {
+        const POLICY: TargetDespawnPolicy = TargetDespawnPolicy::Remove;
+    }
+
+    impl DespawnPolicy for DespawnSubject {
+        const POLICY: TargetDespawnPolicy = TargetDespawnPolicy::DespawnSubject;
+    }
+
+    impl DespawnPolicy for Error {
+        const POLICY: TargetDespawnPolicy = TargetDespawnPolicy::Error;
+    }
+
+    impl DespawnPolicy for Panic {
+        const POLICY: TargetDespawnPolicy = TargetDespawnPolicy::Panic;
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+};
+
+use super::Metadata;
+
+component! {
+    /// Marks the component as discoverable through the component registry.
+    ///
+    /// See: [`Registered`]
+    pub registered: (),
+}
+
+/// Makes the component discoverable through the `ComponentRegistry` without the component having
+/// been used first.
+///
+/// Requires the `registry` feature. Relations and generic components are not registered.
+#[derive(Debug, Clone)]
+pub struct Registered;
+
+impl<T> Metadata<T> for Registered
+where
+    T: ComponentValue,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(registered(), ());
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+};
+
+use super::Metadata;
+
+component! {
+    /// Allows the values of the component to be shared between entities.
+    ///
+    /// See: [`Shared`]
+    pub shared: (),
+}
+
+/// Allows the values of a component to be shared between entities using
+/// [`World::set_shared`](crate::World::set_shared), rather than stored for each entity.
+///
+/// Entities using the same shared value are grouped into the same archetype. Setting a shared
+/// value removes the value held by the entity itself, and the entity holding a shared value is
+/// despawned once no entity uses it anymore.
+///
+/// Shared values are read using [`FetchExt::shared`](crate::FetchExt::shared), which yields the
+/// value held by the entity, or else its shared value. Plain component fetches only yield the
+/// values held by the entities themselves.
+///
+/// ```rust
+/// # use flax::{*, metadata::Shared};
+/// component! {
+///     material: &'static str => [ Shared ],
+/// }
+///
+/// let mut world = World::new();
+///
+/// let a = world.spawn();
+/// let b = world.spawn();
+///
+/// world.set_shared(a, material(), "stone").unwrap();
+/// world.set_shared(b, material(), "stone").unwrap();
+///
+/// let mut query = Query::new(material().shared());
+/// assert_eq!(query.borrow(&world).iter().collect::<Vec<_>>(), [&"stone", &"stone"]);
+/// ```
+pub struct Shared;
+
+impl<T: ComponentValue + PartialEq> Metadata<T> for Shared {
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(shared(), ());
+    }
+}
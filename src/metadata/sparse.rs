@@ -0,0 +1,178 @@
+use crate::{
+    archetype::{Slice, Storage},
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentKey, ComponentValue},
+    entity::EntityLocation,
+    relation::{PairTicks, RelationExt, SparsePairs},
+    writer::{EntityWriter, InsertPair, SingleComponentWriter},
+    Component, Entity, World,
+};
+
+use super::{exclusive, Metadata};
+
+component! {
+    /// Stores the pairs of a relation in a single column of the subject rather than in an
+    /// archetype for each target.
+    ///
+    /// See: [`Sparse`]
+    pub sparse: SparseRelation,
+    /// Marks the column storing the pairs of a sparse relation
+    pub(crate) sparse_storage: (),
+}
+
+/// Stores the pairs of a relation in a single column for each subject, rather than creating a new
+/// archetype for each distinct target.
+///
+/// This keeps the number of archetypes flat for relations with many targets, such as large graphs,
+/// at the cost of iteration speed, as entities in the same archetype no longer share targets.
+///
+/// Pairs are set, accessed and removed as usual, and are supported by
+/// [`relations_like`](crate::relations_like), [`nth_relation`](crate::RelationExt::nth_relation),
+/// [`any_target`](crate::RelationExt::any_target), [`targeting`](crate::RelationExt::targeting),
+/// and the [`Dfs`](crate::Dfs), [`Bfs`](crate::Bfs), and [`Topo`](crate::Topo) strategies.
+///
+/// Changes to a single pair are tracked by the pair itself and can be queried using
+/// `targeting(target).modified()` or `targeting(target).added()`, while changes to any pair of a
+/// subject are tracked through the [`sparse_pairs`](crate::RelationExt::sparse_pairs) component of
+/// the relation.
+///
+/// **Note**: Fetching a pair directly in a query, e.g; `child_of(parent)` or
+/// `child_of(parent).modified()`, panics for sparse relations; use
+/// [`targeting`](crate::RelationExt::targeting) instead.
+///
+/// ```rust
+/// # use flax::{*, metadata::Sparse};
+/// component! {
+///     link(target): f32 => [ Sparse ],
+/// }
+///
+/// let mut world = World::new();
+/// let targets = world.spawn_many().take(3).collect::<Vec<_>>();
+///
+/// let a = world.spawn();
+/// let b = world.spawn();
+/// for &target in &targets {
+///     world.set(a, link(target), 1.0).unwrap();
+///     world.set(b, link(target), 2.0).unwrap();
+/// }
+///
+/// assert_eq!(*world.get(b, link(targets[1])).unwrap(), 2.0);
+///
+/// let mut query = Query::new(link.any_target());
+/// assert_eq!(query.borrow(&world).iter().map(|v| v.count()).sum::<usize>(), 6);
+/// ```
+pub struct Sparse;
+
+/// Type erased access to the pairs of a sparse relation
+#[derive(Debug, Clone, Copy)]
+pub struct SparseRelation {
+    /// Moves the value of the pair out of the subject into `dst`, returning when it was added
+    pub(crate) take: unsafe fn(&mut World, Entity, ComponentDesc, &mut Storage, u32) -> Option<u32>,
+    /// Inserts the pair for the subject, adding the pairs column if missing
+    pub(crate) put:
+        unsafe fn(&mut World, Entity, ComponentDesc, *mut u8, PairTicks, u32) -> EntityLocation,
+}
+
+impl<T: ComponentValue> Metadata<T> for Sparse {
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            sparse(),
+            SparseRelation {
+                take: take::<T>,
+                put: put::<T>,
+            },
+        );
+    }
+}
+
+/// Marks the column storing the pairs of a sparse relation
+pub(crate) struct SparseStorage;
+
+impl<T: ComponentValue> Metadata<T> for SparseStorage {
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(sparse_storage(), ());
+    }
+}
+
+/// Returns the sparse storage of the relation pair `desc`, if any
+pub(crate) fn sparse_relation(desc: ComponentDesc) -> Option<SparseRelation> {
+    if !desc.key.is_relation() {
+        return None;
+    }
+
+    desc.meta_ref().get(sparse()).copied()
+}
+
+/// Panics if `desc` is a pair of a sparse relation, which is not stored in a column of its own.
+#[track_caller]
+pub(crate) fn assert_not_sparse_pair(desc: ComponentDesc) {
+    if sparse_relation(desc).is_some() {
+        panic!(
+            "The pair {} belongs to a sparse relation and can not be fetched directly. Use `targeting` or `targeting(..).modified()` instead",
+            desc.name()
+        );
+    }
+}
+
+fn pairs_of<T: ComponentValue>(desc: ComponentDesc) -> Component<SparsePairs<T>> {
+    let relation: Component<T> =
+        Component::from_raw_parts(ComponentKey::new(desc.key.id, None), desc.vtable);
+
+    relation.sparse_pairs()
+}
+
+unsafe fn take<T: ComponentValue>(
+    world: &mut World,
+    id: Entity,
+    desc: ComponentDesc,
+    dst: &mut Storage,
+    tick: u32,
+) -> Option<u32> {
+    let target = desc.key.target.unwrap();
+    let loc = world.location(id).ok()?;
+
+    let arch = world.archetypes.get_mut(loc.arch_id);
+    let cell = arch.cell_mut(pairs_of::<T>(desc).key())?;
+
+    let data = cell.data.get_mut();
+    let added = data.storage.downcast_ref::<SparsePairs<T>>()[loc.slot]
+        .ticks(target)?
+        .added;
+
+    data.record_previous(&[id], Slice::single(loc.slot), tick);
+
+    let value = data.storage.downcast_mut::<SparsePairs<T>>()[loc.slot]
+        .remove(target)
+        .unwrap();
+
+    data.set_modified(&[id], Slice::single(loc.slot), tick);
+
+    dst.push(value);
+    Some(added)
+}
+
+unsafe fn put<T: ComponentValue>(
+    world: &mut World,
+    id: Entity,
+    desc: ComponentDesc,
+    value: *mut u8,
+    ticks: PairTicks,
+    tick: u32,
+) -> EntityLocation {
+    let value = value.cast::<T>().read();
+    let loc = world.location(id).unwrap();
+
+    // Make sure the relation is initialized from the pair rather than the pairs column
+    world.init_component(desc);
+
+    let writer = InsertPair {
+        target: desc.key.target.unwrap(),
+        value,
+        ticks,
+        exclusive: desc.meta_ref().has(exclusive()),
+    };
+
+    SingleComponentWriter::new(pairs_of::<T>(desc).desc(), writer)
+        .write(world, id, loc, tick)
+        .0
+}
@@ -0,0 +1,44 @@
+use alloc::boxed::Box;
+use core::any::Any;
+
+use crate::{
+    archetype::{Slot, Storage},
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+};
+
+use super::Metadata;
+
+component! {
+    /// Keeps the value of a component from before it was modified.
+    ///
+    /// See: [`TrackPrevious`]
+    pub track_previous: TrackPrevious,
+}
+
+/// Keeps a copy of the previous value of a component.
+///
+/// The value is cloned on the *first* mutation of each change tick, which means several
+/// modifications in a row, such as through a `CommandBuffer` or repeated `set`, will retain the
+/// value from before the first of them.
+///
+/// The previous value can be read using the [`previous`](crate::FetchExt::previous) fetch
+/// transform.
+#[derive(Clone)]
+pub struct TrackPrevious {
+    pub(crate) clone_slot: fn(&Storage, Slot) -> Box<dyn Any + Send + Sync>,
+}
+
+impl<T> Metadata<T> for TrackPrevious
+where
+    T: ComponentValue + Clone,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            track_previous(),
+            TrackPrevious {
+                clone_slot: |storage, slot| Box::new(storage.downcast_ref::<T>()[slot].clone()),
+            },
+        );
+    }
+}
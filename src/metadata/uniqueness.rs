@@ -0,0 +1,189 @@
+use core::marker::PhantomData;
+
+use crate::{
+    archetype::{Slot, Storage},
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentValue},
+};
+
+use super::{Indexed, Metadata};
+
+component! {
+    /// Restricts a component to a single entity, or to unique values.
+    ///
+    /// See: [`Unique`] and [`UniqueValue`]
+    pub unique: UniqueConstraint,
+}
+
+/// What happens when a unique component is set for an entity while another entity holds it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UniquePolicy {
+    /// Refuse to set the component, returning [`Error::NotUnique`](crate::Error::NotUnique)
+    #[default]
+    Error,
+    /// Remove the component from the previous holder
+    Steal,
+}
+
+/// The constraint attached by [`Unique`] or [`UniqueValue`]
+#[derive(Debug, Clone, Copy)]
+pub struct UniqueConstraint {
+    /// How conflicts are resolved
+    pub policy: UniquePolicy,
+    /// Compares two type erased values, or `None` if any two values conflict
+    eq: Option<unsafe fn(*const u8, *const u8) -> bool>,
+}
+
+impl UniqueConstraint {
+    /// Returns true if only equal values conflict, as opposed to any two values
+    pub(crate) fn compares_values(&self) -> bool {
+        self.eq.is_some()
+    }
+
+    /// Returns true if `value` conflicts with `other`
+    ///
+    /// # Safety
+    /// Both pointers must point to valid values of the constrained component
+    pub(crate) unsafe fn conflicts(&self, value: *const u8, other: *const u8) -> bool {
+        match self.eq {
+            Some(eq) => eq(value, other),
+            None => true,
+        }
+    }
+
+    /// Returns true if `value` conflicts with the value at `slot`
+    ///
+    /// # Safety
+    /// `value` must point to a valid value of the same type as the storage
+    pub(crate) unsafe fn conflicts_at(
+        &self,
+        storage: &Storage,
+        slot: Slot,
+        value: *const u8,
+    ) -> bool {
+        match self.eq {
+            Some(eq) => eq(value, storage.at(slot).unwrap()),
+            None => true,
+        }
+    }
+}
+
+/// Ensures at most one entity has the component.
+///
+/// Applies to [`World::set`], [`EntityRefMut::set`], [`EntityBuilder`] and
+/// [`CommandBuffer::apply`]. The policy `P` determines whether setting the component for another
+/// entity fails, or removes it from the previous holder.
+///
+/// ```rust
+/// # use flax::{*, metadata::{Unique, unique}};
+/// component! {
+///     main_camera: () => [ Unique ],
+///     player_controller: () => [ Unique<unique::Steal> ],
+/// }
+///
+/// let mut world = World::new();
+/// let a = Entity::builder().set(main_camera(), ()).set(player_controller(), ()).spawn(&mut world);
+/// let b = world.spawn();
+///
+/// assert_eq!(world.set(b, main_camera(), ()), Err(Error::NotUnique(main_camera().desc(), Some(a))));
+///
+/// world.set(b, player_controller(), ()).unwrap();
+/// assert!(!world.has(a, player_controller()));
+/// assert_eq!(world.single(player_controller()).map(|v| v.0), Some(b));
+/// ```
+///
+/// Infallible methods such as [`EntityRefMut::set`] and [`EntityBuilder::spawn`] panic when
+/// the policy refuses the component.
+///
+/// Unique components can not be spawned in batches, and are refused by [`BatchSpawn::set`].
+///
+/// [`World::set`]: crate::World::set
+/// [`EntityRefMut::set`]: crate::EntityRefMut::set
+/// [`EntityBuilder`]: crate::EntityBuilder
+/// [`EntityBuilder::spawn`]: crate::EntityBuilder::spawn
+/// [`CommandBuffer::apply`]: crate::CommandBuffer::apply
+/// [`BatchSpawn::set`]: crate::archetype::BatchSpawn::set
+pub struct Unique<P = unique::Error>(PhantomData<P>);
+
+impl<T, P> Metadata<T> for Unique<P>
+where
+    T: ComponentValue,
+    P: unique::UniquePolicyKind,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            unique(),
+            UniqueConstraint {
+                policy: P::POLICY,
+                eq: None,
+            },
+        );
+    }
+}
+
+/// Ensures no two entities have equal values of the component.
+///
+/// Conflicts are resolved in the same way as [`Unique`].
+///
+/// The component is [`Indexed`] as well, which is used to find the holder of a value without
+/// checking each entity.
+///
+/// ```rust
+/// # use flax::{*, metadata::UniqueValue};
+/// component! {
+///     network_id: u64 => [ UniqueValue ],
+/// }
+///
+/// let mut world = World::new();
+/// let a = Entity::builder().set(network_id(), 1).spawn(&mut world);
+/// let b = Entity::builder().set(network_id(), 2).spawn(&mut world);
+///
+/// assert_eq!(world.set(b, network_id(), 1), Err(Error::NotUnique(network_id().desc(), Some(a))));
+/// ```
+///
+/// **Note**: Values modified in place, such as through a query or [`World::get_mut`], are not
+/// checked.
+///
+/// [`World::get_mut`]: crate::World::get_mut
+pub struct UniqueValue<P = unique::Error>(PhantomData<P>);
+
+impl<T, P> Metadata<T> for UniqueValue<P>
+where
+    T: ComponentValue + Ord + Clone,
+    P: unique::UniquePolicyKind,
+{
+    fn attach(desc: ComponentDesc, buffer: &mut ComponentBuffer) {
+        <Indexed as Metadata<T>>::attach(desc, buffer);
+        buffer.set(
+            unique(),
+            UniqueConstraint {
+                policy: P::POLICY,
+                eq: Some(|a, b| unsafe { *a.cast::<T>() == *b.cast::<T>() }),
+            },
+        );
+    }
+}
+
+/// Type level policies for [`Unique`] and [`UniqueValue`]
+pub mod unique {
+    use super::UniquePolicy;
+
+    /// A type level [`UniquePolicy`]
+    pub trait UniquePolicyKind {
+        /// The policy to attach
+        const POLICY: UniquePolicy;
+    }
+
+    /// See [`UniquePolicy::Error`]
+    pub struct Error;
+    /// See [`UniquePolicy::Steal`]
+    pub struct Steal;
+
+    impl UniquePolicyKind for Error {
+        const POLICY: UniquePolicy = UniquePolicy::Error;
+    }
+
+    impl UniquePolicyKind for Steal {
+        const POLICY: UniquePolicy = UniquePolicy::Steal;
+    }
+}
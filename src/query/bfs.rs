@@ -0,0 +1,632 @@
+use core::marker::PhantomData;
+
+use crate::{
+    archetype::{CellGuard, Slice},
+    component::{ComponentKey, ComponentValue},
+    fetch::{FetchAccessData, PreparedFetch},
+    filter::{All, Filtered},
+    relation::{RelationExt, SparsePairs},
+    system::{Access, AccessKind},
+    FetchItem,
+};
+use alloc::{collections::VecDeque, vec::Vec};
+use smallvec::SmallVec;
+
+use crate::{Entity, Fetch, World};
+
+use super::{borrow::QueryBorrowState, dfs::State, Chunk, PreparedArchetype, QueryStrategy};
+
+/// Traverse from all roots in breadth first, or level, order
+pub struct Bfs<T> {
+    relation: Entity,
+
+    state: State,
+
+    marker: PhantomData<T>,
+}
+
+impl<T: ComponentValue> Bfs<T> {
+    /// Iterate all hierarchies in breadth-first order
+    pub fn new(relation: impl RelationExt<T>) -> Self {
+        Self {
+            relation: relation.id(),
+
+            state: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'w, Q, F, T: ComponentValue> QueryStrategy<'w, Q, F> for Bfs<T>
+where
+    Q: 'w + Fetch<'w>,
+    F: 'w + Fetch<'w>,
+{
+    type Borrow = BfsBorrow<'w, Q, F, T>;
+
+    fn borrow(&'w mut self, query_state: QueryBorrowState<'w, Q, F>, dirty: bool) -> Self::Borrow {
+        if dirty || self.state.sparse_gen != query_state.world.sparse_gen() {
+            self.state
+                .update(query_state.world, self.relation, query_state.fetch)
+        }
+
+        BfsBorrow::new(query_state, self)
+    }
+
+    fn access(&self, world: &'w World, fetch: &'w Filtered<Q, F>, dst: &mut Vec<Access>) {
+        let mut state = State::default();
+        state.update(world, self.relation, fetch);
+
+        state.archetypes.iter().for_each(|&arch_id| {
+            let arch = world.archetypes.get(arch_id);
+            let data = FetchAccessData {
+                world,
+                arch,
+                arch_id,
+            };
+
+            fetch.access(data, dst);
+        });
+
+        dst.push(Access {
+            kind: AccessKind::World,
+            mutable: false,
+        });
+    }
+}
+
+/// Borrowed state for [`Bfs`] strategy
+pub struct BfsBorrow<'w, Q, F = All, T = ()>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+{
+    prepared: SmallVec<[PreparedArchetype<'w, Q::Prepared, F::Prepared>; 8]>,
+    query_state: QueryBorrowState<'w, Q, F>,
+    bfs: &'w Bfs<T>,
+}
+
+/// A chunk waiting to be visited by [`BfsBorrow::traverse`]
+struct TraverseNode<'q, Q: PreparedFetch<'q>> {
+    chunk: Chunk<'q, Q>,
+    /// The relation storage between the chunk and its parent
+    edge: Option<NodeEdge>,
+    /// Index of the value returned by the parent
+    parent: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NodeEdge {
+    /// Index of the relation storage
+    Column(usize),
+    /// Index of the pairs storage of a sparse relation, along with the target
+    Pair(usize, Entity),
+}
+
+impl<'w, Q, F, T> BfsBorrow<'w, Q, F, T>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    T: ComponentValue,
+{
+    fn new(query_state: QueryBorrowState<'w, Q, F>, bfs: &'w Bfs<T>) -> Self {
+        let prepared = bfs
+            .state
+            .archetypes
+            .iter()
+            .map(|&arch_id| {
+                let arch = query_state.world.archetypes.get(arch_id);
+                query_state.prepare_fetch(arch_id, arch).unwrap()
+            })
+            .collect();
+
+        Self {
+            prepared,
+            bfs,
+            query_state,
+        }
+    }
+
+    /// Iterate the subtree of `root` in breadth first order, where `root` has a depth of `0`.
+    ///
+    /// Returns an empty iterator if `root` is not valid
+    pub fn iter_from<'q>(&'q mut self, root: Entity) -> BfsIter<'w, 'q, Q, F>
+    where
+        'w: 'q,
+    {
+        let mut iter = BfsIter {
+            prepared: &mut self.prepared[..],
+            queue: VecDeque::new(),
+            state: &self.bfs.state,
+            world: self.query_state.world,
+            depth: 0,
+        };
+
+        let loc = self.query_state.world.location(root);
+        if let Ok(loc) = loc {
+            if let Some(&arch_index) = self.bfs.state.archetypes_index.get(&loc.arch_id) {
+                // Safety: is root archetype
+                unsafe {
+                    iter.push_slice_to_queue(arch_index, Slice::single(loc.slot));
+                }
+            }
+        }
+
+        iter
+    }
+
+    /// Iterate all trees in breadth first order, level by level.
+    ///
+    /// All roots have a depth of `0`
+    pub fn iter<'q>(&'q mut self) -> BfsIter<'w, 'q, Q, F>
+    where
+        'w: 'q,
+    {
+        let mut iter = BfsIter {
+            prepared: &mut self.prepared[..],
+            queue: VecDeque::new(),
+            state: &self.bfs.state,
+            world: self.query_state.world,
+            depth: 0,
+        };
+
+        // Safety: the iterator will not borrow these archetypes again
+        for &arch_index in &self.bfs.state.roots {
+            unsafe { iter.push_to_queue(arch_index, 0) }
+        }
+
+        iter
+    }
+
+    /// Traverse the subtree in breadth first order, visiting each node using the provided
+    /// function `visit(query, edge, value)` where `value` is the return value of the parent.
+    ///
+    /// All nodes of a level are visited before the nodes of the next level.
+    pub fn traverse_from<V, Visit>(&mut self, root: Entity, value: &V, mut visit: Visit)
+    where
+        Visit: for<'q> FnMut(<Q as FetchItem<'q>>::Item, Option<&T>, &V) -> V,
+    {
+        let Ok(loc) = self.query_state.world.location(root) else {
+            return;
+        };
+
+        let Some(&arch_index) = self.bfs.state.archetypes_index.get(&loc.arch_id) else {
+            return;
+        };
+
+        let prepared = (&mut self.prepared[..]) as *mut [_] as *mut PreparedArchetype<_, _>;
+
+        // Fetch will never change and all calls are disjoint as the graph is acyclic
+        let p = unsafe { &mut *prepared.add(arch_index) };
+
+        let mut queue = VecDeque::new();
+        if let Some(chunk) = unsafe { p.create_chunk(Slice::single(loc.slot)) } {
+            queue.push_back(TraverseNode {
+                chunk,
+                edge: None,
+                parent: None,
+            });
+        }
+
+        Self::traverse_queue(
+            self.query_state.world,
+            self.bfs,
+            prepared,
+            queue,
+            value,
+            &mut visit,
+        )
+    }
+
+    /// Traverse all trees in breadth first order, visiting each node using the provided function
+    /// `visit(query, edge, value)` where `value` is the return value of the parent.
+    ///
+    /// All nodes of a level are visited before the nodes of the next level.
+    pub fn traverse<V, Visit>(&mut self, value: &V, mut visit: Visit)
+    where
+        Visit: for<'q> FnMut(<Q as FetchItem<'q>>::Item, Option<&T>, &V) -> V,
+    {
+        let prepared = (&mut self.prepared[..]) as *mut [_] as *mut PreparedArchetype<_, _>;
+
+        let mut queue = VecDeque::new();
+        for &arch_index in self.bfs.state.roots.iter() {
+            // Fetch will never change and all calls are disjoint
+            let p = unsafe { &mut *prepared.add(arch_index) };
+            queue.extend(p.chunks().map(|chunk| TraverseNode {
+                chunk,
+                edge: None,
+                parent: None,
+            }));
+        }
+
+        Self::traverse_queue(
+            self.query_state.world,
+            self.bfs,
+            prepared,
+            queue,
+            value,
+            &mut visit,
+        )
+    }
+
+    fn traverse_queue<'q, V, Visit>(
+        world: &'w World,
+        bfs: &Bfs<T>,
+        prepared: *mut PreparedArchetype<'w, Q::Prepared, F::Prepared>,
+        mut queue: VecDeque<TraverseNode<'q, Q::Prepared>>,
+        root: &V,
+        visit: &mut Visit,
+    ) where
+        Visit: for<'x> FnMut(<Q as FetchItem<'x>>::Item, Option<&T>, &V) -> V,
+        Q: 'w,
+        F: 'w,
+        'w: 'q,
+    {
+        // Values and relation storages are kept alive until all children have been visited
+        let mut values: Vec<V> = Vec::new();
+        let mut edges: Vec<CellGuard<'w, [T]>> = Vec::new();
+        let mut pairs: Vec<CellGuard<'w, [SparsePairs<T>]>> = Vec::new();
+
+        while let Some(mut node) = queue.pop_front() {
+            while let Some((slot, id, item)) = node.chunk.next_full() {
+                let edge = node.edge.and_then(|v| match v {
+                    NodeEdge::Column(index) => Some(&edges[index].get()[slot]),
+                    NodeEdge::Pair(index, target) => pairs[index].get()[slot].get(target),
+                });
+                let parent = node.parent.map(|v| &values[v]).unwrap_or(root);
+
+                let value = (visit)(item, edge, parent);
+
+                let mut children = bfs.state.subjects(world, id).peekable();
+                if children.peek().is_none() {
+                    continue;
+                }
+
+                let parent = values.len();
+                values.push(value);
+
+                // Iterate the archetypes which contain all references to `id`
+                for (arch_index, slot) in children {
+                    let arch_id = bfs.state.archetypes[arch_index];
+                    let arch = world.archetypes.get(arch_id);
+
+                    // Fetch will never change and all calls are disjoint as the graph is acyclic
+                    let p = unsafe { &mut *prepared.add(arch_index) };
+
+                    match slot {
+                        None => {
+                            let edge = arch
+                                .borrow::<T>(ComponentKey::new(bfs.relation, Some(id)))
+                                .map(|v| {
+                                    edges.push(v);
+                                    NodeEdge::Column(edges.len() - 1)
+                                });
+
+                            queue.extend(p.chunks().map(|chunk| TraverseNode {
+                                chunk,
+                                edge,
+                                parent: Some(parent),
+                            }));
+                        }
+                        // A single subject of a sparse relation
+                        Some(slot) => {
+                            let edge = arch.borrow_sparse::<T>(bfs.relation).map(|v| {
+                                pairs.push(v);
+                                NodeEdge::Pair(pairs.len() - 1, id)
+                            });
+
+                            queue.extend(unsafe { p.create_chunk(Slice::single(slot)) }.map(
+                                |chunk| TraverseNode {
+                                    chunk,
+                                    edge,
+                                    parent: Some(parent),
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Iterate a hierarchy in breadth-first order
+pub struct BfsIter<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    prepared: &'q mut [PreparedArchetype<'w, Q::Prepared, F::Prepared>],
+    queue: VecDeque<(usize, Chunk<'q, Q::Prepared>)>,
+
+    state: &'q State,
+    world: &'w World,
+    depth: usize,
+}
+
+impl<'w, 'q, Q, F> BfsIter<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+{
+    /// Returns the depth of the most recently returned item.
+    ///
+    /// The roots of the traversal have a depth of `0`.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Group the remaining items by level.
+    pub fn levels(self) -> BfsLevels<'w, 'q, Q, F> {
+        BfsLevels {
+            iter: self,
+            pending: None,
+        }
+    }
+
+    fn next_with_depth(&mut self) -> Option<(usize, <Q::Prepared as PreparedFetch<'q>>::Item)> {
+        loop {
+            let (depth, chunk) = self.queue.front_mut()?;
+            let depth = *depth;
+
+            if let Some((id, item)) = chunk.next_with_id() {
+                // Add the children to the back of the queue, after all items of the current level
+                for (arch_index, slot) in self.state.subjects(self.world, id) {
+                    let p = &mut self.prepared[arch_index];
+
+                    // Promote the borrow of the fetch to 'q
+                    // This is safe because each borrow is disjoint
+                    let p = unsafe { &mut *(p as *mut PreparedArchetype<_, _>) };
+
+                    match slot {
+                        None => self
+                            .queue
+                            .extend(p.chunks().map(|chunk| (depth + 1, chunk))),
+                        Some(slot) => self.queue.extend(
+                            unsafe { p.create_chunk(Slice::single(slot)) }
+                                .map(|chunk| (depth + 1, chunk)),
+                        ),
+                    }
+                }
+
+                self.depth = depth;
+                return Some((depth, item));
+            } else {
+                // The front of the queue is exhausted
+                self.queue.pop_front();
+            }
+        }
+    }
+
+    /// Pushes all chunks from arch onto the queue
+    ///
+    /// # Safety
+    /// The arch_index must not be pushed twice or appear later in the queue as a result of
+    /// the hierarchy
+    unsafe fn push_to_queue(&mut self, arch_index: usize, depth: usize) {
+        let arch = &mut self.prepared[arch_index];
+        // Fetch will never change and all calls are disjoint
+        let p = unsafe { &mut *(arch as *mut PreparedArchetype<_, _>) };
+        self.queue.extend(p.chunks().map(|chunk| (depth, chunk)))
+    }
+
+    /// See: [`Self::push_to_queue`]
+    unsafe fn push_slice_to_queue(&mut self, arch_index: usize, slice: Slice) {
+        let arch = &mut self.prepared[arch_index];
+        // Fetch will never change and all calls are disjoint
+        let p = unsafe { &mut *(arch as *mut PreparedArchetype<_, _>) };
+        if let Some(chunk) = p.create_chunk(slice) {
+            self.queue.push_back((0, chunk))
+        }
+    }
+}
+
+impl<'w, 'q, Q, F> Iterator for BfsIter<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    type Item = <Q::Prepared as PreparedFetch<'q>>::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|(_, item)| item)
+    }
+}
+
+/// Iterate a hierarchy one level at a time, yielding the depth and all items of each level.
+///
+/// See: [`BfsIter::levels`]
+pub struct BfsLevels<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    iter: BfsIter<'w, 'q, Q, F>,
+    /// The first item of the next level
+    pending: Option<(usize, <Q::Prepared as PreparedFetch<'q>>::Item)>,
+}
+
+impl<'w, 'q, Q, F> Iterator for BfsLevels<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    type Item = (usize, Vec<<Q::Prepared as PreparedFetch<'q>>::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, first) = self
+            .pending
+            .take()
+            .or_else(|| self.iter.next_with_depth())?;
+
+        let mut items = alloc::vec![first];
+        while let Some((item_depth, item)) = self.iter.next_with_depth() {
+            if item_depth != depth {
+                self.pending = Some((item_depth, item));
+                break;
+            }
+
+            items.push(item);
+        }
+
+        Some((depth, items))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{
+        collections::BTreeMap,
+        string::{String, ToString},
+        vec,
+    };
+    use itertools::Itertools;
+
+    use crate::{
+        components::{child_of, name},
+        entity_ids, CommandBuffer, FetchExt, Query,
+    };
+
+    use super::*;
+
+    fn spawn_tree(world: &mut World) -> Vec<Entity> {
+        //       a           f
+        //       |           |
+        //   *---*---*       g
+        //   |       |
+        //   b       c
+        //   |       |
+        // *-*-*     e
+        // |   |
+        // d   h
+        let ids = ('a'..='h')
+            .map(|v| Entity::builder().set(name(), v.into()).spawn(world))
+            .collect_vec();
+
+        for (child, parent) in [(1, 0), (2, 0), (3, 1), (4, 2), (6, 5), (7, 1)] {
+            world.set(ids[child], child_of(ids[parent]), ()).unwrap();
+        }
+
+        ids
+    }
+
+    #[test]
+    fn bfs() {
+        let mut world = World::new();
+        let ids = spawn_tree(&mut world);
+
+        let mut query = Query::new(entity_ids()).with_strategy(Bfs::new(child_of));
+
+        let mut depths = BTreeMap::new();
+        {
+            let mut borrow = query.borrow(&world);
+            let mut iter = borrow.iter();
+            let mut last_depth = 0;
+            while let Some(id) = iter.next() {
+                assert!(iter.depth() >= last_depth, "Levels visited out of order");
+                last_depth = iter.depth();
+                depths.insert(id, iter.depth());
+            }
+        }
+
+        assert_eq!(
+            depths,
+            BTreeMap::from([
+                (ids[0], 0),
+                (ids[1], 1),
+                (ids[2], 1),
+                (ids[3], 2),
+                (ids[4], 2),
+                (ids[5], 0),
+                (ids[6], 1),
+                (ids[7], 2),
+            ])
+        );
+
+        let levels = query
+            .borrow(&world)
+            .iter()
+            .levels()
+            .map(|(depth, items)| (depth, items.into_iter().sorted().collect_vec()))
+            .collect_vec();
+
+        assert_eq!(
+            levels,
+            [
+                (0, vec![ids[0], ids[5]]),
+                (1, vec![ids[1], ids[2], ids[6]]),
+                (2, vec![ids[3], ids[4], ids[7]]),
+            ]
+        );
+
+        let levels = query
+            .borrow(&world)
+            .iter_from(ids[1])
+            .levels()
+            .map(|(depth, items)| (depth, items.into_iter().sorted().collect_vec()))
+            .collect_vec();
+
+        assert_eq!(levels, [(0, vec![ids[1]]), (1, vec![ids[3], ids[7]])]);
+    }
+
+    #[test]
+    fn traverse_bfs() {
+        component! {
+            path: String,
+        }
+
+        let mut world = World::new();
+        let ids = spawn_tree(&mut world);
+
+        let mut cmd = CommandBuffer::new();
+        let mut visited = Vec::new();
+
+        Query::new((entity_ids(), name()))
+            .with_strategy(Bfs::new(child_of))
+            .borrow(&world)
+            .traverse(&String::new(), |(id, name), _, prefix| {
+                visited.push(id);
+                let p = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    prefix.clone() + "::" + name
+                };
+
+                cmd.set(id, path(), p.clone());
+                p
+            });
+
+        cmd.apply(&mut world).unwrap();
+
+        // Level order
+        let position = |id| visited.iter().position(|&v| v == id).unwrap();
+        assert!(position(ids[2]) < position(ids[3]));
+        assert!(position(ids[6]) < position(ids[4]));
+
+        let paths = Query::new(path().cloned())
+            .borrow(&world)
+            .iter()
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(
+            paths,
+            ["a", "a::b", "a::b::d", "a::b::h", "a::c", "a::c::e", "f", "f::g"]
+        );
+
+        let mut visited = Vec::new();
+        Query::new(name().cloned())
+            .with_strategy(Bfs::new(child_of))
+            .borrow(&world)
+            .traverse_from(ids[2], &0, |name, _, depth| {
+                visited.push((name, *depth));
+                depth + 1
+            });
+
+        assert_eq!(visited, [("c".to_string(), 0), ("e".to_string(), 1)]);
+    }
+}
@@ -36,9 +36,15 @@ impl<'w, Q, F> PreparedArchetype<'w, Q, F> {
 
     #[inline]
     pub fn chunks(&mut self) -> ArchetypeChunks<Q, F> {
+        self.chunks_in(self.arch.slots())
+    }
+
+    /// Iterates the chunks of a subset of the archetype
+    #[inline]
+    pub fn chunks_in(&mut self, slots: Slice) -> ArchetypeChunks<'_, Q, F> {
         ArchetypeChunks {
             fetch: &mut self.fetch as *mut _,
-            slots: self.arch.slots(),
+            slots,
             arch: self.arch,
         }
     }
@@ -1,7 +1,7 @@
 use core::marker::PhantomData;
 
 use crate::{
-    archetype::{ArchetypeId, Slice},
+    archetype::{ArchetypeId, Slice, Slot},
     component::{ComponentKey, ComponentValue},
     fetch::{FetchAccessData, PreparedFetch},
     filter::{All, Filtered},
@@ -16,7 +16,35 @@ use crate::{Entity, Fetch, World};
 
 use super::{borrow::QueryBorrowState, Chunk, PreparedArchetype, QueryStrategy};
 
-type AdjMap = BTreeMap<Entity, SmallVec<[usize; 8]>>;
+pub(crate) type AdjMap = BTreeMap<Entity, SmallVec<[Edge; 8]>>;
+
+/// Connects a target to its subjects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Edge {
+    /// All entities of the archetype at the index
+    Archetype(usize),
+    /// A subject of a sparse relation, which is located when visited as it may move between
+    /// slots
+    Subject(Entity),
+}
+
+/// The value of the relation on the visited entities
+#[derive(Debug)]
+pub(crate) enum EdgeValue<'a, T> {
+    /// The relation column of the archetype
+    Column(&'a [T]),
+    /// The pair of the single visited subject
+    Pair(&'a T),
+}
+
+impl<'a, T> EdgeValue<'a, T> {
+    pub(crate) fn get(&self, slot: Slot) -> &'a T {
+        match *self {
+            EdgeValue::Column(v) => &v[slot],
+            EdgeValue::Pair(v) => v,
+        }
+    }
+}
 
 /// Traverse from all roots in depth first order
 pub struct Dfs<T> {
@@ -47,7 +75,7 @@ where
     type Borrow = DfsBorrow<'w, Q, F, T>;
 
     fn borrow(&'w mut self, query_state: QueryBorrowState<'w, Q, F>, dirty: bool) -> Self::Borrow {
-        if dirty {
+        if dirty || self.state.sparse_gen != query_state.world.sparse_gen() {
             self.state
                 .update(query_state.world, self.relation, query_state.fetch)
         }
@@ -78,15 +106,38 @@ where
 }
 
 #[derive(Default, Debug)]
-struct State {
+pub(crate) struct State {
     /// Maps each entity to a list of indices of query archetypes
-    edges: AdjMap,
-    archetypes: Vec<ArchetypeId>,
-    archetypes_index: BTreeMap<ArchetypeId, usize>,
-    roots: Vec<usize>,
+    pub(crate) edges: AdjMap,
+    pub(crate) archetypes: Vec<ArchetypeId>,
+    pub(crate) archetypes_index: BTreeMap<ArchetypeId, usize>,
+    pub(crate) roots: Vec<usize>,
+    /// The generation of sparse pairs the edges were built from
+    pub(crate) sparse_gen: u32,
 }
 
 impl State {
+    /// Returns the archetype index of the subjects of `target`, and the slot of the subject if
+    /// only a single entity of the archetype is to be visited
+    pub(crate) fn subjects<'a>(
+        &'a self,
+        world: &'a World,
+        target: Entity,
+    ) -> impl Iterator<Item = (usize, Option<Slot>)> + 'a {
+        self.edges
+            .get(&target)
+            .into_iter()
+            .flatten()
+            .filter_map(|&edge| match edge {
+                Edge::Archetype(arch_index) => Some((arch_index, None)),
+                Edge::Subject(id) => {
+                    let loc = world.location(id).ok()?;
+                    let &arch_index = self.archetypes_index.get(&loc.arch_id)?;
+                    Some((arch_index, Some(loc.slot)))
+                }
+            })
+    }
+
     pub(crate) fn update<'w, Q>(&mut self, world: &'w World, relation: Entity, fetch: &Q)
     where
         Q: Fetch<'w>,
@@ -95,6 +146,7 @@ impl State {
         self.archetypes.clear();
         self.archetypes_index.clear();
         self.roots.clear();
+        self.sparse_gen = world.sparse_gen();
 
         let mut searcher = ArchetypeSearcher::default();
         fetch.searcher(&mut searcher);
@@ -114,12 +166,15 @@ impl State {
             debug_assert_eq!(existing, None, "duplicate archetype");
 
             // Go backwards through the relations
-            let mut root = true;
+            let mut root = arch.sparse_targets(relation).is_none();
             for (key, _) in arch.relations_like(relation) {
                 root = false;
                 let target = key.target.unwrap();
 
-                self.edges.entry(target).or_default().push(idx);
+                self.edges
+                    .entry(target)
+                    .or_default()
+                    .push(Edge::Archetype(idx));
             }
 
             if root {
@@ -127,6 +182,25 @@ impl State {
             }
         });
 
+        // Subjects may later move into a matched archetype without the edges being rebuilt, so
+        // all subjects are added regardless of the fetch
+        let mut searcher = ArchetypeSearcher::default();
+        searcher.add_required(ComponentKey::new(relation, None));
+        searcher.find_archetypes(&world.archetypes, |_, arch| {
+            let Some(targets) = arch.sparse_targets(relation) else {
+                return;
+            };
+
+            for (slot, &id) in arch.entities().iter().enumerate() {
+                for &target in targets.get(slot) {
+                    self.edges
+                        .entry(target)
+                        .or_default()
+                        .push(Edge::Subject(id));
+                }
+            }
+        });
+
         // for (arch_id, arch) in world.archetypes.iter() {
         //     if !fetch.filter_arch(FetchAccessData {
         //         world,
@@ -254,7 +328,8 @@ where
         let mut iter = DfsIter {
             prepared: &mut self.prepared[..],
             stack: smallvec::smallvec![],
-            adj: &self.dfs.state.edges,
+            state: &self.dfs.state,
+            world: self.query_state.world,
         };
 
         let loc = self.query_state.world.location(root);
@@ -285,7 +360,8 @@ where
         let mut iter = DfsIter {
             prepared: &mut self.prepared[..],
             stack: smallvec::smallvec![],
-            adj: &self.dfs.state.edges,
+            state: &self.dfs.state,
+            world: self.query_state.world,
         };
 
         // Safety: the iterator will not borrow these archetypes again
@@ -362,7 +438,7 @@ where
         // Alternative: release all borrows and borrow/prepare each fetch inside the loop
         prepared: *mut PreparedArchetype<Q::Prepared, F::Prepared>,
         chunk: &mut Chunk<Q::Prepared>,
-        edge: Option<&[T]>,
+        edge: Option<&EdgeValue<T>>,
         value: &V,
         visit: &mut Visit,
     ) where
@@ -371,27 +447,52 @@ where
         F: 'w,
     {
         while let Some((slot, id, item)) = chunk.next_full() {
-            let value = (visit)(item, edge.map(|v| &v[slot]), value);
+            let value = (visit)(item, edge.map(|v| v.get(slot)), value);
 
             // Iterate the archetypes which contain all references to `id`
-            for &arch_index in dfs.state.edges.get(&id).into_iter().flatten() {
+            for (arch_index, slot) in dfs.state.subjects(world, id) {
                 let arch_id = dfs.state.archetypes[arch_index];
                 let arch = world.archetypes.get(arch_id);
 
-                let edge = arch.borrow::<T>(ComponentKey::new(dfs.relation, Some(id)));
-
                 let p = unsafe { &mut *prepared.add(arch_index) };
 
-                for mut chunk in p.chunks() {
-                    Self::traverse_batch(
-                        world,
-                        dfs,
-                        prepared,
-                        &mut chunk,
-                        edge.as_ref().map(|v| v.get()),
-                        &value,
-                        visit,
-                    )
+                match slot {
+                    None => {
+                        let edge = arch.borrow::<T>(ComponentKey::new(dfs.relation, Some(id)));
+                        let edge = edge.as_ref().map(|v| EdgeValue::Column(v.get()));
+
+                        for mut chunk in p.chunks() {
+                            Self::traverse_batch(
+                                world,
+                                dfs,
+                                prepared,
+                                &mut chunk,
+                                edge.as_ref(),
+                                &value,
+                                visit,
+                            )
+                        }
+                    }
+                    // A single subject of a sparse relation
+                    Some(slot) => {
+                        let pairs = arch.borrow_sparse::<T>(dfs.relation);
+                        let edge = pairs
+                            .as_ref()
+                            .and_then(|v| v.get()[slot].get(id))
+                            .map(EdgeValue::Pair);
+
+                        if let Some(mut chunk) = unsafe { p.create_chunk(Slice::single(slot)) } {
+                            Self::traverse_batch(
+                                world,
+                                dfs,
+                                prepared,
+                                &mut chunk,
+                                edge.as_ref(),
+                                &value,
+                                visit,
+                            )
+                        }
+                    }
                 }
             }
         }
@@ -408,7 +509,8 @@ where
     pub(crate) prepared: &'q mut [PreparedArchetype<'w, Q::Prepared, F::Prepared>],
     pub(crate) stack: SmallVec<[Chunk<'q, Q::Prepared>; 8]>,
 
-    pub(crate) adj: &'q AdjMap,
+    pub(crate) state: &'q State,
+    pub(crate) world: &'w World,
 }
 
 impl<'w, 'q, Q, F> DfsIter<'w, 'q, Q, F>
@@ -452,16 +554,19 @@ where
             let chunk = self.stack.last_mut()?;
             if let Some((id, item)) = chunk.next_with_id() {
                 // Add the children
-                for &arch_index in self.adj.get(&id).into_iter().flatten() {
+                for (arch_index, slot) in self.state.subjects(self.world, id) {
                     let p = &mut self.prepared[arch_index];
 
                     // Promote the borrow of the fetch to 'q
                     // This is safe because each borrow is disjoint
                     let p = unsafe { &mut *(p as *mut PreparedArchetype<_, _>) };
 
-                    let chunks = p.chunks();
-
-                    self.stack.extend(chunks);
+                    match slot {
+                        None => self.stack.extend(p.chunks()),
+                        Some(slot) => self
+                            .stack
+                            .extend(unsafe { p.create_chunk(Slice::single(slot)) }),
+                    }
                 }
 
                 return Some(item);
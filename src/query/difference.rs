@@ -4,6 +4,11 @@ use crate::{
     archetype::ArchetypeId, component::ComponentDesc, components::component_info, Fetch, World,
 };
 
+use super::{
+    searcher::{is_any_target, is_shared},
+    ArchetypeSearcher,
+};
+
 /// Returns all items in left not in right
 struct SetDifference<T, L: Iterator<Item = T>, R: Iterator<Item = T>> {
     left: Peekable<L>,
@@ -55,8 +60,20 @@ where
 {
     let arch = world.archetypes.get(arch_id);
 
-    let mut searcher = Default::default();
+    let mut searcher: ArchetypeSearcher = Default::default();
     fetch.searcher(&mut searcher);
+
+    // Relations to any target are satisfied by any pair of the relation
+    searcher
+        .required
+        .retain(|v| !is_any_target(v) || !arch.has_relation(v.id));
+
+    // Shared components are also satisfied by a shared value of the archetype
+    let shared = searcher.shared.into_iter().filter(|&v| !is_shared(arch, v));
+
+    searcher.required.extend(shared);
+    searcher.required.sort();
+
     SetDifference::new(
         searcher.required.into_iter(),
         arch.components().keys().copied(),
@@ -1,3 +1,4 @@
+mod bfs;
 mod borrow;
 mod data;
 mod dfs;
@@ -28,6 +29,7 @@ use crate::{
 use alloc::vec::Vec;
 
 use self::borrow::QueryBorrowState;
+pub use bfs::{Bfs, BfsBorrow, BfsIter, BfsLevels};
 pub(crate) use borrow::*;
 pub use data::*;
 pub use dfs::*;
@@ -132,7 +134,7 @@ impl<Q> Query<Q, All, Planar> {
         Q: for<'x> Fetch<'x>,
     {
         Self {
-            fetch: Filtered::new(fetch, All, false),
+            fetch: Filtered::new(fetch, All, false, false),
             change_tick: 0,
             strategy: Planar::new(),
             archetype_gen: 0,
@@ -222,6 +224,7 @@ where
                 self.fetch.fetch,
                 self.fetch.filter.push_right(filter),
                 self.fetch.include_components,
+                self.fetch.include_disabled,
             ),
             change_tick: self.change_tick,
             archetype_gen: 0,
@@ -229,6 +232,15 @@ where
         }
     }
 
+    /// Include entities disabled through [`World::disable`].
+    ///
+    /// By default, disabled entities are excluded from all queries.
+    pub fn include_disabled(mut self) -> Self {
+        self.fetch.include_disabled = true;
+        self.archetype_gen = 0;
+        self
+    }
+
     /// Limits the size of each batch using [`QueryBorrow::iter_batched`]
     pub fn batch_size(self, size: Slot) -> Query<Q, F::PushRight, S>
     where
@@ -6,7 +6,7 @@ use crate::{
     archetype::{ArchetypeId, Slice},
     entity::EntityLocation,
     error::{MissingComponent, Result},
-    fetch::{FetchAccessData, PreparedFetch},
+    fetch::{FetchAccessData, FmtQuery, PreparedFetch},
     filter::{All, Filtered},
     system::{Access, AccessKind},
     Entity, Error, Fetch, FetchItem, World,
@@ -59,6 +59,54 @@ impl Planar {
             result.push(arch_id)
         });
     }
+
+    /// Panics if a component is borrowed mutably in an archetype which the same fetch also
+    /// accesses in another way, such as a mutable component and the same component of descendants.
+    ///
+    /// The borrows would otherwise conflict once the archetypes are prepared.
+    fn assert_disjoint_access<'w, Q: Fetch<'w>, F: Fetch<'w>>(
+        world: &World,
+        fetch: &Filtered<Q, F>,
+        archetypes: &[ArchetypeId],
+    ) {
+        if !Q::MUTABLE {
+            return;
+        }
+
+        let mut accesses = Vec::new();
+        for &arch_id in archetypes {
+            let data = FetchAccessData {
+                world,
+                arch: world.archetypes.get(arch_id),
+                arch_id,
+            };
+
+            fetch.access(data, &mut accesses);
+        }
+
+        accesses.retain(|v| v.kind.is_archetype());
+        accesses.sort_by_key(|v| v.kind);
+
+        for group in accesses.chunk_by(|a, b| a.kind == b.kind) {
+            if group.len() > 1 && group.iter().any(|v| v.mutable) {
+                let AccessKind::Archetype { id, component } = group[0].kind else {
+                    unreachable!()
+                };
+
+                let name = world
+                    .archetypes
+                    .get(id)
+                    .component(component)
+                    .map(|v| v.name())
+                    .unwrap_or("?");
+
+                panic!(
+                    "Query {:?} borrows {name} mutably while also accessing it",
+                    FmtQuery(fetch),
+                );
+            }
+        }
+    }
 }
 
 impl<'w, Q, F> QueryStrategy<'w, Q, F> for Planar
@@ -73,6 +121,7 @@ where
         if dirty {
             self.archetypes.clear();
             Self::update_state(state.world, state.fetch, &mut self.archetypes);
+            Self::assert_disjoint_access(state.world, state.fetch, &self.archetypes);
         }
 
         QueryBorrow {
@@ -389,23 +438,6 @@ where
     pub(crate) current: Option<ArchetypeChunks<'q, Q::Prepared, F::Prepared>>,
 }
 
-/// Iterates over archetypes, yielding batches
-impl<'w, 'q, Q, F> BatchedIter<'w, 'q, Q, F>
-where
-    Q: Fetch<'w>,
-    F: Fetch<'w>,
-    'w: 'q,
-{
-    pub(crate) fn new(
-        archetypes: IterMut<'q, PreparedArchetype<'w, Q::Prepared, F::Prepared>>,
-    ) -> Self {
-        Self {
-            archetypes,
-            current: None,
-        }
-    }
-}
-
 impl<'w, 'q, Q, F> Iterator for BatchedIter<'w, 'q, Q, F>
 where
     Q: Fetch<'w>,
@@ -4,13 +4,14 @@ use core::cmp;
 use crate::{
     archetype::{Archetype, ArchetypeId},
     archetypes::Archetypes,
-    component::ComponentKey,
+    component::{dummy, ComponentKey},
 };
 
 #[derive(Default, Debug, Clone)]
 /// Declares search terms for a queries archetypes
 pub struct ArchetypeSearcher {
     pub(crate) required: Vec<ComponentKey>,
+    pub(crate) shared: Vec<ComponentKey>,
 }
 
 impl ArchetypeSearcher {
@@ -19,6 +20,11 @@ impl ArchetypeSearcher {
         self.required.push(component)
     }
 
+    /// Add a component which is required, or provided by a shared value of the archetype
+    pub(crate) fn add_required_or_shared(&mut self, component: ComponentKey) {
+        self.shared.push(component)
+    }
+
     #[inline]
     pub(crate) fn find_archetypes<'a>(
         &mut self,
@@ -28,8 +34,87 @@ impl ArchetypeSearcher {
         self.required.sort();
         self.required.dedup();
 
-        traverse_archetypes(archetypes, archetypes.root(), &self.required, &mut result);
+        self.shared.sort();
+        self.shared.dedup();
+
+        if !self.shared.is_empty() {
+            self.find_shared(archetypes, result);
+            return;
+        }
+
+        if !self.required.iter().any(is_any_target) {
+            traverse_archetypes(archetypes, archetypes.root(), &self.required, &mut result);
+            return;
+        }
+
+        // Relations to any target are not part of the archetype graph, so start from the
+        // archetypes which are indexed by the relation instead
+        let (relations, required): (Vec<&ComponentKey>, Vec<_>) =
+            self.required.iter().partition(|v| is_any_target(v));
+
+        let Some(records) = archetypes.index.find_relation(relations[0].id) else {
+            return;
+        };
+
+        for &arch_id in records.keys() {
+            let arch = archetypes.get(arch_id);
+            if required.iter().all(|&&v| arch.has(v))
+                && relations[1..].iter().all(|v| arch.has_relation(v.id))
+            {
+                result(arch_id, arch)
+            }
+        }
     }
+
+    /// Finds the archetypes which hold the first shared component, or use a shared value of it.
+    ///
+    /// The tags of the shared values are pairs of the component, so both are found through the
+    /// archetype index.
+    fn find_shared<'a>(
+        &self,
+        archetypes: &'a Archetypes,
+        mut result: impl FnMut(ArchetypeId, &'a Archetype),
+    ) {
+        let key = self.shared[0];
+
+        let held = archetypes
+            .index
+            .find(key)
+            .into_iter()
+            .flat_map(|v| v.keys());
+        let tagged = archetypes
+            .index
+            .find_relation(key.id)
+            .into_iter()
+            .flat_map(|v| v.keys())
+            .filter(|&&arch_id| !archetypes.get(arch_id).has(key));
+
+        for &arch_id in held.chain(tagged) {
+            let arch = archetypes.get(arch_id);
+
+            let required = self.required.iter().all(|v| {
+                if is_any_target(v) {
+                    arch.has_relation(v.id)
+                } else {
+                    arch.has(*v)
+                }
+            });
+
+            if required && self.shared[1..].iter().all(|&v| is_shared(arch, v)) {
+                result(arch_id, arch)
+            }
+        }
+    }
+}
+
+/// Returns true if the archetype holds `key`, or uses a shared value of it
+pub(crate) fn is_shared(arch: &Archetype, key: ComponentKey) -> bool {
+    arch.has(key) || arch.has_relation(key.id)
+}
+
+/// Returns true if `key` requires a relation to any target, rather than a specific pair
+pub(crate) fn is_any_target(key: &ComponentKey) -> bool {
+    key.target == Some(dummy())
 }
 
 #[inline]
@@ -1,14 +1,15 @@
-use core::iter::Flatten;
+use core::{iter::Flatten, slice};
 
 use alloc::{
     collections::{BTreeMap, BTreeSet},
+    vec,
     vec::Vec,
 };
 use smallvec::SmallVec;
 
 use crate::{
-    archetype::ArchetypeId,
-    component::ComponentValue,
+    archetype::{ArchetypeId, Slice},
+    component::{ComponentKey, ComponentValue},
     fetch::{FetchAccessData, PreparedFetch},
     filter::Filtered,
     relation::RelationExt,
@@ -17,7 +18,8 @@ use crate::{
 };
 
 use super::{
-    borrow::QueryBorrowState, ArchetypeSearcher, BatchedIter, PreparedArchetype, QueryStrategy,
+    borrow::QueryBorrowState, ArchetypeChunks, ArchetypeSearcher, Chunk, PreparedArchetype,
+    QueryStrategy,
 };
 
 /// Visit entities in topological order following `relation`.
@@ -34,17 +36,53 @@ pub struct Topo {
 #[derive(Default, Debug, Clone)]
 struct State {
     archetypes: Vec<ArchetypeId>,
-    order: Vec<usize>,
+    order: Vec<Visit>,
     archetypes_index: BTreeMap<ArchetypeId, usize>,
+    /// The generation of sparse pairs the order was built from
+    sparse_gen: u32,
+}
+
+/// A node in the dependency graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Node {
+    Archetype(ArchetypeId),
+    /// Subjects of sparse relations are ordered individually as entities in the same archetype
+    /// do not share targets
+    Subject(Entity),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    /// All entities of the archetype at the index
+    Archetype(usize),
+    /// A subject of a sparse relation, which is located when visited
+    Subject(Entity),
 }
 
 impl State {
     fn update<'w, Q: Fetch<'w>>(&mut self, relation: Entity, world: &World, fetch: &'w Q) {
         self.clear();
+        self.sparse_gen = world.sparse_gen();
+
+        let node_of = |target: Entity| {
+            let loc = world.location(target).unwrap();
+            if world
+                .archetypes
+                .get(loc.arch_id)
+                .sparse_targets(relation)
+                .is_some()
+            {
+                Node::Subject(target)
+            } else {
+                Node::Archetype(loc.arch_id)
+            }
+        };
+
         let mut searcher = ArchetypeSearcher::default();
         fetch.searcher(&mut searcher);
         // Maps each entity to all archetypes of its children
         let mut deps: BTreeMap<_, _> = BTreeMap::new();
+        let mut nodes = Vec::new();
 
         searcher.find_archetypes(&world.archetypes, |arch_id, arch| {
             if !fetch.filter_arch(FetchAccessData {
@@ -60,51 +98,89 @@ impl State {
 
             let existing = self.archetypes_index.insert(arch_id, idx);
             debug_assert_eq!(existing, None, "duplicate archetype");
+
+            // The subjects of sparse relations are added below
+            if arch.sparse_targets(relation).is_some() {
+                return;
+            }
+
             // Find dependencies
             let arch_deps: Vec<_> = arch
                 .relations_like(relation)
                 .map(|(key, _)| {
                     assert_eq!(key.id, relation);
-                    let target = key.target.unwrap();
-                    let loc = world.location(target).unwrap();
-                    loc.arch_id
+                    node_of(key.target.unwrap())
                 })
                 .collect();
 
             if !arch_deps.is_empty() {
-                deps.insert(arch_id, arch_deps);
+                deps.insert(Node::Archetype(arch_id), arch_deps);
             }
+
+            nodes.push(Node::Archetype(arch_id));
         });
 
-        fn sort(
-            order: &mut Vec<usize>,
-            visited: &mut BTreeSet<ArchetypeId>,
-            index: &BTreeMap<ArchetypeId, usize>,
-            deps: &BTreeMap<ArchetypeId, Vec<ArchetypeId>>,
-            arch_id: ArchetypeId,
-        ) {
-            if !visited.insert(arch_id) {
+        // Subjects may later move into a matched archetype without the order being rebuilt, so
+        // all subjects are added regardless of the fetch
+        let mut searcher = ArchetypeSearcher::default();
+        searcher.add_required(ComponentKey::new(relation, None));
+        searcher.find_archetypes(&world.archetypes, |_, arch| {
+            let Some(targets) = arch.sparse_targets(relation) else {
                 return;
-            }
+            };
+
+            for (slot, &id) in arch.entities().iter().enumerate() {
+                let subject_deps = targets.get(slot).iter().map(|&v| node_of(v)).collect();
 
-            // Make sure all dependencies i.e; parents, are visited first
-            for &dep in deps.get(&arch_id).into_iter().flatten() {
-                sort(order, visited, index, deps, dep);
+                deps.insert(Node::Subject(id), subject_deps);
+                nodes.push(Node::Subject(id));
             }
+        });
 
-            if let Some(&arch_index) = index.get(&arch_id) {
-                order.push(arch_index);
+        // Iterative, as chains of sparse subjects may be arbitrarily deep
+        fn sort(
+            order: &mut Vec<Visit>,
+            visited: &mut BTreeSet<Node>,
+            index: &BTreeMap<ArchetypeId, usize>,
+            deps: &BTreeMap<Node, Vec<Node>>,
+            node: Node,
+        ) {
+            let mut stack = vec![(node, false)];
+
+            while let Some((node, expanded)) = stack.pop() {
+                if !expanded {
+                    if !visited.insert(node) {
+                        continue;
+                    }
+
+                    // Make sure all dependencies i.e; parents, are visited first
+                    stack.push((node, true));
+                    for &dep in deps.get(&node).into_iter().flatten().rev() {
+                        stack.push((dep, false));
+                    }
+
+                    continue;
+                }
+
+                match node {
+                    Node::Archetype(arch_id) => {
+                        if let Some(&arch_index) = index.get(&arch_id) {
+                            order.push(Visit::Archetype(arch_index));
+                        }
+                    }
+                    Node::Subject(id) => order.push(Visit::Subject(id)),
+                }
             }
         }
 
         let mut visited = BTreeSet::new();
-        for &arch_id in self.archetypes.iter() {
+        for node in nodes {
             sort(
                 &mut self.order,
                 &mut visited,
                 &self.archetypes_index,
                 &deps,
-                arch_id,
+                node,
             )
         }
     }
@@ -138,7 +214,7 @@ where
         query_state: super::borrow::QueryBorrowState<'w, Q, F>,
         dirty: bool,
     ) -> Self::Borrow {
-        if dirty {
+        if dirty || self.state.sparse_gen != query_state.world.sparse_gen() {
             self.state
                 .update(self.relation, query_state.world, query_state.fetch);
         }
@@ -172,6 +248,9 @@ where
     }
 }
 
+type Prepared<'w, Q, F> =
+    PreparedArchetype<'w, <Q as Fetch<'w>>::Prepared, <F as Fetch<'w>>::Prepared>;
+
 /// Borrowed state for [`Topo`] strategy
 pub struct TopoBorrow<'w, Q, F>
 where
@@ -180,8 +259,8 @@ where
 {
     topo: &'w State,
     state: QueryBorrowState<'w, Q, F>,
-    /// Archetypes are in topological order
-    prepared: SmallVec<[PreparedArchetype<'w, Q::Prepared, F::Prepared>; 8]>,
+    /// Prepared archetypes by index, visited in topological order
+    prepared: SmallVec<[Option<Prepared<'w, Q, F>>; 8]>,
 }
 
 impl<'w, 'q, Q, F> IntoIterator for &'q mut TopoBorrow<'w, Q, F>
@@ -208,10 +287,9 @@ where
         if self.prepared.is_empty() {
             self.prepared = self
                 .topo
-                .order
+                .archetypes
                 .iter()
-                .flat_map(|&idx| {
-                    let arch_id = self.topo.archetypes[idx];
+                .map(|&arch_id| {
                     let arch = self.state.world.archetypes.get(arch_id);
 
                     self.state.prepare_fetch(arch_id, arch)
@@ -220,7 +298,14 @@ where
         }
 
         TopoIter {
-            iter: BatchedIter::new(self.prepared.iter_mut()).flatten(),
+            iter: TopoChunks {
+                world: self.state.world,
+                prepared: &mut self.prepared[..],
+                archetypes_index: &self.topo.archetypes_index,
+                order: self.topo.order.iter(),
+                current: None,
+            }
+            .flatten(),
         }
     }
 }
@@ -232,7 +317,65 @@ where
     F: Fetch<'w>,
     'w: 'q,
 {
-    iter: Flatten<BatchedIter<'w, 'q, Q, F>>,
+    iter: Flatten<TopoChunks<'w, 'q, Q, F>>,
+}
+
+/// Yields the chunks of each visited archetype or subject
+struct TopoChunks<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    world: &'w World,
+    prepared: &'q mut [Option<Prepared<'w, Q, F>>],
+    archetypes_index: &'q BTreeMap<ArchetypeId, usize>,
+    order: slice::Iter<'q, Visit>,
+    current: Option<ArchetypeChunks<'q, Q::Prepared, F::Prepared>>,
+}
+
+impl<'w, 'q, Q, F> Iterator for TopoChunks<'w, 'q, Q, F>
+where
+    Q: Fetch<'w>,
+    F: Fetch<'w>,
+    'w: 'q,
+{
+    type Item = Chunk<'q, Q::Prepared>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.current.as_mut().and_then(|v| v.next()) {
+                return Some(chunk);
+            }
+
+            let (arch_index, slots) = match *self.order.next()? {
+                Visit::Archetype(arch_index) => (arch_index, None),
+                Visit::Subject(id) => {
+                    let Ok(loc) = self.world.location(id) else {
+                        continue;
+                    };
+
+                    match self.archetypes_index.get(&loc.arch_id) {
+                        Some(&arch_index) => (arch_index, Some(Slice::single(loc.slot))),
+                        None => continue,
+                    }
+                }
+            };
+
+            let Some(p) = &mut self.prepared[arch_index] else {
+                continue;
+            };
+
+            // Promote the borrow of the fetch to 'q
+            // This is safe because each archetype or subject is visited once
+            let p = unsafe { &mut *(p as *mut PreparedArchetype<_, _>) };
+
+            self.current = Some(match slots {
+                Some(slots) => p.chunks_in(slots),
+                None => p.chunks(),
+            });
+        }
+    }
 }
 
 impl<'w, 'q, Q, F> Iterator for TopoIter<'w, 'q, Q, F>
@@ -321,11 +464,14 @@ mod test {
         let visited = state
             .order
             .iter()
-            .map(|&idx| {
-                let arch_id = state.archetypes[idx];
-                let arch = world.archetypes.get(arch_id);
-
-                arch.entities().to_vec()
+            .map(|&visit| match visit {
+                Visit::Archetype(idx) => {
+                    let arch_id = state.archetypes[idx];
+                    let arch = world.archetypes.get(arch_id);
+
+                    arch.entities().to_vec()
+                }
+                Visit::Subject(id) => vec![id],
             })
             .collect_vec();
 
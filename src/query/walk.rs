@@ -34,7 +34,7 @@ impl<Q> GraphQuery<Q, All> {
     {
         Self {
             relation: relation.id(),
-            fetch: Filtered::new(fetch, All, false),
+            fetch: Filtered::new(fetch, All, false, false),
             change_tick: 0,
             archetype_gen: 0,
             state: Default::default(),
@@ -55,6 +55,7 @@ where
                 self.fetch.fetch,
                 And(self.fetch.filter, filter),
                 self.fetch.include_components,
+                self.fetch.include_disabled,
             ),
             relation: self.relation,
             change_tick: 0,
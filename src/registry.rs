@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+use once_cell::sync::OnceCell;
+
+use crate::{component::ComponentDesc, metadata::registered};
+
+/// Collected for each non-generic component declared using [`component!`](crate::component)
+/// with the [`Registered`](crate::metadata::Registered) metadata.
+#[doc(hidden)]
+pub struct RegistryEntry {
+    desc: fn() -> ComponentDesc,
+}
+
+impl RegistryEntry {
+    #[doc(hidden)]
+    pub const fn new(desc: fn() -> ComponentDesc) -> Self {
+        Self { desc }
+    }
+}
+
+inventory::collect!(RegistryEntry);
+
+/// Lists all components with the [`Registered`](crate::metadata::Registered) metadata, regardless
+/// of whether they have been used.
+///
+/// This allows tooling such as inspectors, scripting or serialization to enumerate the known
+/// components.
+#[derive(Debug, Clone)]
+pub struct ComponentRegistry {
+    components: Vec<ComponentDesc>,
+}
+
+impl ComponentRegistry {
+    /// Collects all registered components
+    pub fn new() -> Self {
+        let mut components = inventory::iter::<RegistryEntry>
+            .into_iter()
+            .map(|v| (v.desc)())
+            .filter(|v| v.meta_ref().has(registered()))
+            .collect::<Vec<_>>();
+
+        components.sort_by_key(|v| (v.name(), v.key()));
+        components.dedup_by_key(|v| v.key());
+
+        Self { components }
+    }
+
+    /// Returns the registry of all registered components in the program
+    pub fn global() -> &'static Self {
+        static REGISTRY: OnceCell<ComponentRegistry> = OnceCell::new();
+        REGISTRY.get_or_init(Self::new)
+    }
+
+    /// Returns the registered components, ordered by name
+    pub fn iter(&self) -> impl Iterator<Item = ComponentDesc> + '_ {
+        self.components.iter().copied()
+    }
+
+    /// Returns the registered component with the given name
+    pub fn get(&self, name: &str) -> Option<ComponentDesc> {
+        self.iter().find(|v| v.name() == name)
+    }
+
+    /// Returns the number of registered components
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns true if no components are registered
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
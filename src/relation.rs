@@ -4,15 +4,16 @@ use core::{
     sync::atomic::AtomicU32,
 };
 
-use alloc::collections::btree_map::Range;
+use alloc::{collections::btree_map::Range, vec::Vec};
 use atomic_refcell::AtomicRef;
 
 use crate::{
-    archetype::{Archetype, RefMut, Slot},
+    archetype::{Archetype, CellData, RefMut, Slot},
     component::{dummy, ComponentKey, ComponentValue},
     entity::EntityKind,
-    fetch::{nth_relation, NthRelation},
+    fetch::{any_target, nth_relation, targeting, AnyTarget, NthRelation, Targeting},
     filter::{WithRelation, WithoutRelation},
+    metadata::{sparse, SparseStorage},
     vtable::{ComponentVTable, UntypedVTable},
     Component, Entity,
 };
@@ -55,6 +56,25 @@ where
         nth_relation(self, n)
     }
 
+    /// Query all relations of the specified kind, matching only entities which have at least one.
+    ///
+    /// Yields `(target, &value)` for each relation. Use in a filter to restrict a query to
+    /// subjects of the relation regardless of target.
+    fn any_target(self) -> AnyTarget<T>
+    where
+        Self: Sized,
+    {
+        any_target(self)
+    }
+
+    /// Query the relation of the specified kind to `target`, yielding `(target, &value)`.
+    fn targeting(self, target: Entity) -> Targeting<T>
+    where
+        Self: Sized,
+    {
+        targeting(self, target)
+    }
+
     /// Query the first relation of the specified kind.
     fn first_relation(self) -> NthRelation<T>
     where
@@ -62,6 +82,22 @@ where
     {
         nth_relation(self, 0)
     }
+
+    /// Returns the component storing the pairs of a [`Sparse`](crate::metadata::Sparse) relation
+    /// for each subject.
+    ///
+    /// This can be used for change detection of the pairs, as well as for accessing all pairs
+    /// of a subject at once.
+    fn sparse_pairs(&self) -> Component<SparsePairs<T>> {
+        Component::from_raw_parts(
+            ComponentKey::new(self.id(), None),
+            sparse_pairs::<T>().vtable(),
+        )
+    }
+}
+
+component! {
+    sparse_pairs<T>: SparsePairs<T> => [SparseStorage],
 }
 
 impl<T, F> RelationExt<T> for F
@@ -164,6 +200,11 @@ where
     pub fn name(&self) -> &'static str {
         self.vtable.name
     }
+
+    /// Returns true if the pairs are stored using [`Sparse`](crate::metadata::Sparse)
+    pub(crate) fn is_sparse(&self) -> bool {
+        self.of(dummy()).desc().meta_ref().has(sparse())
+    }
 }
 
 impl<T: ComponentValue> RelationExt<T> for Relation<T> {
@@ -198,9 +239,190 @@ impl<T: ComponentValue> RelationExt<T> for Relation<T> {
     }
 }
 
+/// The pairs of a [`Sparse`](crate::metadata::Sparse) relation for a single subject, ordered by
+/// target.
+///
+/// See: [`RelationExt::sparse_pairs`]
+#[repr(C)]
+#[derive(Clone)]
+pub struct SparsePairs<T> {
+    // Read without knowing `T` through `SparseTargets`, and must therefore remain the first field
+    targets: Vec<Entity>,
+    values: Vec<T>,
+    /// The change ticks of each pair
+    ticks: Vec<PairTicks>,
+}
+
+/// When a pair of a sparse relation was added and last modified
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PairTicks {
+    pub(crate) added: u32,
+    pub(crate) modified: u32,
+}
+
+impl<T> SparsePairs<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            targets: Vec::new(),
+            values: Vec::new(),
+            ticks: Vec::new(),
+        }
+    }
+
+    /// Returns the value of the pair to `target`
+    pub fn get(&self, target: Entity) -> Option<&T> {
+        let index = self.targets.binary_search(&target).ok()?;
+        Some(&self.values[index])
+    }
+
+    /// Mutably access the pair to `target` along with its change ticks
+    pub(crate) fn get_with_ticks_mut(
+        &mut self,
+        target: Entity,
+    ) -> Option<(&mut T, &mut PairTicks)> {
+        let index = self.targets.binary_search(&target).ok()?;
+        Some((&mut self.values[index], &mut self.ticks[index]))
+    }
+
+    /// Returns the change ticks of the pair to `target`
+    pub(crate) fn ticks(&self, target: Entity) -> Option<PairTicks> {
+        let index = self.targets.binary_search(&target).ok()?;
+        Some(self.ticks[index])
+    }
+
+    /// Returns the value of the nth pair
+    pub fn nth(&self, n: usize) -> Option<(Entity, &T)> {
+        Some((*self.targets.get(n)?, &self.values[n]))
+    }
+
+    /// Returns true if there is a pair to `target`
+    pub fn contains(&self, target: Entity) -> bool {
+        self.targets.binary_search(&target).is_ok()
+    }
+
+    /// Returns the targets of the pairs
+    pub fn targets(&self) -> &[Entity] {
+        &self.targets
+    }
+
+    /// Iterate the targets and values of the pairs
+    pub fn iter(&self) -> SparsePairsIter<'_, T> {
+        SparsePairsIter {
+            targets: self.targets.iter(),
+            values: self.values.iter(),
+        }
+    }
+
+    /// Returns the number of pairs
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns true if there are no pairs
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, target: Entity, value: T, ticks: PairTicks) -> Option<T> {
+        match self.targets.binary_search(&target) {
+            Ok(index) => {
+                self.ticks[index].modified = ticks.modified;
+                Some(core::mem::replace(&mut self.values[index], value))
+            }
+            Err(index) => {
+                self.targets.insert(index, target);
+                self.values.insert(index, value);
+                self.ticks.insert(index, ticks);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, target: Entity) -> Option<T> {
+        let index = self.targets.binary_search(&target).ok()?;
+        self.targets.remove(index);
+        self.ticks.remove(index);
+        Some(self.values.remove(index))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.targets.clear();
+        self.values.clear();
+        self.ticks.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SparsePairs<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// The change ticks are not part of the value
+impl<T: PartialEq> PartialEq for SparsePairs<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.targets == other.targets && self.values == other.values
+    }
+}
+
+impl<T: Eq> Eq for SparsePairs<T> {}
+
+impl<'a, T> IntoIterator for &'a SparsePairs<T> {
+    type Item = (Entity, &'a T);
+
+    type IntoIter = SparsePairsIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterates the pairs of a sparse relation
+#[derive(Debug, Clone)]
+pub struct SparsePairsIter<'a, T> {
+    targets: core::slice::Iter<'a, Entity>,
+    values: core::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for SparsePairsIter<'a, T> {
+    type Item = (Entity, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((*self.targets.next()?, self.values.next()?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.targets.size_hint()
+    }
+}
+
+/// Type erased access to the targets of the [`SparsePairs`] column of an archetype
+pub(crate) struct SparseTargets<'a> {
+    data: AtomicRef<'a, CellData>,
+}
+
+impl<'a> SparseTargets<'a> {
+    /// # Safety
+    ///
+    /// The cell must store [`SparsePairs`]
+    pub(crate) unsafe fn new(data: AtomicRef<'a, CellData>) -> Self {
+        Self { data }
+    }
+
+    /// Returns the targets of the entity at `slot`
+    pub(crate) fn get(&self, slot: Slot) -> &[Entity] {
+        // Safety: `SparsePairs` is `repr(C)`, so the targets are at the start regardless of `T`
+        unsafe {
+            let pairs = self.data.storage.at(slot).expect("Slot out of bounds");
+            &*pairs.cast::<Vec<Entity>>()
+        }
+    }
+}
+
 /// Allows to iterate all relations of a specific type for an entity
 pub struct RelationIter<'a, T> {
     cells: Range<'a, ComponentKey, usize>,
+    sparse: Option<(AtomicRef<'a, SparsePairs<T>>, usize)>,
     arch: &'a Archetype,
     slot: Slot,
     marker: PhantomData<T>,
@@ -208,12 +430,14 @@ pub struct RelationIter<'a, T> {
 
 impl<'a, T: ComponentValue> RelationIter<'a, T> {
     pub(crate) fn new(relation: impl RelationExt<T>, arch: &'a Archetype, slot: Slot) -> Self {
+        let sparse = arch.get(slot, relation.sparse_pairs()).map(|v| (v, 0));
         let relation = relation.id();
         Self {
             cells: arch.components().range(
                 ComponentKey::new(relation, Some(Entity::MIN))
                     ..=ComponentKey::new(relation, Some(Entity::MAX)),
             ),
+            sparse,
             slot,
             marker: PhantomData,
             arch,
@@ -228,6 +452,13 @@ where
     type Item = (Entity, AtomicRef<'a, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some((pairs, index)) = &mut self.sparse {
+            let (target, _) = pairs.nth(*index)?;
+            let value = AtomicRef::map(AtomicRef::clone(pairs), |v| &v.values[*index]);
+            *index += 1;
+            return Some((target, value));
+        }
+
         let (&key, &cell_index) = self.cells.next()?;
         // Safety: the type matches the relation ext
         Some((key.target.unwrap(), unsafe {
@@ -0,0 +1,130 @@
+use core::fmt;
+
+use alloc::{string::String, vec::Vec};
+use itertools::Itertools;
+
+use crate::{system::Access, World};
+
+use super::graph::describe_conflict;
+
+/// A pair of systems which access the same data with at least one of them writing, and are thus
+/// ordered only by the order they were added to the schedule.
+#[derive(Debug, Clone)]
+pub struct Ambiguity {
+    first: String,
+    second: String,
+    reasons: Vec<String>,
+}
+
+impl Ambiguity {
+    /// Returns the name of the system which runs first
+    pub fn first(&self) -> &str {
+        &self.first
+    }
+
+    /// Returns the name of the system which runs second
+    pub fn second(&self) -> &str {
+        &self.second
+    }
+
+    /// Returns the conflicting accesses
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} and {}: {}",
+            self.first,
+            self.second,
+            self.reasons.join(", ")
+        )
+    }
+}
+
+/// Lists the ambiguously ordered systems of a schedule.
+///
+/// See: [`Schedule::check_ambiguities`](crate::Schedule::check_ambiguities)
+#[derive(Debug, Clone, Default)]
+pub struct AmbiguityReport {
+    ambiguities: Vec<Ambiguity>,
+}
+
+impl AmbiguityReport {
+    /// Returns true if there are no ambiguities
+    pub fn is_empty(&self) -> bool {
+        self.ambiguities.is_empty()
+    }
+
+    /// Returns the ambiguous system pairs
+    pub fn ambiguities(&self) -> &[Ambiguity] {
+        &self.ambiguities
+    }
+
+    /// Panics if there are any ambiguities, listing them.
+    ///
+    /// Useful in tests to catch accidental ordering when composing schedules.
+    #[track_caller]
+    pub fn assert_empty(&self) {
+        if !self.is_empty() {
+            panic!("Schedule contains ambiguous system orderings:\n{self}");
+        }
+    }
+}
+
+impl fmt::Display for AmbiguityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ambiguity in &self.ambiguities {
+            writeln!(f, "{ambiguity}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds all pairs of systems with conflicting accesses, or only those which both write to the
+/// same data if `writes_only` is set.
+pub(super) fn ambiguities(
+    systems: &[(&str, Vec<Access>)],
+    allowed: &[(String, String)],
+    writes_only: bool,
+    world: &World,
+) -> AmbiguityReport {
+    let is_allowed = |a: &str, b: &str| {
+        allowed
+            .iter()
+            .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+    };
+
+    let mut ambiguities = Vec::new();
+    for (i, (second, second_accesses)) in systems.iter().enumerate() {
+        for (first, first_accesses) in systems.iter().take(i) {
+            if is_allowed(first, second) {
+                continue;
+            }
+
+            let reasons = first_accesses
+                .iter()
+                .cartesian_product(second_accesses)
+                .filter(|(a, b)| {
+                    !a.is_compatible_with(b) && (!writes_only || (a.mutable && b.mutable))
+                })
+                .map(|(a, b)| describe_conflict(a, b, world))
+                .unique()
+                .collect_vec();
+
+            if !reasons.is_empty() {
+                ambiguities.push(Ambiguity {
+                    first: String::from(*first),
+                    second: String::from(*second),
+                    reasons,
+                });
+            }
+        }
+    }
+
+    AmbiguityReport { ambiguities }
+}
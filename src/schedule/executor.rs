@@ -0,0 +1,161 @@
+use core::fmt;
+
+use alloc::vec::Vec;
+
+/// A single system of a batch, ready to be executed.
+pub struct Job<'a> {
+    func: &'a mut (dyn FnMut() -> anyhow::Result<()> + Send),
+    main_thread: bool,
+}
+
+impl<'a> Job<'a> {
+    pub(super) fn new(
+        func: &'a mut (dyn FnMut() -> anyhow::Result<()> + Send),
+        main_thread: bool,
+    ) -> Self {
+        Self { func, main_thread }
+    }
+
+    /// Executes the system
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        (self.func)()
+    }
+
+    /// Returns true if the job must run on the thread which executes the schedule
+    pub fn is_main_thread(&self) -> bool {
+        self.main_thread
+    }
+}
+
+impl fmt::Debug for Job<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Job")
+            .field("main_thread", &self.main_thread)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Executes the batches of a schedule in parallel.
+///
+/// The systems of a batch are independent of each other, and may run in any order or concurrently.
+/// All jobs must have finished when [`Self::execute_batch`] returns.
+///
+/// Jobs for which [`Job::is_main_thread`] is true must be executed on the calling thread.
+pub trait ScheduleExecutor: Send + Sync {
+    /// Executes all jobs of a batch, returning the first error
+    fn execute_batch(&self, jobs: &mut [Job<'_>]) -> anyhow::Result<()>;
+}
+
+/// Runs every job in order on the calling thread
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SequentialExecutor;
+
+impl ScheduleExecutor for SequentialExecutor {
+    fn execute_batch(&self, jobs: &mut [Job<'_>]) -> anyhow::Result<()> {
+        jobs.iter_mut().try_for_each(Job::run)
+    }
+}
+
+/// Runs the jobs on a rayon thread pool.
+///
+/// Uses the global pool by default.
+#[cfg(feature = "rayon")]
+#[derive(Debug, Default, Clone)]
+pub struct RayonExecutor {
+    pool: Option<alloc::sync::Arc<rayon::ThreadPool>>,
+}
+
+#[cfg(feature = "rayon")]
+impl RayonExecutor {
+    /// Creates an executor using the global rayon pool
+    pub const fn new() -> Self {
+        Self { pool: None }
+    }
+
+    /// Creates an executor which runs the jobs on `pool`
+    pub fn with_pool(pool: alloc::sync::Arc<rayon::ThreadPool>) -> Self {
+        Self { pool: Some(pool) }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ScheduleExecutor for RayonExecutor {
+    fn execute_batch(&self, jobs: &mut [Job<'_>]) -> anyhow::Result<()> {
+        use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+        if !jobs.iter().any(Job::is_main_thread) {
+            let mut execute = || jobs.par_iter_mut().try_for_each(Job::run);
+            return match &self.pool {
+                Some(pool) => pool.install(execute),
+                None => execute(),
+            };
+        }
+
+        let mut results = Vec::new();
+        results.resize_with(jobs.len(), || Ok(()));
+
+        match &self.pool {
+            Some(pool) => pool.in_place_scope(|scope| spawn_jobs(scope, jobs, &mut results)),
+            None => rayon::in_place_scope(|scope| spawn_jobs(scope, jobs, &mut results)),
+        }
+
+        results.into_iter().collect()
+    }
+}
+
+/// Spawns the jobs onto the pool, while running the main thread jobs on the calling thread
+#[cfg(feature = "rayon")]
+fn spawn_jobs<'s, 'j: 's>(
+    scope: &rayon::Scope<'s>,
+    jobs: &'s mut [Job<'j>],
+    results: &'s mut [anyhow::Result<()>],
+) {
+    let mut main_thread = Vec::new();
+    for (job, result) in jobs.iter_mut().zip(results) {
+        if job.is_main_thread() {
+            main_thread.push((job, result));
+        } else {
+            scope.spawn(move |_| *result = job.run());
+        }
+    }
+
+    for (job, result) in main_thread {
+        *result = job.run();
+    }
+}
+
+/// Spawns a scoped thread for each job using [`std::thread::scope`].
+///
+/// Main thread jobs, and the last job if there are none, run on the calling thread.
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadScopeExecutor;
+
+#[cfg(feature = "std")]
+impl ScheduleExecutor for ThreadScopeExecutor {
+    fn execute_batch(&self, jobs: &mut [Job<'_>]) -> anyhow::Result<()> {
+        let (mut main_thread, mut workers): (Vec<_>, Vec<_>) =
+            jobs.iter_mut().partition(|v| v.is_main_thread());
+
+        if main_thread.is_empty() {
+            main_thread.extend(workers.pop());
+        }
+
+        std::thread::scope(|scope| {
+            let handles = workers
+                .into_iter()
+                .map(|job| scope.spawn(move || job.run()))
+                .collect::<Vec<_>>();
+
+            let result = main_thread.into_iter().try_for_each(|job| job.run());
+
+            handles
+                .into_iter()
+                .map(|v| match v.join() {
+                    Ok(v) => v,
+                    Err(payload) => std::panic::resume_unwind(payload),
+                })
+                .fold(result, Result::and)
+        })
+    }
+}
@@ -0,0 +1,158 @@
+use core::fmt::Write;
+
+use alloc::{format, string::String, vec::Vec};
+use itertools::Itertools;
+
+use crate::{
+    system::{Access, AccessKind},
+    World,
+};
+
+use super::BatchInfos;
+
+/// A dependency between two systems which prevents them from running in parallel
+#[derive(Debug, Clone)]
+pub struct SystemDependency {
+    pub(super) src: usize,
+    pub(super) dst: usize,
+    pub(super) reasons: Vec<String>,
+}
+
+impl SystemDependency {
+    /// Returns the index of the system which runs first.
+    ///
+    /// See: [`BatchInfos::systems`]
+    pub fn src(&self) -> usize {
+        self.src
+    }
+
+    /// Returns the index of the system which depends on the `src` system
+    pub fn dst(&self) -> usize {
+        self.dst
+    }
+
+    /// Returns the conflicting accesses which cause the dependency
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+}
+
+/// Collects the dependencies between systems given in execution order
+pub(super) fn dependencies(accesses: &[Vec<Access>], world: &World) -> Vec<SystemDependency> {
+    let mut result = Vec::new();
+    for (dst, dst_accesses) in accesses.iter().enumerate() {
+        for (src, src_accesses) in accesses.iter().take(dst).enumerate() {
+            let reasons = src_accesses
+                .iter()
+                .cartesian_product(dst_accesses)
+                .filter(|(a, b)| !a.is_compatible_with(b))
+                .map(|(a, b)| describe_conflict(a, b, world))
+                .unique()
+                .collect_vec();
+
+            if !reasons.is_empty() {
+                result.push(SystemDependency { src, dst, reasons });
+            }
+        }
+    }
+
+    result
+}
+
+pub(super) fn describe_conflict(src: &Access, dst: &Access, world: &World) -> String {
+    let mode = |v: &Access| if v.mutable { "write" } else { "read" };
+
+    if src.kind == dst.kind {
+        let modes = format!("({} -> {})", mode(src), mode(dst));
+        return describe_access(src.kind, &modes, world);
+    }
+
+    // E.g; a world access conflicting with a component access
+    format!(
+        "{} -> {}",
+        describe_access(src.kind, &format!("({})", mode(src)), world),
+        describe_access(dst.kind, &format!("({})", mode(dst)), world),
+    )
+}
+
+fn describe_access(kind: AccessKind, modes: &str, world: &World) -> String {
+    match kind {
+        AccessKind::Archetype { id, component } => {
+            let arch = world.archetypes.get(id);
+            let name = arch.component(component).map(|v| v.name()).unwrap_or("?");
+
+            let components = arch.components_desc().map(|v| v.name()).join(", ");
+
+            format!("{name} {modes} in archetype [{components}]")
+        }
+        AccessKind::External(ty) => format!("external {ty:?} {modes}"),
+        AccessKind::World => format!("world {modes}"),
+        AccessKind::CommandBuffer => format!("command buffer {modes}"),
+        AccessKind::Input(ty) => format!("input {ty:?} {modes}"),
+        AccessKind::NonSend(ty) => format!("non-send {ty:?} {modes}"),
+    }
+}
+
+impl BatchInfos {
+    /// Exports the batches and the dependencies between systems as a Graphviz DOT graph.
+    ///
+    /// Each batch is a cluster of systems which may run in parallel, and each edge is labeled
+    /// with the conflicting accesses.
+    pub fn to_dot(&self) -> String {
+        fn escape(v: &str) -> String {
+            v.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        let mut s = String::new();
+        s.push_str("digraph schedule {\n    compound=true;\n    node [shape=box];\n");
+
+        let mut index = 0;
+        for (i, batch) in self.iter().enumerate() {
+            writeln!(s, "    subgraph cluster_{i} {{").unwrap();
+            writeln!(s, "        label=\"batch {i}\";").unwrap();
+            for system in batch.iter() {
+                writeln!(s, "        s{index} [label=\"{}\"];", escape(system.name())).unwrap();
+                index += 1;
+            }
+            s.push_str("    }\n");
+        }
+
+        for dep in &self.deps {
+            let label = dep.reasons.iter().map(|v| escape(v)).join("\\n");
+            writeln!(s, "    s{} -> s{} [label=\"{label}\"];", dep.src, dep.dst).unwrap();
+        }
+
+        s.push_str("}\n");
+        s
+    }
+
+    /// Exports the batches and the dependencies between systems as a Mermaid flowchart.
+    ///
+    /// Each batch is a subgraph of systems which may run in parallel, and each edge is labeled
+    /// with the conflicting accesses.
+    pub fn to_mermaid(&self) -> String {
+        fn escape(v: &str) -> String {
+            v.replace('"', "#quot;")
+        }
+
+        let mut s = String::new();
+        s.push_str("flowchart TD\n");
+
+        let mut index = 0;
+        for (i, batch) in self.iter().enumerate() {
+            writeln!(s, "    subgraph batch_{i} [\"batch {i}\"]").unwrap();
+            for system in batch.iter() {
+                writeln!(s, "        s{index}[\"{}\"]", escape(system.name())).unwrap();
+                index += 1;
+            }
+            s.push_str("    end\n");
+        }
+
+        for dep in &self.deps {
+            let label = dep.reasons.iter().map(|v| escape(v)).join("<br/>");
+            writeln!(s, "    s{} -->|\"{label}\"| s{}", dep.src, dep.dst).unwrap();
+        }
+
+        s
+    }
+}
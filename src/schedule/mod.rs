@@ -1,16 +1,37 @@
+mod ambiguity;
+mod executor;
+mod graph;
+mod report;
+mod states;
+mod stats;
+
 use core::{mem, ops::Deref};
 
-use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 
 use anyhow::Context;
 use itertools::Itertools;
 
 use crate::{
-    system::{access_info, AccessInfo, IntoInput, SystemContext},
+    system::{access_info, AccessInfo, AccessKind, IntoInput, SystemContext},
     util::Verbatim,
     BoxedSystem, CommandBuffer, System, World,
 };
 
+pub use ambiguity::{Ambiguity, AmbiguityReport};
+#[cfg(feature = "rayon")]
+pub use executor::RayonExecutor;
+#[cfg(feature = "std")]
+pub use executor::ThreadScopeExecutor;
+pub use executor::{Job, ScheduleExecutor, SequentialExecutor};
+pub use graph::SystemDependency;
+pub use report::{ErrorPolicy, ScheduleReport, SystemFailure};
+pub use states::{state, InState, OnEnter, OnExit, State, StateHook, StateValue, States};
+pub use stats::{ScheduleStats, TimingStats};
+
+use report::{ErrorHandler, Outcome};
+use stats::Timer;
+
 fn flush_system() -> BoxedSystem {
     System::builder()
         .with_name("flush")
@@ -24,10 +45,23 @@ fn flush_system() -> BoxedSystem {
         .boxed()
 }
 
-#[derive(Default, Debug)]
+#[derive(Default)]
 /// Incrementally construct a schedule constisting of systems
 pub struct ScheduleBuilder {
     systems: Vec<BoxedSystem>,
+    allowed_ambiguities: Vec<(String, String)>,
+    stats: bool,
+    executor: Option<Box<dyn ScheduleExecutor>>,
+    main_thread: Vec<String>,
+    errors: ErrorHandler,
+}
+
+impl core::fmt::Debug for ScheduleBuilder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScheduleBuilder")
+            .field("systems", &self.systems)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ScheduleBuilder {
@@ -48,9 +82,71 @@ impl ScheduleBuilder {
         self.with_system(flush_system())
     }
 
+    /// Mark the ordering between two systems as intentional, excluding them from
+    /// [`Schedule::check_ambiguities`].
+    ///
+    /// The systems are identified by name.
+    pub fn allow_ambiguity(&mut self, a: impl Into<String>, b: impl Into<String>) -> &mut Self {
+        self.allowed_ambiguities.push((a.into(), b.into()));
+        self
+    }
+
+    /// Collect timing statistics for each system and batch.
+    ///
+    /// See: [`Schedule::with_stats`]
+    #[cfg(feature = "std")]
+    pub fn with_stats(&mut self) -> &mut Self {
+        self.stats = true;
+        self
+    }
+
+    /// Set the executor used to run the batches in parallel.
+    ///
+    /// See: [`Schedule::with_executor`]
+    pub fn with_executor(&mut self, executor: impl ScheduleExecutor + 'static) -> &mut Self {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
+    /// Always run the system with the given name on the thread executing the schedule.
+    ///
+    /// See: [`Schedule::pin_to_main_thread`]
+    pub fn pin_to_main_thread(&mut self, name: impl Into<String>) -> &mut Self {
+        self.main_thread.push(name.into());
+        self
+    }
+
+    /// Set the policy for handling failing systems.
+    ///
+    /// See: [`Schedule::with_error_policy`]
+    pub fn with_error_policy(&mut self, policy: ErrorPolicy) -> &mut Self {
+        self.errors.default = policy;
+        self
+    }
+
+    /// Set the policy for handling failures of the system with the given name.
+    ///
+    /// See: [`Schedule::with_system_error_policy`]
+    pub fn with_system_error_policy(
+        &mut self,
+        name: impl Into<String>,
+        policy: ErrorPolicy,
+    ) -> &mut Self {
+        self.errors.policies.push((name.into(), policy));
+        self
+    }
+
     /// Build the schedule
     pub fn build(&mut self) -> Schedule {
-        Schedule::from_systems(mem::take(&mut self.systems))
+        let mut schedule = Schedule::from_systems(mem::take(&mut self.systems));
+        schedule.allowed_ambiguities = mem::take(&mut self.allowed_ambiguities);
+        schedule.executor = self.executor.take();
+        schedule.main_thread = mem::take(&mut self.main_thread);
+        schedule.errors = mem::take(&mut self.errors);
+        if self.stats {
+            schedule.stats = Some(Default::default());
+        }
+        schedule
     }
 }
 
@@ -84,13 +180,23 @@ impl SystemInfo {
 pub struct Schedule {
     systems: Vec<Vec<BoxedSystem>>,
     cmd: CommandBuffer,
+    allowed_ambiguities: Vec<(String, String)>,
+    stats: Option<ScheduleStats>,
+    executor: Option<Box<dyn ScheduleExecutor>>,
+    main_thread: Vec<String>,
+    /// Whether each system must run on the thread executing the schedule
+    pinned: Vec<Vec<bool>>,
+    errors: ErrorHandler,
 
     archetype_gen: u32,
 }
 
 /// Holds information regarding a schedule's batches
 #[derive(Debug, Clone)]
-pub struct BatchInfos(Vec<BatchInfo>);
+pub struct BatchInfos {
+    batches: Vec<BatchInfo>,
+    deps: Vec<SystemDependency>,
+}
 
 impl BatchInfos {
     /// Converts the batches into just a list of system names
@@ -99,13 +205,25 @@ impl BatchInfos {
             .map(|v| v.iter().map(|v| v.name().into()).collect_vec())
             .collect_vec()
     }
+
+    /// Returns all systems in execution order
+    pub fn systems(&self) -> impl Iterator<Item = &SystemInfo> {
+        self.iter().flat_map(|v| v.iter())
+    }
+
+    /// Returns the dependencies between the systems, which prevent them from running in parallel.
+    ///
+    /// The systems are referred to by their index in [`Self::systems`].
+    pub fn dependencies(&self) -> &[SystemDependency] {
+        &self.deps
+    }
 }
 
 impl Deref for BatchInfos {
     type Target = [BatchInfo];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.batches
     }
 }
 
@@ -157,14 +275,19 @@ impl Schedule {
     }
 
     /// Execute all systems in the schedule sequentially on the world.
-    /// Returns the first error and aborts if the execution fails.
-    pub fn execute_seq(&mut self, world: &mut World) -> anyhow::Result<()> {
+    ///
+    /// Failing systems are handled according to their [`ErrorPolicy`]. Returns the first error of
+    /// a system with [`ErrorPolicy::Abort`], with any previous failures attached as context,
+    /// otherwise the failures are collected into the returned [`ScheduleReport`].
+    pub fn execute_seq(&mut self, world: &mut World) -> anyhow::Result<ScheduleReport> {
         self.execute_seq_with(world, &mut ())
     }
 
-    #[cfg(feature = "rayon")]
     /// Executes the systems in the schedule in parallel.
     ///
+    /// The batches are dispatched through the schedule's [`ScheduleExecutor`], which defaults to
+    /// `RayonExecutor` if the `rayon` feature is enabled, and [`SequentialExecutor`] otherwise.
+    ///
     /// Systems will run in an order such that changes and mutable accesses made by systems
     /// provided
     /// *before* are observable when the system runs.
@@ -174,7 +297,9 @@ impl Schedule {
     ///
     /// A dependency between two systems is given by a side effect, e.g; a component write, which
     /// is accessed by the seconds system through a read or other side effect.
-    pub fn execute_par(&mut self, world: &mut World) -> anyhow::Result<()> {
+    ///
+    /// Failing systems are handled according to their [`ErrorPolicy`], see [`Self::execute_seq`].
+    pub fn execute_par(&mut self, world: &mut World) -> anyhow::Result<ScheduleReport> {
         self.execute_par_with(world, &mut ())
     }
 
@@ -184,19 +309,30 @@ impl Schedule {
             systems: alloc::vec![systems.into()],
             archetype_gen: 0,
             cmd: CommandBuffer::new(),
+            allowed_ambiguities: Vec::new(),
+            stats: None,
+            executor: None,
+            main_thread: Vec::new(),
+            pinned: Vec::new(),
+            errors: ErrorHandler::default(),
         }
     }
 
     /// Append one schedule onto another
     pub fn append(&mut self, other: Self) {
         self.archetype_gen = 0;
-        self.systems.extend(other.systems)
+        self.pinned.clear();
+        self.systems.extend(other.systems);
+        self.allowed_ambiguities.extend(other.allowed_ambiguities);
+        self.main_thread.extend(other.main_thread);
+        self.errors.append(other.errors);
     }
 
     /// Add a new system to the schedule.
     /// Respects order.
     pub fn with_system(mut self, system: impl Into<BoxedSystem>) -> Self {
         self.archetype_gen = 0;
+        self.pinned.clear();
         let v = match self.systems.first_mut() {
             Some(v) => v,
             None => {
@@ -209,6 +345,124 @@ impl Schedule {
         self
     }
 
+    /// Mark the ordering between two systems as intentional, excluding them from
+    /// [`Self::check_ambiguities`].
+    ///
+    /// The systems are identified by name.
+    pub fn allow_ambiguity(mut self, a: impl Into<String>, b: impl Into<String>) -> Self {
+        self.allowed_ambiguities.push((a.into(), b.into()));
+        self
+    }
+
+    /// Returns all pairs of systems which access the same data with at least one of them
+    /// writing, such as a component, the world, the command buffer, an external resource or the
+    /// schedule input.
+    ///
+    /// The order of such systems is only determined by the order they were added to the
+    /// schedule, which may be accidental when composing schedules from different sources.
+    ///
+    /// Use [`Self::allow_ambiguity`] to mark a pair as intentionally ordered, or
+    /// [`Self::check_write_ambiguities`] to only consider systems which both write.
+    pub fn check_ambiguities(&self, world: &World) -> AmbiguityReport {
+        self.find_ambiguities(false, world)
+    }
+
+    /// Returns all pairs of systems which both write to the same data.
+    ///
+    /// Reads are not considered, which is useful when a read after a write is the expected flow
+    /// of data. See [`Self::check_ambiguities`].
+    pub fn check_write_ambiguities(&self, world: &World) -> AmbiguityReport {
+        self.find_ambiguities(true, world)
+    }
+
+    fn find_ambiguities(&self, writes_only: bool, world: &World) -> AmbiguityReport {
+        let systems = self
+            .systems
+            .iter()
+            .flatten()
+            .map(|system| {
+                let mut access = Vec::new();
+                system.access(world, &mut access);
+                (system.name(), access)
+            })
+            .collect_vec();
+
+        ambiguity::ambiguities(&systems, &self.allowed_ambiguities, writes_only, world)
+    }
+
+    /// Set the policy for handling failing systems.
+    ///
+    /// Defaults to [`ErrorPolicy::Abort`].
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.errors.default = policy;
+        self
+    }
+
+    /// Set the policy for handling failures of the system with the given name, overriding the
+    /// schedule wide policy.
+    pub fn with_system_error_policy(
+        mut self,
+        name: impl Into<String>,
+        policy: ErrorPolicy,
+    ) -> Self {
+        self.errors.policies.push((name.into(), policy));
+        self
+    }
+
+    /// Enables a system which was disabled by [`ErrorPolicy::Disable`].
+    ///
+    /// Returns true if the system was disabled.
+    pub fn enable_system(&mut self, name: &str) -> bool {
+        self.errors.enable(name)
+    }
+
+    /// Returns the names of the systems disabled by [`ErrorPolicy::Disable`]
+    pub fn disabled_systems(&self) -> impl Iterator<Item = &str> {
+        self.errors.disabled()
+    }
+
+    /// Collect wall-clock timing statistics for each system and batch when executing.
+    ///
+    /// Batch statistics are only collected by [`Self::execute_par`].
+    ///
+    /// See: [`Self::stats`]
+    #[cfg(feature = "std")]
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(Default::default());
+        self
+    }
+
+    /// Returns the timing statistics collected, if enabled using [`Self::with_stats`].
+    ///
+    /// The statistics can be printed as a table using `Display`.
+    pub fn stats(&self) -> Option<&ScheduleStats> {
+        self.stats.as_ref()
+    }
+
+    /// Clears the collected timing statistics
+    pub fn reset_stats(&mut self) {
+        if let Some(stats) = &mut self.stats {
+            *stats = Default::default();
+        }
+    }
+
+    /// Set the executor used by [`Self::execute_par`] to run each batch of systems.
+    pub fn with_executor(mut self, executor: impl ScheduleExecutor + 'static) -> Self {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
+    /// Always run the system with the given name on the thread executing the schedule.
+    ///
+    /// The system still runs in parallel with the other systems of its batch.
+    ///
+    /// Systems accessing non-send resources are pinned automatically.
+    pub fn pin_to_main_thread(mut self, name: impl Into<String>) -> Self {
+        self.main_thread.push(name.into());
+        self.pinned.clear();
+        self
+    }
+
     /// Applies the commands inside of the commandbuffer
     pub fn flush(self) -> Self {
         self.with_system(flush_system())
@@ -218,27 +472,39 @@ impl Schedule {
     pub fn batch_info(&mut self, world: &World) -> BatchInfos {
         self.systems = Self::build_dependencies(mem::take(&mut self.systems), world);
 
+        let accesses = self
+            .systems
+            .iter()
+            .flatten()
+            .map(|system| {
+                let mut access = Vec::new();
+                system.access(world, &mut access);
+                access
+            })
+            .collect_vec();
+
+        let mut system_accesses = accesses.iter();
         let batches = self
             .systems
             .iter()
             .map(|batch| {
                 let systems = batch
                     .iter()
-                    .map(|system| {
-                        let mut access = Vec::new();
-                        system.access(world, &mut access);
-                        SystemInfo {
-                            name: system.name().into(),
-                            desc: Verbatim(alloc::format!("{system:#?}")),
-                            access: access_info(&access, world),
-                        }
+                    .zip(&mut system_accesses)
+                    .map(|(system, access)| SystemInfo {
+                        name: system.name().into(),
+                        desc: Verbatim(alloc::format!("{system:#?}")),
+                        access: access_info(access, world),
                     })
                     .collect_vec();
                 BatchInfo(systems)
             })
             .collect_vec();
 
-        BatchInfos(batches)
+        BatchInfos {
+            batches,
+            deps: graph::dependencies(&accesses, world),
+        }
     }
 
     /// Same as [`Self::execute_seq`] but allows supplying short lived input available to the systems
@@ -248,7 +514,7 @@ impl Schedule {
         &'a mut self,
         world: &'a mut World,
         input: impl IntoInput<'a>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<ScheduleReport> {
         profile_function!();
         let input = input.into_input();
         let ctx = SystemContext::new(world, &mut self.cmd, &input);
@@ -256,24 +522,35 @@ impl Schedule {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("execute_seq").entered();
 
+        // The systems are not necessarily batched yet, so only the systems are timed
+        let mut report = ScheduleReport::default();
         for system in self.systems.iter_mut().flatten() {
-            system.execute(&ctx)?
+            if let Err(err) =
+                Self::execute_system(system, &ctx, &mut self.stats, &mut self.errors, &mut report)
+            {
+                return Err(report.abort(err));
+            }
         }
 
         self.cmd
             .apply(world)
-            .context("Failed to apply commandbuffer")
+            .context("Failed to apply commandbuffer")?;
+
+        Ok(report)
     }
 
-    #[cfg(feature = "rayon")]
     /// Same as [`Self::execute_par`] but allows supplying short lived data available to the systems
     pub fn execute_par_with<'a>(
         &'a mut self,
         world: &'a mut World,
         input: impl IntoInput<'a>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<ScheduleReport> {
         profile_function!();
-        use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+        #[cfg(feature = "rayon")]
+        static DEFAULT_EXECUTOR: RayonExecutor = RayonExecutor::new();
+        #[cfg(not(feature = "rayon"))]
+        static DEFAULT_EXECUTOR: SequentialExecutor = SequentialExecutor;
 
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("execute_par").entered();
@@ -283,44 +560,171 @@ impl Schedule {
         if self.archetype_gen != w_gen {
             self.archetype_gen = w_gen;
             self.systems = Self::build_dependencies(mem::take(&mut self.systems), world);
+            self.pinned.clear();
+        }
+
+        if self.pinned.len() != self.systems.len() {
+            self.pinned = self.pinned_systems(world);
         }
 
         let input = input.into_input();
         let mut ctx = SystemContext::new(world, &mut self.cmd, &input);
 
+        let executor = self.executor.as_deref().unwrap_or(&DEFAULT_EXECUTOR);
         let mut batches = self.systems.iter_mut();
+        let mut outcomes = Vec::new();
+        let mut report = ScheduleReport::default();
+        let enabled = self.stats.is_some();
+
+        for ((i, batch), pinned) in (&mut batches).enumerate().zip(&self.pinned) {
+            let timer = Timer::start(enabled);
+            outcomes.clear();
+            outcomes.resize_with(batch.len(), Outcome::default);
+
+            let skipped = batch
+                .iter()
+                .map(|system| self.errors.skip(system.name()))
+                .collect_vec();
+
+            let result = {
+                let ctx = &ctx;
+                let errors = &self.errors;
+                let mut funcs = batch
+                    .iter_mut()
+                    .zip(&mut outcomes)
+                    .zip(pinned.iter().zip(skipped))
+                    .map(|((system, outcome), (&pinned, skip))| {
+                        let abort = errors.policy(system.name()) == ErrorPolicy::Abort;
+
+                        let func = move || {
+                            if skip {
+                                return Ok(());
+                            }
+
+                            let timer = Timer::start(enabled);
+                            match system.execute(ctx) {
+                                Ok(()) => *outcome = Outcome::Succeeded(timer.elapsed()),
+                                Err(err) if abort => return Err(err),
+                                Err(err) => *outcome = Outcome::Failed(err),
+                            }
+
+                            anyhow::Ok(())
+                        };
+
+                        (func, pinned)
+                    })
+                    .collect_vec();
 
-        for batch in &mut batches {
-            batch
-                .par_iter_mut()
-                .try_for_each(|system| system.execute(&ctx))?;
+                let mut jobs = funcs
+                    .iter_mut()
+                    .map(|(func, pinned)| Job::new(func, *pinned))
+                    .collect_vec();
+
+                executor.execute_batch(&mut jobs)
+            };
+
+            // Systems which completed before an aborting system are still accounted for
+            for (system, outcome) in batch.iter().zip(outcomes.drain(..)) {
+                match outcome {
+                    Outcome::Skipped => {}
+                    Outcome::Succeeded(duration) => {
+                        self.errors.succeeded(system.name());
+                        if let (Some(stats), Some(duration)) = (&mut self.stats, duration) {
+                            stats.record_system(system.name(), duration);
+                        }
+                    }
+                    Outcome::Failed(err) => self.errors.failed(system.name(), err, &mut report)?,
+                }
+            }
+
+            if let Err(err) = result {
+                return Err(report.abort(err));
+            }
+
+            if let (Some(stats), Some(elapsed)) = (&mut self.stats, timer.elapsed()) {
+                stats.record_batch(i, elapsed);
+            }
 
             // If the archetype generation changed the batches are invalidated
             //
             // Execute sequentially, and rebuild the schedule next time around
             if self.archetype_gen != ctx.world.get_mut().archetype_gen() {
-                return Self::bail_seq(batches, &mut ctx);
+                for (j, batch) in batches.enumerate() {
+                    let timer = Timer::start(enabled);
+
+                    for system in batch {
+                        if let Err(err) = Self::execute_system(
+                            system,
+                            &ctx,
+                            &mut self.stats,
+                            &mut self.errors,
+                            &mut report,
+                        ) {
+                            return Err(report.abort(err));
+                        }
+                    }
+
+                    if let (Some(stats), Some(elapsed)) = (&mut self.stats, timer.elapsed()) {
+                        stats.record_batch(i + 1 + j, elapsed);
+                    }
+                }
+
+                break;
             }
         }
 
         self.cmd
             .apply(world)
-            .context("Failed to apply commandbuffer")
+            .context("Failed to apply commandbuffer")?;
+
+        Ok(report)
     }
 
-    #[cfg(feature = "rayon")]
-    fn bail_seq(
-        batches: core::slice::IterMut<Vec<BoxedSystem>>,
-        ctx: &mut SystemContext<'_, '_, '_>,
+    /// Returns whether each system is pinned by name or accesses non-send data
+    fn pinned_systems(&self, world: &World) -> Vec<Vec<bool>> {
+        let mut access = Vec::new();
+        self.systems
+            .iter()
+            .map(|batch| {
+                batch
+                    .iter()
+                    .map(|system| {
+                        access.clear();
+                        system.access(world, &mut access);
+
+                        self.main_thread.iter().any(|v| v == system.name())
+                            || access
+                                .iter()
+                                .any(|v| matches!(v.kind, AccessKind::NonSend(_)))
+                    })
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+
+    fn execute_system(
+        system: &mut BoxedSystem,
+        ctx: &SystemContext<'_, '_, '_>,
+        stats: &mut Option<ScheduleStats>,
+        errors: &mut ErrorHandler,
+        report: &mut ScheduleReport,
     ) -> anyhow::Result<()> {
-        for system in batches.flatten() {
-            system.execute(ctx)?;
+        if errors.skip(system.name()) {
+            return Ok(());
+        }
+
+        let timer = Timer::start(stats.is_some());
+        match system.execute(ctx) {
+            Ok(()) => {
+                errors.succeeded(system.name());
+                if let (Some(stats), Some(elapsed)) = (stats, timer.elapsed()) {
+                    stats.record_system(system.name(), elapsed);
+                }
+            }
+            Err(err) => errors.failed(system.name(), err, report)?,
         }
 
-        ctx.cmd
-            .get_mut()
-            .apply(ctx.world.get_mut())
-            .context("Failed to apply commandbuffer")
+        Ok(())
     }
 
     fn build_dependencies(systems: Vec<Vec<BoxedSystem>>, world: &World) -> Vec<Vec<BoxedSystem>> {
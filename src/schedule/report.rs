@@ -0,0 +1,251 @@
+use core::{fmt, time::Duration};
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Determines how a schedule reacts to a failing system
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop executing the schedule and return the error
+    #[default]
+    Abort,
+    /// Log the error using `tracing`, if enabled, and continue executing the remaining systems
+    Log,
+    /// Continue executing the remaining systems, and retry the system with an exponential
+    /// backoff.
+    ///
+    /// The system is retried the next time the schedule executes, and each further consecutive
+    /// failure doubles the number of executions it is skipped for, up to `max_delay`. A success
+    /// resets the backoff.
+    Retry {
+        /// The maximum number of executions the system is skipped for
+        max_delay: u32,
+    },
+    /// Continue executing the remaining systems, but disable the system after `after` consecutive
+    /// failures.
+    ///
+    /// Disabled systems can be enabled again using [`Schedule::enable_system`](crate::Schedule::enable_system).
+    Disable {
+        /// The number of consecutive failures before disabling the system
+        after: u32,
+    },
+}
+
+/// A system which failed during the execution of a schedule
+#[derive(Debug)]
+pub struct SystemFailure {
+    name: String,
+    error: anyhow::Error,
+    disabled: bool,
+}
+
+impl SystemFailure {
+    /// Returns the name of the failed system
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the error of the system
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+
+    /// Returns true if the system was disabled due to this failure
+    pub fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+impl fmt::Display for SystemFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:#}", self.name, self.error)?;
+        if self.disabled {
+            f.write_str(" (disabled)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The systems which failed without aborting the execution of a schedule.
+///
+/// See: [`ErrorPolicy`]
+#[derive(Debug, Default)]
+pub struct ScheduleReport {
+    failures: Vec<SystemFailure>,
+}
+
+impl ScheduleReport {
+    /// Returns true if no systems failed
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns the failed systems in the order they failed
+    pub fn failures(&self) -> &[SystemFailure] {
+        &self.failures
+    }
+
+    /// Consumes the report, returning the failed systems
+    pub fn into_failures(self) -> Vec<SystemFailure> {
+        self.failures
+    }
+
+    pub(super) fn append(&mut self, other: Self) {
+        self.failures.extend(other.failures);
+    }
+
+    /// Attaches the failures collected so far to the error which aborted the execution
+    pub(super) fn abort(self, error: anyhow::Error) -> anyhow::Error {
+        if self.is_ok() {
+            return error;
+        }
+
+        let report = self.to_string();
+        error.context(alloc::format!("Aborted after {}", report.trim_end()))
+    }
+}
+
+impl fmt::Display for ScheduleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.failures.is_empty() {
+            return f.write_str("all systems succeeded");
+        }
+
+        writeln!(f, "{} systems failed:", self.failures.len())?;
+        for failure in &self.failures {
+            writeln!(f, "  {failure}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The result of executing a single system in a batch
+#[derive(Default)]
+pub(super) enum Outcome {
+    /// The system is disabled
+    #[default]
+    Skipped,
+    Succeeded(Option<Duration>),
+    Failed(anyhow::Error),
+}
+
+/// Applies the error policies of a schedule, and keeps track of failing systems.
+///
+/// Systems are identified by name.
+#[derive(Debug, Default)]
+pub(super) struct ErrorHandler {
+    pub(super) default: ErrorPolicy,
+    pub(super) policies: Vec<(String, ErrorPolicy)>,
+    /// The number of consecutive failures of each system
+    failures: BTreeMap<String, u32>,
+    /// The remaining number of executions to skip before retrying a system
+    delays: BTreeMap<String, u32>,
+    disabled: BTreeSet<String>,
+}
+
+impl ErrorHandler {
+    pub(super) fn policy(&self, name: &str) -> ErrorPolicy {
+        self.policies
+            .iter()
+            .rev()
+            .find(|v| v.0 == name)
+            .map(|v| v.1)
+            .unwrap_or(self.default)
+    }
+
+    pub(super) fn is_disabled(&self, name: &str) -> bool {
+        !self.disabled.is_empty() && self.disabled.contains(name)
+    }
+
+    /// Returns true if the system should not execute this time, either due to being disabled or
+    /// waiting to be retried
+    pub(super) fn skip(&mut self, name: &str) -> bool {
+        if self.is_disabled(name) {
+            return true;
+        }
+
+        match self.delays.get_mut(name) {
+            Some(delay) if *delay > 0 => {
+                *delay -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(super) fn enable(&mut self, name: &str) -> bool {
+        self.failures.remove(name);
+        self.delays.remove(name);
+        self.disabled.remove(name)
+    }
+
+    pub(super) fn disabled(&self) -> impl Iterator<Item = &str> {
+        self.disabled.iter().map(|v| v.as_str())
+    }
+
+    pub(super) fn append(&mut self, other: Self) {
+        self.policies.extend(other.policies);
+    }
+
+    pub(super) fn succeeded(&mut self, name: &str) {
+        if !self.failures.is_empty() {
+            self.failures.remove(name);
+            self.delays.remove(name);
+        }
+    }
+
+    /// Applies the policy of a failed system.
+    ///
+    /// Returns the error if the execution should be aborted
+    pub(super) fn failed(
+        &mut self,
+        name: &str,
+        error: anyhow::Error,
+        report: &mut ScheduleReport,
+    ) -> anyhow::Result<()> {
+        let mut disabled = false;
+        match self.policy(name) {
+            ErrorPolicy::Abort => return Err(error),
+            ErrorPolicy::Log => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(system = name, "{error:?}");
+            }
+            ErrorPolicy::Retry { max_delay } => {
+                let count = self.failures.entry(name.into()).or_default();
+                *count += 1;
+
+                // Retry the next execution after the first failure, then skip 1, 3, 7, ... executions
+                let delay = 1u32
+                    .checked_shl(*count - 1)
+                    .map_or(max_delay, |v| (v - 1).min(max_delay));
+
+                self.delays.insert(name.into(), delay);
+            }
+            ErrorPolicy::Disable { after } => {
+                let count = self.failures.entry(name.into()).or_default();
+                *count += 1;
+
+                if *count >= after {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(system = name, "Disabling system after {count} failures");
+                    self.failures.remove(name);
+                    self.disabled.insert(name.into());
+                    disabled = true;
+                }
+            }
+        }
+
+        report.failures.push(SystemFailure {
+            name: name.into(),
+            error,
+            disabled,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use core::{fmt::Debug, mem};
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::{component::ComponentValue, components::resources, RefMut, World};
+
+use super::{Schedule, ScheduleReport};
+
+/// A value which can be used as an application state
+pub trait StateValue: ComponentValue + Clone + PartialEq + Debug {}
+
+impl<T> StateValue for T where T: ComponentValue + Clone + PartialEq + Debug {}
+
+component! {
+    /// The current state of type `S`, stored on the [`resources`] entity.
+    ///
+    /// See: [`States`]
+    pub state<S: StateValue>: State<S>,
+}
+
+/// Resource holding the current state and the queued transitions.
+///
+/// Transitions are applied the next time the [`States`] are executed.
+#[derive(Debug, Clone)]
+pub struct State<S> {
+    current: S,
+    queued: VecDeque<S>,
+}
+
+impl<S: StateValue> State<S> {
+    /// Creates a new state resource
+    pub fn new(current: S) -> Self {
+        Self {
+            current,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Returns the current state
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    /// Queue a transition to `next`.
+    ///
+    /// Transitioning to the current state does nothing.
+    pub fn queue(&mut self, next: S) {
+        self.queued.push_back(next);
+    }
+
+    /// Returns the queued transitions in the order they will be applied
+    pub fn queued(&self) -> impl Iterator<Item = &S> {
+        self.queued.iter()
+    }
+}
+
+/// Runs a schedule when the state is entered
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnEnter<S>(pub S);
+
+/// Runs a schedule when the state is exited
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnExit<S>(pub S);
+
+/// Runs a schedule each time the [`States`] are executed while in the state
+#[derive(Debug, Clone, PartialEq)]
+pub struct InState<S>(pub S);
+
+/// Determines when a schedule of [`States`] runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateHook<S> {
+    /// See: [`OnEnter`]
+    OnEnter(S),
+    /// See: [`OnExit`]
+    OnExit(S),
+    /// See: [`InState`]
+    InState(S),
+}
+
+impl<S> From<OnEnter<S>> for StateHook<S> {
+    fn from(value: OnEnter<S>) -> Self {
+        Self::OnEnter(value.0)
+    }
+}
+
+impl<S> From<OnExit<S>> for StateHook<S> {
+    fn from(value: OnExit<S>) -> Self {
+        Self::OnExit(value.0)
+    }
+}
+
+impl<S> From<InState<S>> for StateHook<S> {
+    fn from(value: InState<S>) -> Self {
+        Self::InState(value.0)
+    }
+}
+
+/// A state machine of schedules which run when entering, exiting, or while in a state.
+///
+/// The current state is stored in the [`state`] resource, which systems can use to queue
+/// transitions.
+///
+/// Each execution:
+/// 1. Enters the initial state the first time, inserting the [`state`] resource.
+/// 2. Applies the queued transitions, running the [`OnExit`] schedules of the previous state
+///    followed by the [`OnEnter`] schedules of the next.
+/// 3. Runs the [`InState`] schedules of the current state.
+///
+/// Transitions queued while entering or exiting a state are applied in the same execution, up to
+/// [`Self::with_max_transitions`] transitions to guard against cycles.
+#[derive(Debug)]
+pub struct States<S> {
+    initial: S,
+    schedules: Vec<(StateHook<S>, Schedule)>,
+    max_transitions: usize,
+}
+
+impl<S: StateValue> States<S> {
+    /// The default maximum number of transitions applied in a single execution
+    pub const MAX_TRANSITIONS: usize = 64;
+
+    /// Creates a new state machine, starting in `initial`
+    pub fn new(initial: S) -> Self {
+        Self {
+            initial,
+            schedules: Vec::new(),
+            max_transitions: Self::MAX_TRANSITIONS,
+        }
+    }
+
+    /// Set the maximum number of transitions applied in a single execution.
+    ///
+    /// Exceeding it fails the execution, as the hooks likely queue transitions in a cycle.
+    ///
+    /// Defaults to [`Self::MAX_TRANSITIONS`].
+    pub fn with_max_transitions(mut self, max_transitions: usize) -> Self {
+        self.max_transitions = max_transitions;
+        self
+    }
+
+    /// Add a schedule to run for the given hook.
+    ///
+    /// Multiple schedules for the same hook run in the order they were added.
+    pub fn with_schedule(mut self, hook: impl Into<StateHook<S>>, schedule: Schedule) -> Self {
+        self.schedules.push((hook.into(), schedule));
+        self
+    }
+
+    /// Apply the queued transitions and run the schedules of the current state sequentially.
+    ///
+    /// See: [`Schedule::execute_seq`]
+    pub fn execute_seq(&mut self, world: &mut World) -> anyhow::Result<ScheduleReport> {
+        self.execute(world, Schedule::execute_seq)
+    }
+
+    /// Apply the queued transitions and run the schedules of the current state in parallel.
+    ///
+    /// See: [`Schedule::execute_par`]
+    pub fn execute_par(&mut self, world: &mut World) -> anyhow::Result<ScheduleReport> {
+        self.execute(world, Schedule::execute_par)
+    }
+
+    fn execute(
+        &mut self,
+        world: &mut World,
+        mut execute: impl FnMut(&mut Schedule, &mut World) -> anyhow::Result<ScheduleReport>,
+    ) -> anyhow::Result<ScheduleReport> {
+        profile_function!();
+        let mut report = ScheduleReport::default();
+
+        if !world.has(resources(), state::<S>()) {
+            world
+                .set(resources(), state(), State::new(self.initial.clone()))
+                .map_err(|err| anyhow::anyhow!(err))?;
+
+            let hook = StateHook::OnEnter(self.initial.clone());
+            self.run(&hook, world, &mut execute, &mut report)?;
+        }
+
+        let mut transitions = 0;
+        loop {
+            let (current, next) = {
+                let mut state = state_mut::<S>(world)?;
+                let next = state.queued.pop_front();
+                (state.current.clone(), next)
+            };
+
+            let Some(next) = next else {
+                break;
+            };
+
+            if next == current {
+                continue;
+            }
+
+            if transitions == self.max_transitions {
+                let err = anyhow::anyhow!(
+                    "Exceeded {} state transitions in a single execution while transitioning from {current:?} to {next:?}",
+                    self.max_transitions
+                );
+
+                // Keep the transition for the next execution
+                state_mut::<S>(world)?.queued.push_front(next);
+                return Err(report.abort(err));
+            }
+
+            transitions += 1;
+
+            self.run(
+                &StateHook::OnExit(current),
+                world,
+                &mut execute,
+                &mut report,
+            )?;
+            state_mut::<S>(world)?.current = next.clone();
+            self.run(&StateHook::OnEnter(next), world, &mut execute, &mut report)?;
+        }
+
+        let current = state_mut::<S>(world)?.current.clone();
+        self.run(
+            &StateHook::InState(current),
+            world,
+            &mut execute,
+            &mut report,
+        )?;
+
+        Ok(report)
+    }
+
+    fn run(
+        &mut self,
+        hook: &StateHook<S>,
+        world: &mut World,
+        execute: &mut impl FnMut(&mut Schedule, &mut World) -> anyhow::Result<ScheduleReport>,
+        report: &mut ScheduleReport,
+    ) -> anyhow::Result<()> {
+        for (_, schedule) in self.schedules.iter_mut().filter(|v| v.0 == *hook) {
+            match execute(schedule, world) {
+                Ok(v) => report.append(v),
+                Err(err) => return Err(mem::take(report).abort(err)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn state_mut<S: StateValue>(world: &World) -> anyhow::Result<RefMut<'_, State<S>>> {
+    world
+        .get_mut(resources(), state::<S>())
+        .map_err(|err| anyhow::anyhow!(err))
+}
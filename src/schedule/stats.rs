@@ -0,0 +1,186 @@
+use core::{fmt, time::Duration};
+
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+/// Wall-clock timing statistics of a system or batch
+#[derive(Debug, Clone, Default)]
+pub struct TimingStats {
+    count: u64,
+    max: Duration,
+    last: Duration,
+    window: VecDeque<Duration>,
+    window_sum: Duration,
+}
+
+impl TimingStats {
+    /// The number of samples used for the rolling mean
+    pub const WINDOW: usize = 64;
+
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.max = self.max.max(elapsed);
+        self.last = elapsed;
+
+        if self.window.len() == Self::WINDOW {
+            if let Some(old) = self.window.pop_front() {
+                self.window_sum -= old;
+            }
+        }
+
+        self.window.push_back(elapsed);
+        self.window_sum += elapsed;
+    }
+
+    /// Returns the number of times the system or batch has executed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the mean duration of the last [`Self::WINDOW`] executions
+    pub fn mean(&self) -> Duration {
+        if self.window.is_empty() {
+            Duration::ZERO
+        } else {
+            self.window_sum / self.window.len() as u32
+        }
+    }
+
+    /// Returns the longest duration of any execution
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the duration of the most recent execution
+    pub fn last(&self) -> Duration {
+        self.last
+    }
+}
+
+/// Per-system and per-batch timing statistics of a schedule.
+///
+/// Systems are identified by name, which means systems sharing a name are recorded together.
+///
+/// See: [`Schedule::with_stats`](crate::Schedule::with_stats)
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleStats {
+    systems: Vec<(String, TimingStats)>,
+    batches: Vec<TimingStats>,
+}
+
+impl ScheduleStats {
+    pub(super) fn record_system(&mut self, name: &str, elapsed: Duration) {
+        let index = match self.systems.iter().position(|v| v.0 == name) {
+            Some(index) => index,
+            None => {
+                self.systems.push((name.into(), TimingStats::default()));
+                self.systems.len() - 1
+            }
+        };
+
+        self.systems[index].1.record(elapsed);
+    }
+
+    pub(super) fn record_batch(&mut self, batch: usize, elapsed: Duration) {
+        if self.batches.len() <= batch {
+            self.batches.resize_with(batch + 1, Default::default);
+        }
+
+        self.batches[batch].record(elapsed);
+    }
+
+    /// Returns the statistics of each system, in the order they first executed
+    pub fn systems(&self) -> impl Iterator<Item = (&str, &TimingStats)> {
+        self.systems
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    /// Returns the statistics of the system with the given name
+    pub fn system(&self, name: &str) -> Option<&TimingStats> {
+        self.systems().find(|v| v.0 == name).map(|v| v.1)
+    }
+
+    /// Returns the statistics of each batch.
+    ///
+    /// Batches are only recorded when executing in parallel, as sequential execution does not
+    /// batch the systems. The batches are recomputed when new archetypes are added, so a batch
+    /// index may refer to different systems over time.
+    pub fn batches(&self) -> &[TimingStats] {
+        &self.batches
+    }
+}
+
+impl fmt::Display for ScheduleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let batch_names = (0..self.batches.len())
+            .map(|i| alloc::format!("batch {i}"))
+            .collect::<Vec<_>>();
+
+        let rows = self
+            .systems()
+            .chain(batch_names.iter().map(|v| v.as_str()).zip(&self.batches));
+
+        let width = self
+            .systems()
+            .map(|v| v.0.len())
+            .chain(batch_names.iter().map(|v| v.len()))
+            .chain([6])
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            f,
+            "{:<width$} {:>10} {:>12} {:>12} {:>12}",
+            "system", "calls", "mean", "max", "last"
+        )?;
+
+        for (name, stats) in rows {
+            writeln!(
+                f,
+                "{:<width$} {:>10} {:>12} {:>12} {:>12}",
+                name,
+                stats.count(),
+                alloc::format!("{:.2?}", stats.mean()),
+                alloc::format!("{:.2?}", stats.max()),
+                alloc::format!("{:.2?}", stats.last()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Measures the wall-clock time of a system or batch when statistics are enabled
+pub(super) struct Timer {
+    #[cfg(feature = "std")]
+    start: Option<std::time::Instant>,
+}
+
+impl Timer {
+    #[inline]
+    pub(super) fn start(enabled: bool) -> Self {
+        #[cfg(feature = "std")]
+        {
+            Self {
+                start: enabled.then(std::time::Instant::now),
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = enabled;
+            Self {}
+        }
+    }
+
+    #[inline]
+    pub(super) fn elapsed(&self) -> Option<Duration> {
+        #[cfg(feature = "std")]
+        {
+            self.start.map(|v| v.elapsed())
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            None
+        }
+    }
+}
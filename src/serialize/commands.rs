@@ -0,0 +1,480 @@
+use alloc::{string::String, vec::Vec};
+use serde::{
+    de::{self, DeserializeSeed, SeqAccess, VariantAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeTupleVariant},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    archetype::BatchSpawn, buffer::ComponentBuffer, commands::Command, component::ComponentKey,
+    CommandBuffer, Entity, EntityBuilder,
+};
+
+use super::{
+    de::{DeserializeEntityData, DeserializeSet, DeserializeStorages},
+    ser::SerializeStorage,
+    DeserializeContext, SerializeContext,
+};
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CommandKind {
+    Spawn,
+    SpawnAt,
+    AppendTo,
+    SpawnBatch,
+    SpawnBatchAt,
+    Set,
+    SetMissing,
+    Remove,
+    Despawn,
+}
+
+const COMMAND_VARIANTS: &[&str] = &[
+    "spawn",
+    "spawn_at",
+    "append_to",
+    "spawn_batch",
+    "spawn_batch_at",
+    "set",
+    "set_missing",
+    "remove",
+    "despawn",
+];
+
+impl SerializeContext {
+    /// Serialize the commands recorded in a commandbuffer.
+    ///
+    /// Only the registered components are recorded, and deferred functions and children attached
+    /// to an [`EntityBuilder`] are skipped.
+    ///
+    /// The commands can be deserialized using [`DeserializeContext::deserialize_commands`] and
+    /// applied to another world to replay them.
+    pub fn serialize_commands<'a>(&'a self, cmd: &'a CommandBuffer) -> CommandsSerializer<'a> {
+        CommandsSerializer { context: self, cmd }
+    }
+
+    fn is_recorded(&self, cmd: &Command) -> bool {
+        match cmd {
+            Command::Set { desc, .. }
+            | Command::SetDedup { desc, .. }
+            | Command::SetMissing { desc, .. }
+            | Command::Remove { desc, .. } => self.slots.contains_key(&desc.key()),
+            Command::Defer(_) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Serializes the commands of a [`CommandBuffer`]
+pub struct CommandsSerializer<'a> {
+    context: &'a SerializeContext,
+    cmd: &'a CommandBuffer,
+}
+
+impl<'a> Serialize for CommandsSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let commands = || {
+            self.cmd
+                .commands()
+                .iter()
+                .filter(|cmd| self.context.is_recorded(cmd))
+        };
+
+        let mut seq = serializer.serialize_seq(Some(commands().count()))?;
+        for cmd in commands() {
+            seq.serialize_element(&SerializeCommand {
+                context: self.context,
+                buffer: self.cmd,
+                cmd,
+            })?;
+        }
+
+        seq.end()
+    }
+}
+
+struct SerializeCommand<'a> {
+    context: &'a SerializeContext,
+    buffer: &'a CommandBuffer,
+    cmd: &'a Command,
+}
+
+impl<'a> Serialize for SerializeCommand<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let context = self.context;
+        let components = |builder: &'a EntityBuilder| SerializeComponents {
+            context,
+            buffer: builder.buffer(),
+        };
+        let batch = |batch: &'a BatchSpawn| SerializeBatch { context, batch };
+
+        match self.cmd {
+            Command::Spawn(builder) => {
+                serializer.serialize_newtype_variant("Command", 0, "spawn", &components(builder))
+            }
+            Command::SpawnAt(builder, id) => {
+                let mut state = serializer.serialize_tuple_variant("Command", 1, "spawn_at", 2)?;
+                state.serialize_field(id)?;
+                state.serialize_field(&components(builder))?;
+                state.end()
+            }
+            Command::AppendTo(builder, id) => {
+                let mut state = serializer.serialize_tuple_variant("Command", 2, "append_to", 2)?;
+                state.serialize_field(id)?;
+                state.serialize_field(&components(builder))?;
+                state.end()
+            }
+            Command::SpawnBatch(chunk) => {
+                let mut state =
+                    serializer.serialize_tuple_variant("Command", 3, "spawn_batch", 2)?;
+                state.serialize_field(&chunk.len())?;
+                state.serialize_field(&batch(chunk))?;
+                state.end()
+            }
+            Command::SpawnBatchAt(chunk, ids) => {
+                let mut state =
+                    serializer.serialize_tuple_variant("Command", 4, "spawn_batch_at", 2)?;
+                state.serialize_field(ids)?;
+                state.serialize_field(&batch(chunk))?;
+                state.end()
+            }
+            // Deduplication is not preserved
+            Command::Set {
+                id, desc, offset, ..
+            }
+            | Command::SetDedup {
+                id, desc, offset, ..
+            } => self.serialize_set(serializer, 5, "set", *id, desc.key(), *offset),
+            Command::SetMissing { id, desc, offset } => {
+                self.serialize_set(serializer, 6, "set_missing", *id, desc.key(), *offset)
+            }
+            Command::Remove { id, desc } => {
+                let slot = &context.slots[&desc.key()];
+                serializer.serialize_newtype_variant("Command", 7, "remove", &(id, &slot.key))
+            }
+            Command::Despawn(id) => {
+                serializer.serialize_newtype_variant("Command", 8, "despawn", id)
+            }
+            Command::Defer(_) => unreachable!("Deferred functions are not recorded"),
+        }
+    }
+}
+
+impl<'a> SerializeCommand<'a> {
+    fn serialize_set<S: Serializer>(
+        &self,
+        serializer: S,
+        variant_index: u32,
+        variant: &'static str,
+        id: Entity,
+        key: ComponentKey,
+        offset: usize,
+    ) -> Result<S::Ok, S::Error> {
+        let slot = &self.context.slots[&key];
+        // Safety: the offset belongs to the command
+        let ptr = unsafe { self.buffer.inserted(offset) };
+
+        let mut state = serializer.serialize_tuple_variant("Command", variant_index, variant, 3)?;
+        state.serialize_field(&id)?;
+        state.serialize_field(&slot.key)?;
+        state.serialize_field((slot.ser_ptr)(&ptr))?;
+        state.end()
+    }
+}
+
+/// { component: value }
+struct SerializeComponents<'a> {
+    context: &'a SerializeContext,
+    buffer: &'a ComponentBuffer,
+}
+
+impl<'a> Serialize for SerializeComponents<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let components = || {
+            self.buffer
+                .iter_dyn()
+                .filter_map(|(desc, ptr)| Some((self.context.slots.get(&desc.key())?, ptr)))
+        };
+
+        let mut state = serializer.serialize_map(Some(components().count()))?;
+        for (slot, ptr) in components() {
+            state.serialize_entry(&slot.key, (slot.ser_ptr)(&ptr))?;
+        }
+
+        state.end()
+    }
+}
+
+/// { component: [value] }
+struct SerializeBatch<'a> {
+    context: &'a SerializeContext,
+    batch: &'a BatchSpawn,
+}
+
+impl<'a> Serialize for SerializeBatch<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let storages = || {
+            self.batch.storages().filter_map(|storage| {
+                Some((self.context.slots.get(&storage.desc().key())?, storage))
+            })
+        };
+
+        let mut state = serializer.serialize_map(Some(storages().count()))?;
+        for (slot, storage) in storages() {
+            state.serialize_entry(&slot.key, &SerializeStorage { storage, slot })?;
+        }
+
+        state.end()
+    }
+}
+
+impl DeserializeContext {
+    /// Deserializes commands serialized by [`SerializeContext::serialize_commands`] into a new
+    /// commandbuffer.
+    pub fn deserialize_commands<'de, D>(&self, deserializer: D) -> Result<CommandBuffer, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(CommandsVisitor { context: self })
+    }
+}
+
+struct CommandsVisitor<'a> {
+    context: &'a DeserializeContext,
+}
+
+impl<'de, 'a> Visitor<'de> for CommandsVisitor<'a> {
+    type Value = CommandBuffer;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "a sequence of commands")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut cmd = CommandBuffer::new();
+        while seq
+            .next_element_seed(DeserializeCommand {
+                context: self.context,
+                cmd: &mut cmd,
+            })?
+            .is_some()
+        {}
+
+        Ok(cmd)
+    }
+}
+
+struct DeserializeCommand<'a> {
+    context: &'a DeserializeContext,
+    cmd: &'a mut CommandBuffer,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for DeserializeCommand<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_enum("Command", COMMAND_VARIANTS, self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for DeserializeCommand<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "a command")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        let context = self.context;
+        let cmd = self.cmd;
+
+        let (kind, variant) = data.variant::<CommandKind>()?;
+        match kind {
+            CommandKind::Spawn => {
+                let mut builder = EntityBuilder::new();
+                variant.newtype_variant_seed(DeserializeEntityData {
+                    context,
+                    builder: &mut builder,
+                })?;
+
+                cmd.spawn(builder);
+            }
+            CommandKind::SpawnAt => {
+                let (id, builder) = variant.tuple_variant(2, BuilderVisitor { context })?;
+                cmd.spawn_at(id, builder);
+            }
+            CommandKind::AppendTo => {
+                let (id, builder) = variant.tuple_variant(2, BuilderVisitor { context })?;
+                cmd.append_to(id, builder);
+            }
+            CommandKind::SpawnBatch => {
+                let (_, batch) = variant.tuple_variant(
+                    2,
+                    BatchVisitor {
+                        context,
+                        len: |&len: &usize| len,
+                    },
+                )?;
+                cmd.spawn_batch(batch);
+            }
+            CommandKind::SpawnBatchAt => {
+                let (ids, batch) = variant.tuple_variant(
+                    2,
+                    BatchVisitor {
+                        context,
+                        len: |ids: &Vec<Entity>| ids.len(),
+                    },
+                )?;
+                cmd.spawn_batch_at(ids, batch);
+            }
+            CommandKind::Set | CommandKind::SetMissing => {
+                variant.tuple_variant(
+                    3,
+                    SetVisitor {
+                        context,
+                        cmd,
+                        missing: matches!(kind, CommandKind::SetMissing),
+                    },
+                )?;
+            }
+            CommandKind::Remove => {
+                let (id, key): (Entity, String) = variant.newtype_variant()?;
+                let slot = context.get(&key).map_err(de::Error::custom)?;
+                cmd.remove_dyn(id, slot.desc);
+            }
+            CommandKind::Despawn => {
+                cmd.despawn(variant.newtype_variant()?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// (id, components)
+struct BuilderVisitor<'a> {
+    context: &'a DeserializeContext,
+}
+
+impl<'de, 'a> Visitor<'de> for BuilderVisitor<'a> {
+    type Value = (Entity, EntityBuilder);
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "an entity id followed by a map of components")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        let mut builder = EntityBuilder::new();
+        seq.next_element_seed(DeserializeEntityData {
+            context: self.context,
+            builder: &mut builder,
+        })?
+        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok((id, builder))
+    }
+}
+
+/// (len or ids, { component: [value] })
+struct BatchVisitor<'a, K> {
+    context: &'a DeserializeContext,
+    len: fn(&K) -> usize,
+}
+
+impl<'de, 'a, K: Deserialize<'de>> Visitor<'de> for BatchVisitor<'a, K> {
+    type Value = (K, BatchSpawn);
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            formatter,
+            "a batch of entities followed by a map of components"
+        )
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let key: K = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        let batch = seq
+            .next_element_seed(DeserializeStorages {
+                len: (self.len)(&key),
+                context: self.context,
+            })?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok((key, batch))
+    }
+}
+
+/// (id, component, value)
+struct SetVisitor<'a> {
+    context: &'a DeserializeContext,
+    cmd: &'a mut CommandBuffer,
+    missing: bool,
+}
+
+impl<'de, 'a> Visitor<'de> for SetVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "an entity id, component and value")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        let key: String = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        let slot = self.context.get(&key).map_err(de::Error::custom)?;
+
+        seq.next_element_seed(DeserializeSet {
+            slot,
+            id,
+            missing: self.missing,
+            cmd: self.cmd,
+        })?
+        .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+
+        Ok(())
+    }
+}
@@ -9,13 +9,14 @@ use serde::{
 use crate::{
     archetype::{BatchSpawn, Storage},
     component::{ComponentDesc, ComponentValue},
-    Component, Entity, EntityBuilder, World,
+    world::MigratedEntities,
+    CommandBuffer, Component, Entity, EntityBuilder, World,
 };
 
 use super::{RowFields, SerializeFormat, WorldFields};
 
 #[derive(Clone)]
-struct Slot {
+pub(super) struct Slot {
     /// Takes a whole column and returns a serializer for it
     deser_col: fn(
         deserializer: &mut dyn erased_serde::Deserializer,
@@ -27,7 +28,64 @@ struct Slot {
         component: ComponentDesc,
         builder: &mut EntityBuilder,
     ) -> erased_serde::Result<()>,
-    desc: ComponentDesc,
+    /// Deserializes a value and records a set command
+    deser_set: fn(
+        deserializer: &mut dyn erased_serde::Deserializer,
+        component: ComponentDesc,
+        id: Entity,
+        missing: bool,
+        cmd: &mut CommandBuffer,
+    ) -> erased_serde::Result<()>,
+    pub(super) desc: ComponentDesc,
+}
+
+impl Slot {
+    pub(super) fn new<T: ComponentValue + for<'x> Deserialize<'x>>(desc: ComponentDesc) -> Self {
+        fn deser_col<T: ComponentValue + for<'x> Deserialize<'x>>(
+            deserializer: &mut dyn erased_serde::Deserializer,
+            len: usize,
+            desc: ComponentDesc,
+        ) -> erased_serde::Result<Storage> {
+            deserializer.deserialize_seq(StorageVisitor::<T> {
+                desc,
+                cap: len,
+                _marker: PhantomData,
+            })
+        }
+
+        fn deser_one<T: ComponentValue + for<'x> Deserialize<'x>>(
+            deserializer: &mut dyn erased_serde::Deserializer,
+            desc: ComponentDesc,
+            builder: &mut EntityBuilder,
+        ) -> erased_serde::Result<()> {
+            let value = T::deserialize(deserializer)?;
+            builder.set(desc.downcast(), value);
+            Ok(())
+        }
+
+        fn deser_set<T: ComponentValue + for<'x> Deserialize<'x>>(
+            deserializer: &mut dyn erased_serde::Deserializer,
+            desc: ComponentDesc,
+            id: Entity,
+            missing: bool,
+            cmd: &mut CommandBuffer,
+        ) -> erased_serde::Result<()> {
+            let value = T::deserialize(deserializer)?;
+            if missing {
+                cmd.set_missing(id, desc.downcast(), value);
+            } else {
+                cmd.set(id, desc.downcast(), value);
+            }
+            Ok(())
+        }
+
+        Slot {
+            deser_col: deser_col::<T>,
+            deser_one: deser_one::<T>,
+            deser_set: deser_set::<T>,
+            desc,
+        }
+    }
 }
 
 /// [ T, T, T ]
@@ -78,38 +136,17 @@ impl DeserializeBuilder {
     where
         T: ComponentValue + for<'x> Deserialize<'x>,
     {
-        fn deser_col<T: ComponentValue + for<'x> Deserialize<'x>>(
-            deserializer: &mut dyn erased_serde::Deserializer,
-            len: usize,
-            desc: ComponentDesc,
-        ) -> erased_serde::Result<Storage> {
-            deserializer.deserialize_seq(StorageVisitor::<T> {
-                desc,
-                cap: len,
-                _marker: PhantomData,
-            })
-        }
-
-        fn deser_one<T: ComponentValue + for<'x> Deserialize<'x>>(
-            deserializer: &mut dyn erased_serde::Deserializer,
-            desc: ComponentDesc,
-            builder: &mut EntityBuilder,
-        ) -> erased_serde::Result<()> {
-            let value = T::deserialize(deserializer)?;
-            builder.set(desc.downcast(), value);
-            Ok(())
-        }
+        self.slots
+            .insert(key.into(), Slot::new::<T>(component.desc()));
+        self
+    }
 
-        let key = key.into();
+    pub(super) fn slot(&self, key: &str) -> Option<&Slot> {
+        self.slots.get(key)
+    }
 
-        self.slots.insert(
-            key,
-            Slot {
-                deser_col: deser_col::<T>,
-                deser_one: deser_one::<T>,
-                desc: component.desc(),
-            },
-        );
+    pub(super) fn with_slot(&mut self, key: String, slot: Slot) -> &mut Self {
+        self.slots.insert(key, slot);
         self
     }
 
@@ -137,7 +174,23 @@ impl DeserializeContext {
         deserializer.deserialize_enum("World", &["row", "col"], WorldVisitor { context: self })
     }
 
-    fn get(&self, key: &str) -> Result<&Slot, String> {
+    /// Deserializes entities from the supplied deserializer and merges them into an existing
+    /// world.
+    ///
+    /// Entities which collide with existing ids are migrated, see [`World::merge_with`].
+    pub fn deserialize_into<'de, D>(
+        &self,
+        world: &mut World,
+        deserializer: D,
+    ) -> core::result::Result<MigratedEntities, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut other = self.deserialize(deserializer)?;
+        Ok(world.merge_with(&mut other))
+    }
+
+    pub(super) fn get(&self, key: &str) -> Result<&Slot, String> {
         self.slots
             .get(key)
             .ok_or_else(|| format!("Unknown component key: {key:?}"))
@@ -262,9 +315,9 @@ impl<'de, 'a> Visitor<'de> for DeserializeEntity<'a> {
     }
 }
 
-struct DeserializeEntityData<'a> {
-    context: &'a DeserializeContext,
-    builder: &'a mut EntityBuilder,
+pub(super) struct DeserializeEntityData<'a> {
+    pub(super) context: &'a DeserializeContext,
+    pub(super) builder: &'a mut EntityBuilder,
 }
 
 impl<'de, 'a> DeserializeSeed<'de> for DeserializeEntityData<'a> {
@@ -323,6 +376,35 @@ impl<'de, 'a> DeserializeSeed<'de> for DeserializeComponent<'a> {
     }
 }
 
+/// A single component value which is recorded as a set command
+pub(super) struct DeserializeSet<'a> {
+    pub(super) slot: &'a Slot,
+    pub(super) id: Entity,
+    pub(super) missing: bool,
+    pub(super) cmd: &'a mut CommandBuffer,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for DeserializeSet<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.slot.deser_set)(
+            &mut deserializer,
+            self.slot.desc,
+            self.id,
+            self.missing,
+            self.cmd,
+        )
+        .map_err(de::Error::custom)?;
+
+        Ok(())
+    }
+}
+
 /// Deserializes a list of archetypes
 struct WorldRowVisitor<'a> {
     context: &'a DeserializeContext,
@@ -524,9 +606,9 @@ impl<'a, 'de> Visitor<'de> for ArchetypeVisitor<'a> {
     }
 }
 
-struct DeserializeStorages<'a> {
-    len: usize,
-    context: &'a DeserializeContext,
+pub(super) struct DeserializeStorages<'a> {
+    pub(super) len: usize,
+    pub(super) context: &'a DeserializeContext,
 }
 
 impl<'de, 'a> DeserializeSeed<'de> for DeserializeStorages<'a> {
@@ -1,18 +1,53 @@
+mod commands;
 mod de;
 mod ser;
 
-use alloc::string::String;
+use alloc::{collections::BTreeMap, string::String};
+pub use commands::*;
 pub use de::*;
 pub use ser::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    component::{ComponentKey, ComponentValue},
+    buffer::ComponentBuffer,
+    component::{ComponentDesc, ComponentKey, ComponentValue},
     filter::And,
     filter::{All, StaticFilter},
+    metadata::Metadata,
     Component,
 };
 
+component! {
+    /// Allows the component to be serialized without knowing its type.
+    ///
+    /// See: [`Serializable`]
+    pub serializable: Serializable,
+}
+
+/// Type erased serialization and deserialization of a component.
+///
+/// Used by [`SerdeBuilder::with_registry`] to serialize all registered components.
+#[derive(Clone)]
+pub struct Serializable {
+    ser: fn(String) -> ser::Slot,
+    de: fn(ComponentDesc) -> de::Slot,
+}
+
+impl<T> Metadata<T> for Serializable
+where
+    T: ComponentValue + Serialize + for<'de> Deserialize<'de>,
+{
+    fn attach(_: ComponentDesc, buffer: &mut ComponentBuffer) {
+        buffer.set(
+            serializable(),
+            Serializable {
+                ser: ser::Slot::new::<T>,
+                de: de::Slot::new::<T>,
+            },
+        );
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct ComponentSerKey {
     key: String,
@@ -95,6 +130,44 @@ where
         self
     }
 
+    /// Register all components in the registry which have the [`Serializable`] metadata, using
+    /// the component names.
+    ///
+    /// Fails without registering anything if two distinct components share a name, as they could
+    /// not be told apart when deserializing.
+    #[cfg(feature = "registry")]
+    pub fn with_registry(
+        &mut self,
+        registry: &crate::registry::ComponentRegistry,
+    ) -> crate::error::Result<&mut Self> {
+        let mut components: BTreeMap<&str, (ComponentDesc, Serializable)> = BTreeMap::new();
+        for desc in registry.iter() {
+            let Some(serializable) = desc.meta_ref().get(serializable()) else {
+                continue;
+            };
+
+            let existing = self
+                .de
+                .slot(desc.name())
+                .map(|v| v.desc)
+                .or_else(|| components.get(desc.name()).map(|v| v.0));
+
+            if let Some(existing) = existing.filter(|v| v.key() != desc.key()) {
+                return Err(crate::Error::NameConflict(existing, desc));
+            }
+
+            components.insert(desc.name(), (desc, serializable.clone()));
+        }
+
+        for (name, (desc, serializable)) in components {
+            self.ser
+                .with_slot(desc.key(), (serializable.ser)(name.into()));
+            self.de.with_slot(name.into(), (serializable.de)(desc));
+        }
+
+        Ok(self)
+    }
+
     /// Add a new filter to specify which entities will be serialized.
     pub fn with_filter<G>(self, filter: G) -> SerdeBuilder<And<F, G>> {
         SerdeBuilder {
@@ -246,4 +319,91 @@ mod test {
 
         test_eq(&world, &new_world);
     }
+
+    #[test]
+    fn serialize_commands() {
+        use crate::{entity_ids, CommandBuffer, FetchExt, Query};
+        use itertools::Itertools;
+
+        component! {
+            health: f32,
+            pos: (f32, f32),
+            local: u32,
+        }
+
+        let (serializer, deserializer) = SerdeBuilder::new()
+            .with(name())
+            .with(health())
+            .with(pos())
+            .build();
+
+        let create_world = || {
+            let mut world = World::new();
+            let ids = (0..4)
+                .map(|i| {
+                    Entity::builder()
+                        .set(name(), format!("Entity.{i}"))
+                        .set(health(), 100.0)
+                        .spawn(&mut world)
+                })
+                .collect_vec();
+            (world, ids)
+        };
+
+        let (mut world, ids) = create_world();
+        let mut cmd = CommandBuffer::new();
+
+        let mut batch = BatchSpawn::new(2);
+        batch.set(pos(), [(1.0, 2.0), (3.0, 4.0)]).unwrap();
+        batch.set(local(), [1, 2]).unwrap();
+
+        cmd.set(ids[0], health(), 50.0)
+            .set(ids[0], local(), 5)
+            .set_missing(ids[1], pos(), (5.0, 5.0))
+            .remove(ids[2], health())
+            .despawn(ids[3])
+            .spawn(
+                Entity::builder()
+                    .set(name(), "Spawned".into())
+                    .set(local(), 7),
+            )
+            .append_to(ids[1], Entity::builder().set(health(), 25.0))
+            .spawn_batch(batch)
+            .defer(|_| Ok(()));
+
+        let json = serde_json::to_string(&serializer.serialize_commands(&cmd)).unwrap();
+        let ron = ron::to_string(&serializer.serialize_commands(&cmd)).unwrap();
+
+        cmd.apply(&mut world).unwrap();
+
+        for mut replayed in [
+            deserializer
+                .deserialize_commands(&mut serde_json::Deserializer::from_str(&json))
+                .unwrap(),
+            deserializer
+                .deserialize_commands(&mut ron::Deserializer::from_str(&ron).unwrap())
+                .unwrap(),
+        ] {
+            let (mut replay_world, _) = create_world();
+            replayed.apply(&mut replay_world).unwrap();
+
+            let mut query = Query::new((
+                entity_ids(),
+                name().cloned().opt(),
+                health().copied().opt(),
+                pos().copied().opt(),
+            ));
+
+            // Unregistered components cause a different archetype order
+            let replayed = query.collect_vec(&replay_world);
+            let expected = query.collect_vec(&world);
+            assert_eq!(
+                replayed.into_iter().sorted_by_key(|v| v.0).collect_vec(),
+                expected.into_iter().sorted_by_key(|v| v.0).collect_vec(),
+            );
+
+            // Unregistered components are not recorded
+            assert_eq!(Query::new(local()).borrow(&replay_world).count(), 0);
+        }
+    }
 }
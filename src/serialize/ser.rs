@@ -15,10 +15,30 @@ use serde::{
 use super::SerializeFormat;
 
 #[derive(Clone)]
-struct Slot {
+pub(super) struct Slot {
     /// Takes a whole column and returns a serializer for it
-    ser: for<'x> fn(storage: &'x Storage, slot: usize) -> &'x dyn erased_serde::Serialize,
-    key: String,
+    pub(super) ser:
+        for<'x> fn(storage: &'x Storage, slot: usize) -> &'x dyn erased_serde::Serialize,
+    /// Returns a serializer for a single type erased value
+    pub(super) ser_ptr: fn(&*const u8) -> &dyn erased_serde::Serialize,
+    pub(super) key: String,
+}
+
+impl Slot {
+    pub(super) fn new<T: ComponentValue + Serialize>(key: String) -> Self {
+        fn ser_col<T: serde::Serialize + ComponentValue + Sized>(
+            storage: &Storage,
+            slot: usize,
+        ) -> &dyn erased_serde::Serialize {
+            &storage.downcast_ref::<T>()[slot]
+        }
+
+        Slot {
+            key,
+            ser: ser_col::<T>,
+            ser_ptr: |ptr| unsafe { &*(ptr.cast::<T>()) },
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -65,21 +85,14 @@ where
     where
         T: ComponentValue + serde::Serialize,
     {
-        fn ser_col<T: serde::Serialize + ComponentValue + Sized>(
-            storage: &Storage,
-            slot: usize,
-        ) -> &dyn erased_serde::Serialize {
-            &storage.downcast_ref::<T>()[slot]
-        }
+        self.slots
+            .insert(component.key(), Slot::new::<T>(key.into()));
 
-        self.slots.insert(
-            component.key(),
-            Slot {
-                key: key.into(),
-                ser: ser_col::<T>,
-            },
-        );
+        self
+    }
 
+    pub(super) fn with_slot(&mut self, key: ComponentKey, slot: Slot) -> &mut Self {
+        self.slots.insert(key, slot);
         self
     }
 
@@ -103,7 +116,7 @@ where
 /// Describes how to serialize a world given a group of components to serialize
 /// and an optional filter. Empty entities will be skipped.
 pub struct SerializeContext {
-    slots: BTreeMap<ComponentKey, Slot>,
+    pub(super) slots: BTreeMap<ComponentKey, Slot>,
     filter: Box<dyn StaticFilter>,
 }
 
@@ -303,9 +316,9 @@ struct SerializeStorages<'a> {
     context: &'a SerializeContext,
 }
 
-struct SerializeStorage<'a> {
-    storage: &'a Storage,
-    slot: &'a Slot,
+pub(super) struct SerializeStorage<'a> {
+    pub(super) storage: &'a Storage,
+    pub(super) slot: &'a Slot,
 }
 
 impl<'a> serde::Serialize for SerializeStorage<'a> {
@@ -4,6 +4,7 @@ use alloc::{sync::Arc, vec::Vec};
 use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
 
 use crate::{
+    commands::ParCmd,
     system::{Access, AccessKind},
     CommandBuffer, World,
 };
@@ -117,6 +118,13 @@ impl<'a, 'b, 'input> SystemContext<'a, 'b, 'input> {
         AtomicRefMut::map(borrow, |v| *v)
     }
 
+    /// Access the commandbuffer's thread safe queue
+    #[inline]
+    pub fn par_cmd(&self) -> AtomicRef<'_, ParCmd> {
+        let borrow = self.cmd.borrow();
+        AtomicRef::map(borrow, |v| v.par())
+    }
+
     /// Access user provided input data
     #[inline]
     pub fn input<T: 'static>(&self) -> Option<AtomicRef<T>> {
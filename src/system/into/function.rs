@@ -5,7 +5,10 @@ use crate::{
     BoxedSystem, CommandBuffer, World,
 };
 
-use super::{param::SystemParam, InitState, InitStateContext, IntoSystem};
+use super::{
+    param::{FmtSystemParam, SystemParam},
+    InitState, InitStateContext, IntoSystem,
+};
 
 pub struct FunctionSystem<F, Ret, Marker>
 where
@@ -249,15 +252,3 @@ tuple_impl! { 0 => A }
 tuple_impl! { 0 => A, 1 => B }
 tuple_impl! { 0 => A, 1 => B, 2 => C }
 tuple_impl! { 0 => A, 1 => B, 2 => C, 3 => D }
-
-struct FmtSystemParam<'a, S>(&'a S::State)
-where
-    S: SystemParam;
-impl<'a, S> core::fmt::Debug for FmtSystemParam<'a, S>
-where
-    S: SystemParam,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        S::describe(self.0, f)
-    }
-}
@@ -12,7 +12,7 @@ mod local;
 mod param;
 
 pub use local::Local;
-pub use param::SystemParam;
+pub use param::{FmtSystemParam, SystemParam};
 
 /// Transform into a system.
 pub trait IntoSystem<Ret, Marker>: Sized {
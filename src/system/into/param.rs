@@ -3,8 +3,9 @@ use core::fmt;
 use atomic_refcell::{AtomicRef, AtomicRefMut};
 
 use crate::{
+    commands::ParCmd,
     system::{
-        traits::{WithCmd, WithCmdMut, WithWorld, WithWorldMut},
+        traits::{WithCmd, WithCmdMut, WithParCmd, WithWorld, WithWorldMut},
         AsBorrowed, SystemAccess, SystemContext,
     },
     CommandBuffer, World,
@@ -32,6 +33,21 @@ pub trait SystemParam {
     fn describe(state: &Self::State, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 }
 
+/// Formats the state of a [`SystemParam`] using [`SystemParam::describe`]
+#[doc(hidden)]
+pub struct FmtSystemParam<'a, S>(pub &'a S::State)
+where
+    S: SystemParam;
+
+impl<'a, S> fmt::Debug for FmtSystemParam<'a, S>
+where
+    S: SystemParam,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        S::describe(self.0, f)
+    }
+}
+
 impl SystemParam for &World {
     type Value<'a> = AtomicRef<'a, World>;
     type State = WithWorld;
@@ -99,3 +115,20 @@ impl SystemParam for &mut CommandBuffer {
         f.write_str("&mut CommandBuffer")
     }
 }
+
+impl SystemParam for &ParCmd {
+    type Value<'a> = AtomicRef<'a, ParCmd>;
+    type State = WithParCmd;
+
+    fn init_state(_: &InitStateContext<'_, '_>) -> Self::State {
+        WithParCmd
+    }
+
+    fn acquire<'a>(_: &'a mut Self::State, ctx: &'a SystemContext<'_, '_, '_>) -> Self::Value<'a> {
+        ctx.par_cmd()
+    }
+
+    fn describe(_: &Self::State, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("&ParCmd")
+    }
+}
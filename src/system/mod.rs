@@ -1,6 +1,8 @@
 mod context;
 mod input;
 mod into;
+#[cfg(feature = "std")]
+mod non_send;
 mod traits;
 
 use crate::{
@@ -25,10 +27,19 @@ use core::{
 
 pub use context::*;
 pub use input::IntoInput;
-pub use into::{InitStateContext, IntoSystem, IntoSystemExt, Local, SystemParam};
+pub use into::{FmtSystemParam, InitStateContext, IntoSystem, IntoSystemExt, Local, SystemParam};
 pub use traits::{AsBorrowed, SystemAccess, SystemData, SystemFn};
 
-use self::traits::{WithCmd, WithCmdMut, WithInput, WithInputMut, WithWorld, WithWorldMut};
+#[cfg(feature = "std")]
+pub(crate) use non_send::NonSendStore;
+#[cfg(feature = "std")]
+pub use non_send::{MainThread, NonSend, NonSendMut};
+#[cfg(feature = "std")]
+use non_send::{WithMainThread, WithNonSend, WithNonSendMut};
+
+use self::traits::{
+    WithCmd, WithCmdMut, WithInput, WithInputMut, WithParCmd, WithWorld, WithWorldMut,
+};
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::{ParallelBridge, ParallelIterator};
@@ -233,6 +244,18 @@ impl<Args> SystemBuilder<Args> {
         self.with(WithCmdMut)
     }
 
+    /// Access the command buffer's thread safe queue, allowing commands to be recorded from
+    /// within parallel iteration.
+    ///
+    /// **Note**: Add `.flush()` after the system in the schedule to have the changes visible in
+    /// the next system
+    pub fn with_par_cmd(self) -> SystemBuilder<Args::PushRight>
+    where
+        Args: TuplePush<WithParCmd>,
+    {
+        self.with(WithParCmd)
+    }
+
     /// Access schedule input
     pub fn with_input<T>(self) -> SystemBuilder<Args::PushRight>
     where
@@ -251,6 +274,45 @@ impl<Args> SystemBuilder<Args> {
         self.with(WithInputMut::<T>(PhantomData))
     }
 
+    /// Access a non-send resource.
+    ///
+    /// The system will always run on the thread executing the schedule.
+    ///
+    /// See: [`World::insert_non_send`]
+    #[cfg(feature = "std")]
+    pub fn with_non_send<T>(self) -> SystemBuilder<Args::PushRight>
+    where
+        T: 'static,
+        Args: TuplePush<WithNonSend<T>>,
+    {
+        self.with(WithNonSend::<T>(PhantomData))
+    }
+
+    /// Access a non-send resource mutably.
+    ///
+    /// The system will always run on the thread executing the schedule.
+    ///
+    /// See: [`World::insert_non_send`]
+    #[cfg(feature = "std")]
+    pub fn with_non_send_mut<T>(self) -> SystemBuilder<Args::PushRight>
+    where
+        T: 'static,
+        Args: TuplePush<WithNonSendMut<T>>,
+    {
+        self.with(WithNonSendMut::<T>(PhantomData))
+    }
+
+    /// Always run the system on the thread executing the schedule.
+    ///
+    /// The system receives a [`MainThread`] marker.
+    #[cfg(feature = "std")]
+    pub fn with_main_thread(self) -> SystemBuilder<Args::PushRight>
+    where
+        Args: TuplePush<WithMainThread>,
+    {
+        self.with(WithMainThread)
+    }
+
     /// Set the systems name
     pub fn with_name(mut self, name: impl Into<String>) -> Self {
         self.name = Some(name.into());
@@ -502,6 +564,10 @@ pub enum AccessKind {
     CommandBuffer,
     /// Data supplied by user in the execution context
     Input(TypeId),
+    /// Data which can only be accessed from the thread executing the schedule.
+    ///
+    /// Systems with this access are never sent to another thread.
+    NonSend(TypeId),
 }
 
 impl AccessKind {
@@ -555,6 +621,7 @@ pub struct AccessInfo {
     cmd: Option<bool>,
     external: Vec<TypeId>,
     input: Vec<(TypeId, bool)>,
+    non_send: Vec<(TypeId, bool)>,
 }
 
 #[derive(Hash, Debug, Clone, PartialEq, Eq)]
@@ -592,6 +659,9 @@ pub(crate) fn access_info(accesses: &[Access], world: &World) -> AccessInfo {
             AccessKind::Input(ty) => {
                 result.input.push((ty, access.mutable));
             }
+            AccessKind::NonSend(ty) => {
+                result.non_send.push((ty, access.mutable));
+            }
             AccessKind::World => match result.world {
                 Some(true) => result.world = Some(true),
                 _ => result.world = Some(access.mutable),
@@ -0,0 +1,329 @@
+use core::{
+    any::{Any, TypeId},
+    fmt::{self, Formatter},
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+};
+
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use atomic_refcell::{AtomicRef, AtomicRefCell, AtomicRefMut};
+use std::thread::{self, ThreadId};
+
+use crate::World;
+
+use super::{
+    Access, AccessKind, AsBorrowed, InitStateContext, SystemAccess, SystemContext, SystemData,
+    SystemParam,
+};
+
+/// Stores values which are not `Send` or `Sync`.
+///
+/// The values are owned by the thread which inserted them, and accessing them from any other
+/// thread panics.
+#[derive(Default)]
+pub(crate) struct NonSendStore {
+    owner: Option<ThreadId>,
+    values: BTreeMap<TypeId, AtomicRefCell<Box<dyn Any>>>,
+}
+
+// SAFETY: the values are only accessed and dropped on the owning thread
+unsafe impl Send for NonSendStore {}
+unsafe impl Sync for NonSendStore {}
+
+impl NonSendStore {
+    fn assert_owner(&self) {
+        if let Some(owner) = self.owner {
+            assert_eq!(
+                owner,
+                thread::current().id(),
+                "Non-send resources can only be accessed from the thread which inserted them"
+            );
+        }
+    }
+
+    pub(crate) fn insert<T: 'static>(&mut self, value: T) -> Option<T> {
+        if self.values.is_empty() {
+            self.owner = Some(thread::current().id());
+        } else {
+            self.assert_owner();
+        }
+
+        self.values
+            .insert(TypeId::of::<T>(), AtomicRefCell::new(Box::new(value)))
+            .map(|v| *v.into_inner().downcast().unwrap())
+    }
+
+    pub(crate) fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.assert_owner();
+        self.values
+            .remove(&TypeId::of::<T>())
+            .map(|v| *v.into_inner().downcast().unwrap())
+    }
+
+    pub(crate) fn get<T: 'static>(&self) -> Option<AtomicRef<'_, T>> {
+        self.assert_owner();
+        let value = self.values.get(&TypeId::of::<T>())?;
+        Some(AtomicRef::map(value.borrow(), |v| {
+            v.downcast_ref().unwrap()
+        }))
+    }
+
+    pub(crate) fn get_mut<T: 'static>(&self) -> Option<AtomicRefMut<'_, T>> {
+        self.assert_owner();
+        let value = self.values.get(&TypeId::of::<T>())?;
+        Some(AtomicRefMut::map(value.borrow_mut(), |v| {
+            v.downcast_mut().unwrap()
+        }))
+    }
+}
+
+impl Drop for NonSendStore {
+    fn drop(&mut self) {
+        // Dropping the values on another thread is unsound, so they are leaked instead
+        if self.owner.is_some_and(|v| v != thread::current().id()) {
+            mem::forget(mem::take(&mut self.values));
+        }
+    }
+}
+
+/// A [`SystemParam`] that represents a reference to a non-send resource.
+///
+/// The system will always run on the thread executing the schedule.
+///
+/// See: [`World::insert_non_send`]
+pub struct NonSend<'w, T: 'static> {
+    borrow: AtomicRef<'w, T>,
+}
+
+impl<'w, T: 'static> Deref for NonSend<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+/// A [`SystemParam`] that represents a mutable reference to a non-send resource.
+///
+/// The system will always run on the thread executing the schedule.
+///
+/// See: [`World::insert_non_send`]
+pub struct NonSendMut<'w, T: 'static> {
+    borrow: AtomicRefMut<'w, T>,
+}
+
+impl<'w, T: 'static> Deref for NonSendMut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.borrow
+    }
+}
+
+impl<'w, T: 'static> DerefMut for NonSendMut<'w, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.borrow
+    }
+}
+
+#[doc(hidden)]
+pub struct NonSendData<'w, T> {
+    world: AtomicRef<'w, World>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'w, 'a, T: 'static> AsBorrowed<'a> for NonSendData<'w, T> {
+    type Borrowed = NonSend<'a, T>;
+
+    fn as_borrowed(&'a mut self) -> Self::Borrowed {
+        match self.world.non_send() {
+            Some(borrow) => NonSend { borrow },
+            None => panic!("Non-send resource {} not found", tynm::type_name::<T>()),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct NonSendMutData<'w, T> {
+    world: AtomicRef<'w, World>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'w, 'a, T: 'static> AsBorrowed<'a> for NonSendMutData<'w, T> {
+    type Borrowed = NonSendMut<'a, T>;
+
+    fn as_borrowed(&'a mut self) -> Self::Borrowed {
+        match self.world.non_send_mut() {
+            Some(borrow) => NonSendMut { borrow },
+            None => panic!("Non-send resource {} not found", tynm::type_name::<T>()),
+        }
+    }
+}
+
+/// Marks a system which must run on the thread executing the schedule.
+///
+/// Use as a [`SystemParam`] or through [`SystemBuilder::with_main_thread`](crate::SystemBuilder::with_main_thread).
+#[derive(Debug, Clone, Copy)]
+pub struct MainThread(PhantomData<*const ()>);
+
+impl<'a> AsBorrowed<'a> for MainThread {
+    type Borrowed = MainThread;
+
+    fn as_borrowed(&'a mut self) -> Self::Borrowed {
+        *self
+    }
+}
+
+/// Access a non-send resource
+pub struct WithNonSend<T>(pub(crate) PhantomData<fn() -> T>);
+
+impl<'a, T: 'static> SystemData<'a> for WithNonSend<T> {
+    type Value = NonSendData<'a, T>;
+
+    fn acquire(&'a mut self, ctx: &'a SystemContext<'_, '_, '_>) -> Self::Value {
+        NonSendData {
+            world: ctx.world(),
+            marker: PhantomData,
+        }
+    }
+
+    fn describe(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("NonSend<")?;
+        f.write_str(&tynm::type_name::<T>())?;
+        f.write_str(">")
+    }
+}
+
+impl<T: 'static> SystemAccess for WithNonSend<T> {
+    fn access(&self, _: &World, dst: &mut Vec<Access>) {
+        dst.push(Access {
+            kind: AccessKind::NonSend(TypeId::of::<T>()),
+            mutable: false,
+        });
+
+        dst.push(Access {
+            kind: AccessKind::World,
+            mutable: false,
+        });
+    }
+}
+
+/// Access a non-send resource mutably
+pub struct WithNonSendMut<T>(pub(crate) PhantomData<fn() -> T>);
+
+impl<'a, T: 'static> SystemData<'a> for WithNonSendMut<T> {
+    type Value = NonSendMutData<'a, T>;
+
+    fn acquire(&'a mut self, ctx: &'a SystemContext<'_, '_, '_>) -> Self::Value {
+        NonSendMutData {
+            world: ctx.world(),
+            marker: PhantomData,
+        }
+    }
+
+    fn describe(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("NonSendMut<")?;
+        f.write_str(&tynm::type_name::<T>())?;
+        f.write_str(">")
+    }
+}
+
+impl<T: 'static> SystemAccess for WithNonSendMut<T> {
+    fn access(&self, _: &World, dst: &mut Vec<Access>) {
+        dst.push(Access {
+            kind: AccessKind::NonSend(TypeId::of::<T>()),
+            mutable: true,
+        });
+
+        dst.push(Access {
+            kind: AccessKind::World,
+            mutable: false,
+        });
+    }
+}
+
+/// Run the system on the thread executing the schedule
+#[derive(Default)]
+pub struct WithMainThread;
+
+impl<'a> SystemData<'a> for WithMainThread {
+    type Value = MainThread;
+
+    fn acquire(&'a mut self, _: &'a SystemContext<'_, '_, '_>) -> Self::Value {
+        MainThread(PhantomData)
+    }
+
+    fn describe(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("MainThread")
+    }
+}
+
+impl SystemAccess for WithMainThread {
+    fn access(&self, _: &World, dst: &mut Vec<Access>) {
+        dst.push(Access {
+            kind: AccessKind::NonSend(TypeId::of::<MainThread>()),
+            mutable: false,
+        });
+    }
+}
+
+impl<'w, T: 'static> SystemParam for NonSend<'w, T> {
+    type Value<'a> = NonSendData<'a, T>;
+    type State = WithNonSend<T>;
+
+    fn init_state(_: &InitStateContext<'_, '_>) -> Self::State {
+        WithNonSend(PhantomData)
+    }
+
+    fn acquire<'a>(
+        state: &'a mut Self::State,
+        ctx: &'a SystemContext<'_, '_, '_>,
+    ) -> Self::Value<'a> {
+        state.acquire(ctx)
+    }
+
+    fn describe(state: &Self::State, f: &mut Formatter<'_>) -> fmt::Result {
+        SystemData::describe(state, f)
+    }
+}
+
+impl<'w, T: 'static> SystemParam for NonSendMut<'w, T> {
+    type Value<'a> = NonSendMutData<'a, T>;
+    type State = WithNonSendMut<T>;
+
+    fn init_state(_: &InitStateContext<'_, '_>) -> Self::State {
+        WithNonSendMut(PhantomData)
+    }
+
+    fn acquire<'a>(
+        state: &'a mut Self::State,
+        ctx: &'a SystemContext<'_, '_, '_>,
+    ) -> Self::Value<'a> {
+        state.acquire(ctx)
+    }
+
+    fn describe(state: &Self::State, f: &mut Formatter<'_>) -> fmt::Result {
+        SystemData::describe(state, f)
+    }
+}
+
+impl SystemParam for MainThread {
+    type Value<'a> = MainThread;
+    type State = WithMainThread;
+
+    fn init_state(_: &InitStateContext<'_, '_>) -> Self::State {
+        WithMainThread
+    }
+
+    fn acquire<'a>(
+        state: &'a mut Self::State,
+        ctx: &'a SystemContext<'_, '_, '_>,
+    ) -> Self::Value<'a> {
+        state.acquire(ctx)
+    }
+
+    fn describe(state: &Self::State, f: &mut Formatter<'_>) -> fmt::Result {
+        SystemData::describe(state, f)
+    }
+}
@@ -6,8 +6,8 @@ use core::{
     marker::PhantomData,
 };
 
-use crate::system::AccessKind;
 use crate::*;
+use crate::{commands::ParCmd, system::AccessKind};
 
 use super::{Access, SystemContext};
 
@@ -232,6 +232,31 @@ impl SystemAccess for WithCmdMut {
     }
 }
 
+/// Access the command buffer's thread safe queue
+#[derive(Default)]
+pub struct WithParCmd;
+
+impl<'a> SystemData<'a> for WithParCmd {
+    type Value = AtomicRef<'a, ParCmd>;
+
+    fn acquire(&mut self, ctx: &'a SystemContext<'_, '_, '_>) -> Self::Value {
+        ctx.par_cmd()
+    }
+
+    fn describe(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("&ParCmd")
+    }
+}
+
+impl SystemAccess for WithParCmd {
+    fn access(&self, _: &World, dst: &mut Vec<Access>) {
+        dst.push(Access {
+            kind: AccessKind::CommandBuffer,
+            mutable: false,
+        });
+    }
+}
+
 /// Access schedule input
 pub struct WithInput<T>(pub(crate) PhantomData<T>);
 
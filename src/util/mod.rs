@@ -3,6 +3,8 @@
 
 use core::marker::PhantomData;
 
+use atomic_refcell::{AtomicRefCell, AtomicRefMut};
+
 use crate::filter::All;
 
 /// Allows pushing onto a tuple
@@ -188,3 +190,17 @@ impl core::fmt::Debug for Verbatim {
         f.write_str(&self.0)
     }
 }
+
+/// Acquire exclusive access by spinning until the cell is released.
+///
+/// Only for cells which are held briefly, such as while moving commands or updating an index, so
+/// contention is rare and short lived.
+pub(crate) fn spin_lock<T>(cell: &AtomicRefCell<T>) -> AtomicRefMut<'_, T> {
+    loop {
+        if let Ok(v) = cell.try_borrow_mut() {
+            break v;
+        }
+
+        core::hint::spin_loop();
+    }
+}
@@ -5,11 +5,14 @@ use once_cell::sync::OnceCell;
 use crate::{
     buffer::ComponentBuffer,
     component::{ComponentDesc, ComponentValue},
+    metadata::{unique, UniqueConstraint},
 };
 
 #[doc(hidden)]
 pub struct LazyComponentBuffer {
     value: OnceCell<ComponentBuffer>,
+    /// Cached from the metadata as it is checked on every insertion
+    unique: OnceCell<Option<UniqueConstraint>>,
     init: fn(ComponentDesc) -> ComponentBuffer,
 }
 
@@ -18,6 +21,7 @@ impl LazyComponentBuffer {
     pub const fn new(init: fn(ComponentDesc) -> ComponentBuffer) -> Self {
         Self {
             value: OnceCell::new(),
+            unique: OnceCell::new(),
             init,
         }
     }
@@ -29,6 +33,12 @@ impl LazyComponentBuffer {
     pub(crate) fn get(&self, desc: ComponentDesc) -> ComponentBuffer {
         (self.init)(desc)
     }
+
+    pub(crate) fn unique(&self, desc: ComponentDesc) -> Option<&UniqueConstraint> {
+        self.unique
+            .get_or_init(|| self.get_ref(desc).get(unique()).copied())
+            .as_ref()
+    }
 }
 
 /// Describes a components dynamic functionality, such as name, metadata, and type layout.
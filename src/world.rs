@@ -1,4 +1,8 @@
-use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec::Vec,
+};
 use core::{
     fmt,
     fmt::Formatter,
@@ -8,29 +12,36 @@ use core::{
 use once_cell::unsync::OnceCell;
 use smallvec::SmallVec;
 
-use atomic_refcell::{AtomicRef, BorrowError, BorrowMutError};
+use atomic_refcell::{AtomicRef, AtomicRefMut, BorrowError, BorrowMutError};
 use itertools::Itertools;
 
 use crate::{
-    archetype::{Archetype, ArchetypeId, ArchetypeInfo, Slot},
+    archetype::{Archetype, ArchetypeId, ArchetypeInfo, Slot, Storage},
     archetypes::Archetypes,
     buffer::ComponentBuffer,
     component::{dummy, ComponentDesc, ComponentKey, ComponentValue},
-    components::{self, component_info, is_static, name},
-    entity::{entity_ids, Entity, EntityIndex, EntityKind, EntityLocation, EntityStore},
+    components::{self, component_info, disabled, is_static, name},
+    entity::{entity_ids, Bundle, Entity, EntityIndex, EntityKind, EntityLocation, EntityStore},
     entity_ref::{EntityRef, EntityRefMut},
     entry::{Entry, OccupiedEntry, VacantEntry},
     error::{MissingComponent, Result},
-    events::EventSubscriber,
+    events::{EventData, EventSubscriber},
     filter::StaticFilter,
     format::{EntitiesFormatter, HierarchyFormatter, WorldFormatter},
-    relation::{Relation, RelationExt},
+    metadata::{
+        entity_mapper, indexed, on_target_despawn, shared, sparse_relation, MapEntities,
+        SparseRelation, TargetDespawnPolicy, UniqueConstraint, UniquePolicy, ValueIndex,
+    },
+    relation::{PairTicks, Relation, RelationExt},
     writer::{
         self, EntityWriter, FnWriter, Replace, ReplaceDyn, SingleComponentWriter, WriteDedup,
     },
     BatchSpawn, Component, ComponentVTable, Error, Fetch, Query, RefMut,
 };
 
+#[cfg(feature = "std")]
+use crate::system::NonSendStore;
+
 #[derive(Debug, Default)]
 struct EntityStores {
     inner: BTreeMap<EntityKind, EntityStore>,
@@ -71,6 +82,21 @@ pub(crate) fn update_entity_loc(
     *ns.get_mut(id).expect("Entity is not valid") = loc;
 }
 
+/// Marks an entity as using the shared value held by the target of the tag.
+///
+/// The tag is a pair of the shared component and the value entity, which allows the archetypes
+/// using any value of a component to be found through the archetype index.
+///
+/// See: [`World::set_shared`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SharedTag;
+
+static SHARED_TAG: &ComponentVTable<SharedTag> = crate::component_vtable!(shared_value: SharedTag);
+
+fn shared_tag(component: ComponentKey, value_id: Entity) -> Component<SharedTag> {
+    Component::new(ComponentKey::new(component.id, Some(value_id)), SHARED_TAG)
+}
+
 /// The main entry point of the ECS
 ///
 /// Holds the entities and components of the ECS.
@@ -84,9 +110,19 @@ pub(crate) fn update_entity_loc(
 pub struct World {
     entities: EntityStores,
     pub(crate) archetypes: Archetypes,
+    /// Value indices of components with the [`Indexed`](crate::metadata::Indexed) metadata
+    pub(crate) indices: BTreeMap<ComponentKey, Arc<dyn ValueIndex>>,
+    /// The subjects of each target of [`Sparse`](crate::metadata::Sparse) relations.
+    ///
+    /// Entries are not removed when the subject is despawned, and are validated on use.
+    sparse_subjects: BTreeMap<Entity, BTreeSet<(ComponentDesc, Entity)>>,
+    /// Incremented when a pair of a sparse relation is added or removed
+    sparse_gen: u32,
     change_tick: AtomicU32,
 
     has_reserved: AtomicBool,
+    #[cfg(feature = "std")]
+    non_send: NonSendStore,
 }
 
 impl World {
@@ -95,8 +131,13 @@ impl World {
         Self {
             entities: EntityStores::new(),
             archetypes: Archetypes::new(),
+            indices: BTreeMap::new(),
+            sparse_subjects: BTreeMap::new(),
+            sparse_gen: 0,
             change_tick: AtomicU32::new(0b11),
             has_reserved: AtomicBool::new(false),
+            #[cfg(feature = "std")]
+            non_send: NonSendStore::default(),
         }
     }
 
@@ -180,7 +221,7 @@ impl World {
     }
 
     // Check if the entity is reserved after flush
-    fn is_reserved(&self, id: Entity) -> bool {
+    pub(crate) fn is_reserved(&self, id: Entity) -> bool {
         self.location(id)
             .map(|v| v.arch_id == self.archetypes.reserved)
             .unwrap_or_default()
@@ -220,8 +261,11 @@ impl World {
         id: Entity,
         buffer: &mut ComponentBuffer,
     ) -> Result<(Entity, EntityLocation)> {
+        unsafe { self.ensure_unique(None, buffer.iter_dyn())? };
+
         let change_tick = self.advance_change_tick();
 
+        let mut sparse = buffer.take_sparse();
         for &component in buffer.components() {
             self.init_component(component);
         }
@@ -233,13 +277,18 @@ impl World {
             unsafe { arch.push(desc.key(), src, change_tick) }
         }
 
+        let loc = writer::write_sparse_buffer(self, id, loc, &mut sparse, change_tick);
+
         Ok((id, loc))
     }
 
     /// Spawn an entity with the given components.
     ///
     /// For increased ergonomics, prefer [crate::EntityBuilder]
-    pub(crate) fn spawn_with(&mut self, buffer: &mut ComponentBuffer) -> Entity {
+    pub(crate) fn spawn_with(&mut self, buffer: &mut ComponentBuffer) -> Result<Entity> {
+        unsafe { self.ensure_unique(None, buffer.iter_dyn())? };
+
+        let mut sparse = buffer.take_sparse();
         for component in buffer.components() {
             self.init_component(*component);
         }
@@ -247,7 +296,7 @@ impl World {
         let change_tick = self.advance_change_tick();
         let (arch_id, _) = self.archetypes.find_create(buffer.components().copied());
 
-        let (id, _, arch) = self.spawn_inner(arch_id, EntityKind::empty());
+        let (id, loc, arch) = self.spawn_inner(arch_id, EntityKind::empty());
 
         for (desc, src) in buffer.drain() {
             unsafe {
@@ -255,7 +304,9 @@ impl World {
             }
         }
 
-        id
+        writer::write_sparse_buffer(self, id, loc, &mut sparse, change_tick);
+
+        Ok(id)
     }
 
     /// Removes all components from an entity without despawning the entity
@@ -267,6 +318,8 @@ impl World {
             .get_disjoint(arch_id, self.archetypes.root)
             .unwrap();
 
+        let shared: SmallVec<[ComponentKey; 4]> = Self::shared_tags(src).collect();
+
         let (dst_slot, swapped) = unsafe { src.move_to(dst, slot, |c, p| c.drop(p)) };
 
         if let Some((swapped, slot)) = swapped {
@@ -285,6 +338,10 @@ impl World {
             arch_id: self.archetypes.root,
         };
 
+        for tag in shared {
+            self.release_shared_value(tag);
+        }
+
         Ok(())
     }
 
@@ -351,13 +408,515 @@ impl World {
         self.spawn_at(id).unwrap();
 
         self.set_with(id, &mut meta).unwrap();
+        self.init_index(desc);
+    }
+
+    /// Creates the value index for a component with the [`Indexed`](crate::metadata::Indexed)
+    /// metadata, indexing any existing values.
+    fn init_index(&mut self, desc: ComponentDesc) {
+        if desc.key.is_relation() || self.indices.contains_key(&desc.key) {
+            return;
+        }
+
+        let Some(indexed) = desc.meta_ref().get(indexed()) else {
+            return;
+        };
+
+        let (index, subscriber) = (indexed.create)(desc.key);
+
+        let archetypes = self.archetypes.index.find(desc.key);
+        for &arch_id in archetypes.into_iter().flat_map(|v| v.keys()) {
+            let arch = self.archetypes.get(arch_id);
+            let data = arch.cell(desc.key).unwrap().data.borrow();
+            let event = EventData {
+                ids: arch.entities(),
+                slots: arch.slots(),
+                key: desc.key,
+            };
+
+            subscriber.on_added(&data.storage, &event);
+        }
+
+        self.archetypes.add_subscriber(subscriber);
+        self.indices.insert(desc.key, index);
+    }
+
+    /// Returns all entities where `component` is equal to `value`.
+    ///
+    /// This uses the index of components with the [`Indexed`](crate::metadata::Indexed)
+    /// metadata, and otherwise falls back to checking each entity with the component.
+    ///
+    /// Disabled entities are included.
+    pub fn find_by<T: ComponentValue + PartialEq>(
+        &self,
+        component: Component<T>,
+        value: &T,
+    ) -> Vec<Entity> {
+        let found = self
+            .indices
+            .get(&component.key())
+            .and_then(|v| v.find(self, value));
+
+        if let Some(found) = found {
+            return found;
+        }
+
+        Query::new((entity_ids(), component))
+            .with_components()
+            .include_disabled()
+            .borrow(self)
+            .iter()
+            .filter(|(_, v)| *v == value)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Returns the entity which has `component`, along with its value.
+    ///
+    /// This is intended for components with the [`Unique`](crate::metadata::Unique) constraint,
+    /// which is held by at most one entity. Should multiple entities have the component anyway,
+    /// such as when the constraint is missing or after [merging](Self::merge_with) worlds which
+    /// both hold it, the first entity of the oldest archetype is returned.
+    ///
+    /// Disabled entities are included.
+    pub fn single<T: ComponentValue>(
+        &self,
+        component: Component<T>,
+    ) -> Option<(Entity, AtomicRef<'_, T>)> {
+        let archetypes = self.archetypes.index.find(component.key())?;
+
+        archetypes.keys().find_map(|&arch_id| {
+            let arch = self.archetypes.get(arch_id);
+            let &id = arch.entities().first()?;
+            Some((id, arch.get(0, component)?))
+        })
+    }
+
+    /// Returns all entities other than `id` which hold a value of the component conflicting with
+    /// `value`.
+    ///
+    /// # Safety
+    /// `value` must point to a valid value of the component
+    pub(crate) unsafe fn unique_conflicts(
+        &self,
+        id: Option<Entity>,
+        desc: ComponentDesc,
+        constraint: &UniqueConstraint,
+        value: *const u8,
+    ) -> Vec<Entity> {
+        // Unique values are indexed, while a unique component is held by at most one entity
+        if constraint.compares_values() {
+            if let Some(index) = self.indices.get(&desc.key) {
+                let mut conflicts = index.find_raw(self, value);
+                conflicts.retain(|&holder| Some(holder) != id);
+                return conflicts;
+            }
+        }
+
+        let Some(archetypes) = self.archetypes.index.find(desc.key) else {
+            return Vec::new();
+        };
+
+        let mut conflicts = Vec::new();
+        for &arch_id in archetypes.keys() {
+            let arch = self.archetypes.get(arch_id);
+            let data = arch.cell(desc.key).unwrap().data.borrow();
+
+            conflicts.extend(
+                arch.entities()
+                    .iter()
+                    .enumerate()
+                    .filter(|&(slot, &holder)| {
+                        Some(holder) != id && constraint.conflicts_at(&data.storage, slot, value)
+                    })
+                    .map(|(_, &holder)| holder),
+            );
+        }
+
+        conflicts
+    }
+
+    /// Resolves conflicts with the [`Unique`](crate::metadata::Unique) and
+    /// [`UniqueValue`](crate::metadata::UniqueValue) constraints of the components which are about
+    /// to be set for `id`, or for a new entity if `None`.
+    ///
+    /// Fails without modifying the world if any conflict is refused.
+    ///
+    /// # Safety
+    /// Each pointer must point to a valid value of the accompanying component
+    pub(crate) unsafe fn ensure_unique(
+        &mut self,
+        id: Option<Entity>,
+        components: impl IntoIterator<Item = (ComponentDesc, *const u8)>,
+    ) -> Result<()> {
+        let mut stolen = Vec::new();
+        for (desc, value) in components {
+            let Some(&constraint) = desc.unique_constraint() else {
+                continue;
+            };
+
+            let conflicts = self.unique_conflicts(id, desc, &constraint, value);
+            match constraint.policy {
+                UniquePolicy::Error => {
+                    if let Some(&holder) = conflicts.first() {
+                        return Err(Error::NotUnique(desc, Some(holder)));
+                    }
+                }
+                UniquePolicy::Steal => stolen.extend(conflicts.into_iter().map(|v| (v, desc))),
+            }
+        }
+
+        if let Some(id) = id {
+            if !stolen.is_empty() {
+                self.init_location(id)?;
+            }
+        }
+
+        for (holder, desc) in stolen {
+            self.remove_dyn(holder, desc)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the shared value of `component` for an entity.
+    ///
+    /// Shared values are stored once rather than for each entity, and entities with equal values
+    /// are grouped into the same archetype. Changing the shared value of an entity moves it to
+    /// another archetype. The value held by the entity itself, if any, is removed.
+    ///
+    /// Shared values are fetched using [`FetchExt::shared`](crate::FetchExt::shared), or
+    /// [`World::get_shared`].
+    ///
+    /// Each distinct value is stored on a component entity, which is despawned once no entity
+    /// uses it anymore.
+    ///
+    /// # Panics
+    /// If the component does not have the [`Shared`](crate::metadata::Shared) metadata, or is a
+    /// relation.
+    pub fn set_shared<T: ComponentValue + PartialEq>(
+        &mut self,
+        id: Entity,
+        component: Component<T>,
+        value: T,
+    ) -> Result<()> {
+        assert!(
+            !component.key().is_relation() && component.desc().meta_ref().has(shared()),
+            "{} is not a shared component",
+            component.name()
+        );
+
+        let loc = self.init_location(id)?;
+        let current = self.shared_value_of(self.archetypes.get(loc.arch_id), component.key());
+
+        let value_id = match self.find_shared_value(component, &value) {
+            Some(v) => v,
+            None => {
+                let tag = self.spawn_component(SHARED_TAG);
+                self.set(tag.id(), name(), component.name().into())?;
+                self.set(tag.id(), component, value)?;
+                tag.id()
+            }
+        };
+
+        if current == Some(value_id) {
+            return Ok(());
+        }
+
+        if self.has(id, component) {
+            self.remove(id, component)?;
+        }
+
+        self.set(id, shared_tag(component.key(), value_id), SharedTag)?;
+
+        if let Some(current) = current {
+            let tag = shared_tag(component.key(), current);
+            self.remove_dyn(id, tag.desc())?;
+            self.release_shared_value(tag.key());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the shared value of `component` for an entity.
+    ///
+    /// See: [`World::set_shared`]
+    pub fn get_shared<T: ComponentValue>(
+        &self,
+        id: Entity,
+        component: Component<T>,
+    ) -> Result<AtomicRef<'_, T>> {
+        let loc = self.location(id)?;
+
+        self.shared_value_of(self.archetypes.get(loc.arch_id), component.key())
+            .and_then(|v| self.get(v, component).ok())
+            .ok_or_else(|| {
+                Error::MissingComponent(MissingComponent {
+                    id,
+                    desc: component.desc(),
+                })
+            })
+    }
+
+    /// Removes the shared value of `component` from an entity.
+    ///
+    /// The value is despawned if no other entity uses it.
+    pub fn remove_shared<T: ComponentValue>(
+        &mut self,
+        id: Entity,
+        component: Component<T>,
+    ) -> Result<()> {
+        let loc = self.init_location(id)?;
+
+        let value_id = self
+            .shared_value_of(self.archetypes.get(loc.arch_id), component.key())
+            .ok_or_else(|| {
+                Error::MissingComponent(MissingComponent {
+                    id,
+                    desc: component.desc(),
+                })
+            })?;
+
+        let tag = shared_tag(component.key(), value_id);
+        self.remove_dyn(id, tag.desc())?;
+        self.release_shared_value(tag.key());
+
+        Ok(())
+    }
+
+    /// Returns the tags of the shared values used by the archetype
+    fn shared_tags(arch: &Archetype) -> impl Iterator<Item = ComponentKey> + '_ {
+        arch.cells()
+            .iter()
+            .map(|v| v.desc())
+            .filter(|v| v.key.is_relation() && v.is::<SharedTag>())
+            .map(|v| v.key)
+    }
+
+    /// Returns the entities holding the shared values used by the archetype
+    pub(crate) fn shared_values<'a>(
+        &'a self,
+        arch: &'a Archetype,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        Self::shared_tags(arch).map(|v| v.target.unwrap())
+    }
+
+    /// Returns the entity holding the shared value of `component` used by the archetype
+    fn shared_value_of(&self, arch: &Archetype, component: ComponentKey) -> Option<Entity> {
+        // Shared components are never relations, so any pair of the component is a tag
+        arch.relations_like(component.id)
+            .next()
+            .map(|(key, _)| key.target.unwrap())
+    }
+
+    /// Despawns the value of a shared tag once no entity uses it anymore
+    fn release_shared_value(&mut self, tag: ComponentKey) {
+        let in_use = self
+            .archetypes
+            .index
+            .find(tag)
+            .into_iter()
+            .flat_map(|v| v.keys())
+            .any(|&arch_id| !self.archetypes.get(arch_id).is_empty());
+
+        let value_id = tag.target.unwrap();
+        if !in_use && self.is_alive(value_id) {
+            self.despawn(value_id)
+                .expect("Shared values are not the target of any relation");
+        }
+    }
+
+    /// Returns the entity holding a shared value of `component` equal to `value`
+    fn find_shared_value<T: ComponentValue + PartialEq>(
+        &self,
+        component: Component<T>,
+        value: &T,
+    ) -> Option<Entity> {
+        let archetypes = self.archetypes.index.find(component.key())?;
+
+        archetypes.keys().find_map(|&arch_id| {
+            let arch = self.archetypes.get(arch_id);
+            let info = arch.borrow::<ComponentDesc>(component_info().key())?;
+            let values = arch.borrow::<T>(component.key())?;
+
+            arch.entities()
+                .iter()
+                .zip(info.get().iter().zip(values.get()))
+                .find(|(_, (info, v))| info.is::<SharedTag>() && *v == value)
+                .map(|(&id, _)| id)
+        })
+    }
+
+    /// Inserts a pair of a sparse relation, moving `value`
+    pub(crate) unsafe fn put_sparse(
+        &mut self,
+        id: Entity,
+        desc: ComponentDesc,
+        sparse: SparseRelation,
+        value: *mut u8,
+        ticks: PairTicks,
+        tick: u32,
+    ) -> EntityLocation {
+        let loc = (sparse.put)(self, id, desc, value, ticks, tick);
+
+        self.sparse_subjects
+            .entry(desc.key.target.unwrap())
+            .or_default()
+            .insert((desc, id));
+        self.sparse_gen = self.sparse_gen.wrapping_add(1);
+
+        loc
+    }
+
+    /// Removes a pair of a sparse relation, and the pairs column once empty
+    unsafe fn remove_sparse(
+        &mut self,
+        id: Entity,
+        desc: ComponentDesc,
+        sparse: SparseRelation,
+        on_drop: &mut dyn FnMut(*mut u8),
+    ) -> Result<EntityLocation> {
+        let mut storage = Storage::new(desc);
+        if (sparse.take)(self, id, desc, &mut storage, self.advance_change_tick()).is_none() {
+            return Err(Error::MissingComponent(MissingComponent { id, desc }));
+        }
+
+        storage.swap_remove(0, on_drop);
+
+        let target = desc.key.target.unwrap();
+        if let Some(subjects) = self.sparse_subjects.get_mut(&target) {
+            subjects.remove(&(desc, id));
+        }
+        self.sparse_gen = self.sparse_gen.wrapping_add(1);
+
+        let loc = self.location(id)?;
+        let arch = self.archetypes.get(loc.arch_id);
+
+        let empty = arch
+            .sparse_targets(desc.key.id)
+            .is_some_and(|v| v.get(loc.slot).is_empty());
+
+        if empty {
+            let pairs = arch
+                .component(ComponentKey::new(desc.key.id, None))
+                .unwrap();
+            return self.remove_inner(id, pairs, |ptr| pairs.drop(ptr));
+        }
+
+        Ok(loc)
+    }
+
+    /// Returns the subjects of sparse relation pairs targeting `target`
+    pub(crate) fn sparse_subjects(
+        &self,
+        target: Entity,
+    ) -> impl Iterator<Item = (ComponentDesc, Entity)> + '_ {
+        self.sparse_subjects
+            .get(&target)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&(desc, id)| {
+                self.location(id)
+                    .is_ok_and(|loc| self.archetypes.get(loc.arch_id).has_at(loc.slot, desc.key))
+            })
     }
 
     /// Despawn an entity.
     /// Any relations to other entities will be removed.
+    ///
+    /// Subjects of relations targeting the entity are handled according to the
+    /// [`TargetDespawnPolicy`] of each relation, which may despawn them as well.
     pub fn despawn(&mut self, id: Entity) -> Result<()> {
         profile_function!();
         self.flush_reserved();
+        self.init_location(id)?;
+
+        // Despawn the subjects first to avoid moving them when detaching the target
+        for id in self.despawn_targets(id)?.into_iter().rev() {
+            self.despawn_one(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `id` and all subjects which would be despawned along with it, according to the
+    /// [`TargetDespawnPolicy`] of their relations.
+    pub(crate) fn despawn_targets(&self, id: Entity) -> Result<Vec<Entity>> {
+        let mut result = alloc::vec![id];
+        let mut visited = BTreeSet::from([id]);
+
+        let mut index = 0;
+        while let Some(&target) = result.get(index) {
+            index += 1;
+
+            let archetypes = self
+                .archetypes
+                .index
+                .find_relation_targets(target)
+                .into_iter()
+                .flat_map(|v| v.keys());
+
+            for &arch_id in archetypes {
+                let arch = self.archetypes.get(arch_id);
+                if arch.is_empty() {
+                    continue;
+                }
+
+                for desc in arch
+                    .components_desc()
+                    .filter(|v| v.key().target() == Some(target))
+                {
+                    let policy = desc
+                        .meta_ref()
+                        .get(on_target_despawn())
+                        .copied()
+                        .unwrap_or_default();
+
+                    match policy {
+                        TargetDespawnPolicy::Remove => {}
+                        TargetDespawnPolicy::DespawnSubject => result.extend(
+                            arch.entities()
+                                .iter()
+                                .copied()
+                                .filter(|&v| visited.insert(v)),
+                        ),
+                        TargetDespawnPolicy::Error => return Err(Error::TargetInUse(target, desc)),
+                        TargetDespawnPolicy::Panic => {
+                            panic!("Attempt to despawn {target} which is the target of relation {desc:?}")
+                        }
+                    }
+                }
+            }
+
+            for (desc, subject) in self.sparse_subjects(target) {
+                let policy = desc
+                    .meta_ref()
+                    .get(on_target_despawn())
+                    .copied()
+                    .unwrap_or_default();
+
+                match policy {
+                    TargetDespawnPolicy::Remove => {}
+                    TargetDespawnPolicy::DespawnSubject => {
+                        if visited.insert(subject) {
+                            result.push(subject)
+                        }
+                    }
+                    TargetDespawnPolicy::Error => return Err(Error::TargetInUse(target, desc)),
+                    TargetDespawnPolicy::Panic => {
+                        panic!(
+                            "Attempt to despawn {target} which is the target of relation {desc:?}"
+                        )
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn despawn_one(&mut self, id: Entity) -> Result<()> {
         let EntityLocation {
             arch_id: arch,
             slot,
@@ -369,6 +928,9 @@ impl World {
 
         let src = self.archetypes.get_mut(arch);
 
+        // The shared values used by the entity may no longer be in use
+        let shared: SmallVec<[ComponentKey; 4]> = Self::shared_tags(src).collect();
+
         let swapped = unsafe {
             src.take(slot, |c, p| {
                 c.drop(p);
@@ -387,21 +949,54 @@ impl World {
         // self.archetypes.prune_arch(arch);
         self.entities.init(id.kind()).despawn(id)?;
         self.detach(id);
+
+        for tag in shared {
+            self.release_shared_value(tag);
+        }
+
         Ok(())
     }
 
     /// Despawns all entities which matches the filter
-    pub fn despawn_many<F>(&mut self, filter: F)
+    ///
+    /// Entities which are refused by an [`OnTargetDespawn<Error>`] relation are kept, and the
+    /// first refusal is returned once all other entities are despawned.
+    ///
+    /// [`OnTargetDespawn<Error>`]: crate::metadata::OnTargetDespawn
+    pub fn despawn_many<F>(&mut self, filter: F) -> Result<()>
     where
         F: for<'x> Fetch<'x>,
     {
         profile_function!();
         self.flush_reserved();
-        let mut query = Query::new(entity_ids()).filter(filter);
-        let ids = query.borrow(self).iter().collect_vec();
+        let mut query = Query::new(entity_ids()).include_disabled().filter(filter);
+        let mut ids = query.borrow(self).iter().collect_vec();
+
+        // Refused entities are retried, as their subjects may be despawned later on
+        loop {
+            let count = ids.len();
+            let mut refused = None;
+
+            ids.retain(|&id| {
+                // May already have been despawned along with a relation target
+                if !self.is_alive(id) {
+                    return false;
+                }
+
+                match self.despawn(id) {
+                    Ok(()) => false,
+                    Err(err) => {
+                        refused.get_or_insert(err);
+                        true
+                    }
+                }
+            });
 
-        for id in ids {
-            self.despawn(id).expect("Invalid entity id");
+            match refused {
+                None => return Ok(()),
+                Some(err) if ids.len() == count => return Err(err),
+                Some(_) => {}
+            }
         }
     }
 
@@ -430,7 +1025,18 @@ impl World {
 
         let mut stack = alloc::vec![id];
         let mut archetypes = Vec::new();
+        let mut sparse_children = Vec::new();
+        let mut shared = BTreeSet::new();
         while let Some(id) = stack.pop() {
+            let children = self
+                .sparse_subjects(id)
+                .filter(|(desc, _)| desc.key.id == relation.id())
+                .map(|(_, child)| child);
+
+            let start = sparse_children.len();
+            sparse_children.extend(children);
+            stack.extend_from_slice(&sparse_children[start..]);
+
             profile_scope!("traverse_archetypes");
             archetypes.clear();
             archetypes.extend(
@@ -444,6 +1050,7 @@ impl World {
             for &arch_id in &archetypes {
                 let arch = self.archetypes.get(arch_id);
                 stack.extend(arch.entities());
+                shared.extend(Self::shared_tags(arch));
                 for &id in arch.entities() {
                     self.entities.init(id.kind()).despawn(id).unwrap();
                 }
@@ -451,6 +1058,17 @@ impl World {
             }
         }
 
+        // Despawned last, as the pairs are needed to find the children
+        for id in sparse_children {
+            if self.is_alive(id) {
+                self.despawn_one(id)?;
+            }
+        }
+
+        for tag in shared {
+            self.release_shared_value(tag);
+        }
+
         Ok(())
     }
 
@@ -485,6 +1103,34 @@ impl World {
                 }
             }
         }
+
+        let sparse = self.sparse_subjects(id).collect_vec();
+        for (desc, subject) in sparse {
+            self.remove_dyn(subject, desc).unwrap();
+        }
+
+        self.sparse_subjects.remove(&id);
+    }
+
+    /// Disables an entity, excluding it from all queries which do not opt in using
+    /// [`Query::include_disabled`].
+    ///
+    /// The entity keeps all its components and can still be accessed directly.
+    pub fn disable(&mut self, id: Entity) -> Result<()> {
+        self.set(id, disabled(), ())?;
+        Ok(())
+    }
+
+    /// Enables an entity previously disabled using [`World::disable`].
+    pub fn enable(&mut self, id: Entity) -> Result<()> {
+        if self.has(id, disabled()) {
+            self.remove(id, disabled())?;
+        } else {
+            // Still fail for dead entities
+            self.location(id)?;
+        }
+
+        Ok(())
     }
 
     /// Updates a component in place
@@ -542,6 +1188,8 @@ impl World {
         component: Component<T>,
         value: T,
     ) -> Result<Option<T>> {
+        unsafe { self.ensure_unique(Some(id), [(component.desc(), (&value as *const T).cast())])? };
+
         Ok(self
             .set_with_writer(
                 id,
@@ -553,6 +1201,7 @@ impl World {
 
     /// Add the components stored in a component buffer to an entity
     pub fn set_with(&mut self, id: Entity, buffer: &mut ComponentBuffer) -> Result<()> {
+        unsafe { self.ensure_unique(Some(id), buffer.iter_dyn())? };
         self.set_with_writer(id, writer::Buffered::new(buffer))?;
 
         Ok(())
@@ -565,6 +1214,8 @@ impl World {
         desc: ComponentDesc,
         value: *mut u8,
     ) -> Result<EntityLocation> {
+        unsafe { self.ensure_unique(Some(id), [(desc, value.cast_const())])? };
+
         let (loc, _) =
             self.set_with_writer(id, SingleComponentWriter::new(desc, ReplaceDyn { value }))?;
 
@@ -607,6 +1258,13 @@ impl World {
         let src = self.archetypes.get(src_id);
 
         if !src.has(desc.key()) {
+            if let Some(sparse) = sparse_relation(desc) {
+                let mut on_drop = Some(on_drop);
+                return self.remove_sparse(id, desc, sparse, &mut |ptr| {
+                    (on_drop.take().expect("On drop called more than once"))(ptr)
+                });
+            }
+
             return Err(Error::MissingComponent(MissingComponent { id, desc }));
         }
 
@@ -673,6 +1331,23 @@ impl World {
         Ok(res)
     }
 
+    /// Removes all components of a bundle from the entity and returns them as the bundle.
+    ///
+    /// Nothing is removed if the entity is missing any of the components.
+    pub fn remove_bundle<B: Bundle>(&mut self, id: Entity) -> Result<B> {
+        let loc = self.location(id)?;
+        let arch = self.archetypes.get(loc.arch_id);
+
+        if let Some(desc) = B::components()
+            .into_iter()
+            .find(|desc| !arch.has_at(loc.slot, desc.key()))
+        {
+            return Err(Error::MissingComponent(MissingComponent { id, desc }));
+        }
+
+        B::remove_from(self, id)
+    }
+
     /// Randomly access an entity's component.
     pub fn get<T: ComponentValue>(
         &self,
@@ -761,7 +1436,9 @@ impl World {
     /// specified component
     pub fn has<T: ComponentValue>(&self, id: Entity, component: Component<T>) -> bool {
         if let Ok(loc) = self.location(id) {
-            self.archetypes.get(loc.arch_id).has(component.key())
+            self.archetypes
+                .get(loc.arch_id)
+                .has_at(loc.slot, component.key())
         } else {
             false
         }
@@ -960,6 +1637,12 @@ impl World {
         (id, loc, arch)
     }
 
+    /// Returns a counter which changes whenever a pair of a
+    /// [`Sparse`](crate::metadata::Sparse) relation is added or removed
+    pub(crate) fn sparse_gen(&self) -> u32 {
+        self.sparse_gen
+    }
+
     /// Get a reference to the world's archetype generation
     #[must_use]
     pub fn archetype_gen(&self) -> u32 {
@@ -1113,6 +1796,42 @@ impl World {
         self.archetypes.add_subscriber(Arc::new(subscriber))
     }
 
+    /// Insert a resource which is not `Send` or `Sync`, such as a window handle.
+    ///
+    /// Non-send resources are owned by the thread which inserted them, and accessing them from any
+    /// other thread panics. Systems accessing them using [`NonSend`](crate::system::NonSend) run on
+    /// the thread executing the schedule.
+    ///
+    /// If the world is dropped on another thread, the non-send resources are leaked.
+    ///
+    /// Returns the previous value, if any.
+    #[cfg(feature = "std")]
+    pub fn insert_non_send<T: 'static>(&mut self, value: T) -> Option<T> {
+        self.non_send.insert(value)
+    }
+
+    /// Removes a non-send resource
+    #[cfg(feature = "std")]
+    pub fn remove_non_send<T: 'static>(&mut self) -> Option<T> {
+        self.non_send.remove()
+    }
+
+    /// Borrow a non-send resource.
+    ///
+    /// Panics if called from another thread than the one which inserted the resources.
+    #[cfg(feature = "std")]
+    pub fn non_send<T: 'static>(&self) -> Option<AtomicRef<'_, T>> {
+        self.non_send.get()
+    }
+
+    /// Borrow a non-send resource mutably.
+    ///
+    /// Panics if called from another thread than the one which inserted the resources.
+    #[cfg(feature = "std")]
+    pub fn non_send_mut<T: 'static>(&self) -> Option<AtomicRefMut<'_, T>> {
+        self.non_send.get_mut()
+    }
+
     /// Merges `other` into `self`.
     ///
     /// Colliding entities will be migrated to a new entity id. Static entities will not be
@@ -1121,6 +1840,9 @@ impl World {
     ///
     /// Returns a map of all the entities which were remapped.
     ///
+    /// Entity ids stored inside components with the
+    /// [`EntityMapper`](crate::metadata::EntityMapper) metadata are remapped as well.
+    ///
     /// `other` will be left empty
     ///
     /// **Note**: The data from `other` will all be marked as *added*
@@ -1193,6 +1915,11 @@ impl World {
                         storage.set_id(id);
                     }
 
+                    // Modify the entities referenced by the values
+                    if let Some(mapper) = storage.desc().meta_ref().get(entity_mapper()) {
+                        (mapper.map_storage)(&mut storage, &mut |v| *new_ids.get(&v).unwrap_or(&v));
+                    }
+
                     batch.append(storage).expect("Batch is incomplete");
                 }
 
@@ -1220,11 +1947,21 @@ impl World {
                             *target = *new_ids.get(target).unwrap_or(target);
                         }
 
+                        if let Some(mapper) = desc.meta_ref().get(entity_mapper()) {
+                            (mapper.map_ptr)(ptr, &mut |v| *new_ids.get(&v).unwrap_or(&v));
+                        }
+
                         // Migrate custom components
                         buffer.set_dyn(desc, ptr);
                     })
                 } {
                     buffer.append_to(self, id).unwrap();
+
+                    // Component entities are appended directly rather than initialized
+                    let desc = self.get(id, component_info()).map(|v| *v);
+                    if let Ok(desc) = desc {
+                        self.init_index(desc);
+                    }
                 }
             }
         }
@@ -1233,7 +1970,7 @@ impl World {
 
     /// Converts all reserved entity ids into actual empty entities placed in a special archetype.
     #[inline]
-    fn flush_reserved(&mut self) {
+    pub(crate) fn flush_reserved(&mut self) {
         if !self.has_reserved.swap(false, Relaxed) {
             return;
         }
@@ -1253,7 +1990,7 @@ impl World {
         }
     }
 
-    fn reserve_at(&mut self, id: Entity) -> Result<()> {
+    pub(crate) fn reserve_at(&mut self, id: Entity) -> Result<()> {
         self.flush_reserved();
         self.entities.init(id.kind).reserve_at(id.index())
     }
@@ -1304,6 +2041,14 @@ impl MigratedEntities {
         move |target| component.of(target)
     }
 
+    /// Rewrites the entities referenced by `value` to their migrated ids.
+    ///
+    /// Components with the [`EntityMapper`](crate::metadata::EntityMapper) metadata are migrated
+    /// automatically.
+    pub fn map_entities<T: MapEntities>(&self, value: &mut T) {
+        value.map_entities(&mut |v| self.get(v))
+    }
+
     /// Returns the migrated ids
     pub fn ids(&self) -> &BTreeMap<Entity, Entity> {
         &self.ids
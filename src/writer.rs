@@ -4,11 +4,12 @@ use alloc::vec::Vec;
 use itertools::{Either, Itertools};
 
 use crate::{
-    archetype::{ArchetypeId, CellData, Slice, Slot},
+    archetype::{ArchetypeId, Cell, CellData, Slice, Slot},
     buffer::ComponentBuffer,
     component::{ComponentDesc, ComponentValue},
     entity::EntityLocation,
-    metadata::exclusive,
+    metadata::{exclusive, sparse_relation, SparseRelation},
+    relation::{PairTicks, SparsePairs},
     world::update_entity_loc,
     Entity, World,
 };
@@ -56,6 +57,8 @@ where
     type Updated = U;
 
     unsafe fn update(self, data: &mut CellData, slot: Slot, id: Entity, tick: u32) -> U {
+        data.record_previous(&[id], Slice::single(slot), tick);
+
         let value = &mut *(data.storage.at_mut(slot).unwrap() as *mut T);
         let res = (self.func)(value);
 
@@ -112,6 +115,11 @@ unsafe impl<W: ComponentUpdater + ComponentPusher> EntityWriter for SingleCompon
             return (src_loc, Either::Left(res));
         }
 
+        if let Some(sparse) = sparse_relation(self.desc) {
+            return unsafe { write_sparse(world, id, self.desc, sparse, self.writer, tick) };
+        }
+
+        let arch = world.archetypes.get_mut(src_loc.arch_id);
         let (src, dst, dst_id) = if let Some(&dst_id) = arch.outgoing.get(&key) {
             let (src, dst) = world
                 .archetypes
@@ -174,6 +182,82 @@ unsafe impl<W: ComponentUpdater + ComponentPusher> EntityWriter for SingleCompon
     }
 }
 
+/// Writes a pair of a sparse relation into the pairs column of the entity.
+///
+/// The existing value is moved out into a temporary cell which the writer operates on, before
+/// being moved back into the pairs.
+unsafe fn write_sparse<W: ComponentUpdater + ComponentPusher>(
+    world: &mut World,
+    id: Entity,
+    desc: ComponentDesc,
+    sparse: SparseRelation,
+    writer: W,
+    tick: u32,
+) -> (EntityLocation, Either<W::Updated, W::Pushed>) {
+    let mut cell = Cell::new(desc);
+    let data = cell.data.get_mut();
+
+    // A replaced pair keeps when it was added
+    let added = (sparse.take)(world, id, desc, &mut data.storage, tick);
+    let res = if added.is_some() {
+        Either::Left(writer.update(data, 0, id, tick))
+    } else {
+        Either::Right(writer.push(data, id, tick))
+    };
+
+    let ticks = PairTicks {
+        added: added.unwrap_or(tick),
+        modified: tick,
+    };
+
+    let mut dst_loc = None;
+    data.storage.swap_remove(0, |value| {
+        dst_loc = Some(world.put_sparse(id, desc, sparse, value, ticks, tick))
+    });
+
+    (dst_loc.unwrap(), res)
+}
+
+/// Inserts a pair into the [`SparsePairs`] of an entity
+pub(crate) struct InsertPair<T> {
+    pub(crate) target: Entity,
+    pub(crate) value: T,
+    pub(crate) ticks: PairTicks,
+    /// Remove all other pairs
+    pub(crate) exclusive: bool,
+}
+
+impl<T: ComponentValue> ComponentUpdater for InsertPair<T> {
+    type Updated = ();
+
+    unsafe fn update(self, data: &mut CellData, slot: Slot, id: Entity, tick: u32) {
+        data.record_previous(&[id], Slice::single(slot), tick);
+
+        let pairs = &mut data.storage.downcast_mut::<SparsePairs<T>>()[slot];
+        if self.exclusive {
+            pairs.clear();
+        }
+
+        pairs.insert(self.target, self.value, self.ticks);
+
+        data.set_modified(&[id], Slice::single(slot), tick);
+    }
+}
+
+impl<T: ComponentValue> ComponentPusher for InsertPair<T> {
+    type Pushed = ();
+
+    unsafe fn push(self, data: &mut CellData, id: Entity, tick: u32) {
+        let slot = data.storage.len();
+
+        let mut pairs = SparsePairs::new();
+        pairs.insert(self.target, self.value, self.ticks);
+        data.storage.push(pairs);
+
+        data.set_added(&[id], Slice::single(slot), tick);
+    }
+}
+
 /// # Safety
 /// *All* components of the new slot must be initialized
 pub(crate) unsafe trait MigrateEntity {
@@ -200,6 +284,8 @@ impl<T: ComponentValue> ComponentUpdater for Replace<T> {
     type Updated = T;
 
     unsafe fn update(self, data: &mut CellData, slot: Slot, id: Entity, tick: u32) -> T {
+        data.record_previous(&[id], Slice::single(slot), tick);
+
         let storage = data.storage.downcast_mut::<T>();
         let old = mem::replace(&mut storage[slot], self.value);
 
@@ -262,9 +348,10 @@ impl<T: ComponentValue + PartialEq> ComponentUpdater for WriteDedup<T> {
 
     unsafe fn update(self, data: &mut CellData, slot: Slot, id: Entity, tick: u32) {
         let storage = data.storage.downcast_mut::<T>();
-        let current = &mut storage[slot];
-        if current != &self.value {
-            *current = self.value;
+        if storage[slot] != self.value {
+            data.record_previous(&[id], Slice::single(slot), tick);
+
+            data.storage.downcast_mut::<T>()[slot] = self.value;
 
             data.set_modified(&[id], Slice::single(slot), tick);
         }
@@ -303,6 +390,8 @@ impl ComponentUpdater for WriteDedupDyn {
                 return;
             }
 
+            data.record_previous(&[id], Slice::single(slot), tick);
+
             desc.drop(dst);
 
             ptr::copy_nonoverlapping(self.value, dst, desc.size());
@@ -331,6 +420,8 @@ impl ComponentUpdater for ReplaceDyn {
     type Updated = ();
 
     unsafe fn update(self, data: &mut CellData, slot: Slot, id: Entity, tick: u32) {
+        data.record_previous(&[id], Slice::single(slot), tick);
+
         let desc = data.storage.desc();
         unsafe {
             let dst = data.storage.at_mut(slot).unwrap();
@@ -403,6 +494,9 @@ unsafe impl<'b> EntityWriter for Buffered<'b> {
     ) -> (EntityLocation, ()) {
         let mut exclusive_relations = Vec::new();
 
+        // Written separately as they do not affect the archetype
+        let mut sparse = self.buffer.take_sparse();
+
         let arch = world.archetypes.get_mut(src_loc.arch_id);
         unsafe {
             self.buffer.retain(|desc, src| {
@@ -412,6 +506,8 @@ unsafe impl<'b> EntityWriter for Buffered<'b> {
                 if let Some(cell) = arch.cell_mut(key) {
                     let data = cell.data.get_mut();
 
+                    data.record_previous(&[id], Slice::single(src_loc.slot), tick);
+
                     let dst = data.storage.at_mut(src_loc.slot).unwrap();
                     desc.drop(dst);
                     ptr::copy_nonoverlapping(src, dst, desc.size());
@@ -436,7 +532,10 @@ unsafe impl<'b> EntityWriter for Buffered<'b> {
         }
 
         if self.buffer.is_empty() {
-            return (src_loc, ());
+            return (
+                write_sparse_buffer(world, id, src_loc, &mut sparse, tick),
+                (),
+            );
         }
 
         // Add the existing components, making sure new exclusive relations are favored
@@ -474,10 +573,30 @@ unsafe impl<'b> EntityWriter for Buffered<'b> {
         update_entity_loc(world, id, dst_loc, swapped);
         // world.archetypes.prune_arch(src_loc.arch_id);
 
-        (dst_loc, ())
+        (
+            write_sparse_buffer(world, id, dst_loc, &mut sparse, tick),
+            (),
+        )
     }
 }
 
+/// Writes the pairs of sparse relations taken from a buffer
+pub(crate) fn write_sparse_buffer(
+    world: &mut World,
+    id: Entity,
+    mut loc: EntityLocation,
+    buffer: &mut ComponentBuffer,
+    tick: u32,
+) -> EntityLocation {
+    for (desc, value) in buffer.drain() {
+        loc = SingleComponentWriter::new(desc, ReplaceDyn { value })
+            .write(world, id, loc, tick)
+            .0;
+    }
+
+    loc
+}
+
 fn find_archetype_components(
     current_components: impl IntoIterator<Item = ComponentDesc>,
     new_components: impl IntoIterator<Item = ComponentDesc>,
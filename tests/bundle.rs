@@ -0,0 +1,59 @@
+#[test]
+#[cfg(feature = "derive")]
+fn derive_bundle() {
+    use flax::{components::name, *};
+    use itertools::Itertools;
+
+    component! {
+        health: f32,
+        position: (f32, f32),
+    }
+
+    #[derive(Bundle, Debug, Clone, PartialEq)]
+    struct Player {
+        #[bundle(component = name())]
+        label: String,
+        health: f32,
+        #[bundle(component = position())]
+        pos: (f32, f32),
+    }
+
+    let player = |i: usize| Player {
+        label: format!("player.{i}"),
+        health: 100.0 - i as f32,
+        pos: (i as f32, 0.0),
+    };
+
+    let mut world = World::new();
+
+    let id = EntityBuilder::from(player(0)).spawn(&mut world);
+    assert_eq!(world.get(id, name()).unwrap().as_str(), "player.0");
+    assert_eq!(*world.get(id, position()).unwrap(), (0.0, 0.0));
+
+    let ids = BatchSpawn::from((1..4).map(player).collect_vec()).spawn(&mut world);
+
+    let mut cmd = CommandBuffer::new();
+    cmd.spawn(player(4));
+    cmd.apply(&mut world).unwrap();
+
+    let mut query = Query::new((health().copied(), position().copied()));
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .sorted_by_key(|v| v.1 .0 as i32)
+            .collect_vec(),
+        (0..5)
+            .map(|i| (100.0 - i as f32, (i as f32, 0.0)))
+            .collect_vec()
+    );
+
+    assert_eq!(world.remove_bundle::<Player>(ids[1]), Ok(player(2)));
+    assert!(!world.has(ids[1], health()));
+
+    // Nothing is removed when a component is missing
+    world.remove(ids[2], health()).unwrap();
+    assert!(world.remove_bundle::<Player>(ids[2]).is_err());
+    assert!(world.has(ids[2], name()));
+    assert!(world.has(ids[2], position()));
+}
@@ -275,3 +275,49 @@ fn added_opt_and() {
     assert_eq!(query.borrow(&world).iter().collect_vec(), [(&5, &2)]);
     assert_eq!(query.borrow(&world).iter().collect_vec(), []);
 }
+
+#[test]
+fn track_previous() {
+    component! {
+        health: f32 => [TrackPrevious],
+        tag: (),
+    }
+
+    let mut world = World::new();
+
+    let id = Entity::builder().set(health(), 100.0).spawn(&mut world);
+    let id2 = Entity::builder().set(health(), 50.0).spawn(&mut world);
+
+    let mut query = Query::new((
+        entity_ids(),
+        health().modified().copied(),
+        health().previous().map(|v| v.copied()),
+    ));
+
+    assert_eq!(
+        query.collect_vec(&world),
+        [(id, 100.0, None), (id2, 50.0, None)]
+    );
+
+    world.set(id, health(), 90.0).unwrap();
+    // Only the first mutation in the same tick is recorded
+    world.set(id, health(), 80.0).unwrap();
+
+    assert_eq!(query.collect_vec(&world), [(id, 80.0, Some(100.0))]);
+
+    *world.get_mut(id, health()).unwrap() -= 5.0;
+
+    // Moving the entity to another archetype retains the previous value
+    world.set(id, tag(), ()).unwrap();
+
+    assert_eq!(query.collect_vec(&world), [(id, 75.0, Some(80.0))]);
+
+    Query::new(health().as_mut())
+        .borrow(&world)
+        .for_each(|v| *v *= 2.0);
+
+    assert_eq!(
+        query.collect_vec(&world),
+        [(id2, 100.0, Some(50.0)), (id, 150.0, Some(75.0))]
+    );
+}
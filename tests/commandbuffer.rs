@@ -7,6 +7,9 @@ use itertools::Itertools;
 component! {
     shared: Arc<String>,
     health: f32,
+    attached_to(target): () => [ flax::metadata::Sparse, Exclusive ],
+    camera: () => [ flax::metadata::Unique ],
+    controller: () => [ flax::metadata::Unique<flax::metadata::unique::Steal> ],
 }
 
 #[test]
@@ -145,3 +148,231 @@ fn commandbuffer() {
 
     assert_eq!(soldiers.len(), 99);
 }
+
+#[test]
+fn apply_atomic() {
+    use flax::commands::{CommandError, CommandErrorKind};
+    use flax::error::MissingComponent;
+
+    let mut world = World::new();
+    let ids = world.spawn_many().take(4).collect_vec();
+
+    world.set(ids[0], health(), 50.0).unwrap();
+    world.despawn(ids[3]).unwrap();
+
+    let mut cmd = CommandBuffer::new();
+
+    cmd.set(ids[0], health(), 100.0)
+        .set(ids[3], health(), 100.0)
+        .despawn(ids[1])
+        .remove(ids[1], health())
+        .remove(ids[2], health())
+        .spawn_at(ids[0], EntityBuilder::new())
+        .spawn(EntityBuilder::new().set(name(), "Spawned".into()));
+
+    let errors = cmd.apply_atomic(&mut world).unwrap_err();
+
+    assert_eq!(
+        errors,
+        [
+            CommandError {
+                index: 1,
+                kind: CommandErrorKind::Error(Error::NoSuchEntity(ids[3])),
+            },
+            CommandError {
+                index: 3,
+                kind: CommandErrorKind::Error(Error::NoSuchEntity(ids[1])),
+            },
+            CommandError {
+                index: 4,
+                kind: CommandErrorKind::Error(Error::MissingComponent(MissingComponent {
+                    id: ids[2],
+                    desc: health().desc(),
+                })),
+            },
+            CommandError {
+                index: 5,
+                kind: CommandErrorKind::Error(Error::EntityOccupied(ids[0])),
+            },
+        ]
+    );
+
+    // Nothing was applied
+    assert_eq!(world.get(ids[0], health()).as_deref(), Ok(&50.0));
+    assert!(world.is_alive(ids[1]));
+    assert_eq!(Query::new(name()).borrow(&world).count(), 0);
+
+    cmd.set(ids[2], health(), 25.0)
+        .remove(ids[2], health())
+        .despawn(ids[1])
+        .spawn_at(ids[1], EntityBuilder::new().set(health(), 75.0))
+        .spawn(EntityBuilder::new().set(name(), "Spawned".into()))
+        .spawn_at(ids[3], EntityBuilder::new().set(health(), 10.0));
+
+    cmd.apply_atomic(&mut world).unwrap();
+
+    assert!(!world.has(ids[2], health()));
+    assert_eq!(world.get(ids[1], health()).as_deref(), Ok(&75.0));
+    assert_eq!(world.get(ids[3], health()).as_deref(), Ok(&10.0));
+    assert_eq!(Query::new(name()).borrow(&world).count(), 1);
+
+    cmd.defer(|_| Ok(()));
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 0,
+            kind: CommandErrorKind::Deferred,
+        }])
+    );
+}
+
+#[test]
+fn apply_atomic_metadata() {
+    use flax::commands::{CommandError, CommandErrorKind};
+    use flax::error::MissingComponent;
+
+    let mut world = World::new();
+    let [a, b, e] = [(); 3].map(|_| world.spawn());
+
+    world.set(e, child_of(a), ()).unwrap();
+    world.set(e, attached_to(a), ()).unwrap();
+    world.set(a, camera(), ()).unwrap();
+    world.set(a, controller(), ()).unwrap();
+
+    let mut cmd = CommandBuffer::new();
+
+    // Exclusive relations replace the previous pair, both dense and sparse
+    cmd.set(e, child_of(b), ())
+        .remove(e, child_of(a))
+        .set(e, attached_to(b), ())
+        .remove(e, attached_to(a));
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![
+            CommandError {
+                index: 1,
+                kind: CommandErrorKind::Error(Error::MissingComponent(MissingComponent {
+                    id: e,
+                    desc: child_of(a).desc(),
+                })),
+            },
+            CommandError {
+                index: 3,
+                kind: CommandErrorKind::Error(Error::MissingComponent(MissingComponent {
+                    id: e,
+                    desc: attached_to(a).desc(),
+                })),
+            },
+        ])
+    );
+
+    assert!(world.has(e, child_of(a)));
+    assert!(!world.has(e, child_of(b)));
+    assert!(world.has(e, attached_to(a)));
+    assert!(!world.has(e, attached_to(b)));
+
+    // Unique values are either rejected or stolen from the previous holder
+    cmd.set(b, camera(), ())
+        .set(b, controller(), ())
+        .remove(a, controller());
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![
+            CommandError {
+                index: 0,
+                kind: CommandErrorKind::Error(Error::NotUnique(camera().desc(), Some(a))),
+            },
+            CommandError {
+                index: 2,
+                kind: CommandErrorKind::Error(Error::MissingComponent(MissingComponent {
+                    id: a,
+                    desc: controller().desc(),
+                })),
+            },
+        ])
+    );
+
+    assert!(world.has(a, controller()));
+    assert!(!world.has(b, controller()));
+
+    // Subjects are despawned along with their target
+    cmd.despawn(a).set(e, health(), 1.0);
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 1,
+            kind: CommandErrorKind::Error(Error::NoSuchEntity(e)),
+        }])
+    );
+
+    assert!(world.is_alive(a));
+
+    // Commands which agree with the metadata are applied
+    cmd.set(e, child_of(b), ())
+        .set(e, attached_to(b), ())
+        .set(b, controller(), ())
+        .despawn(a)
+        .set(e, health(), 1.0);
+
+    cmd.apply_atomic(&mut world).unwrap();
+
+    assert!(!world.is_alive(a));
+    assert!(world.has(e, child_of(b)));
+    assert!(world.has(e, attached_to(b)));
+    assert!(world.has(b, controller()));
+    assert_eq!(world.get(e, health()).as_deref(), Ok(&1.0));
+}
+
+#[test]
+fn apply_atomic_prefix() {
+    use flax::commands::{CommandError, CommandErrorKind};
+    use flax::error::MissingComponent;
+
+    let mut world = World::new();
+    let [a, b] = [(); 2].map(|_| world.spawn());
+    let e = Entity::builder().set(child_of(a), ()).spawn(&mut world);
+
+    // The relation to `a` is replaced by the second command, which the last command relies on
+    let mut cmd = CommandBuffer::new();
+    cmd.set(e, health(), 5.0)
+        .set(e, child_of(b), ())
+        .remove(e, child_of(a));
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 2,
+            kind: CommandErrorKind::Error(Error::MissingComponent(MissingComponent {
+                id: e,
+                desc: child_of(a).desc(),
+            })),
+        }])
+    );
+
+    assert!(!world.has(e, health()));
+    assert!(world.has(e, child_of(a)));
+    assert!(!world.has(e, child_of(b)));
+
+    // Unique values of attached children are validated as well
+    world.set(a, camera(), ()).unwrap();
+
+    cmd.set(e, health(), 5.0).spawn(
+        Entity::builder()
+            .set(health(), 1.0)
+            .attach(child_of, Entity::builder().set(camera(), ())),
+    );
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 1,
+            kind: CommandErrorKind::Error(Error::NotUnique(camera().desc(), Some(a))),
+        }])
+    );
+
+    assert!(!world.has(e, health()));
+    assert_eq!(Query::new(health()).borrow(&world).count(), 0);
+}
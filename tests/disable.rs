@@ -0,0 +1,41 @@
+use flax::{components::disabled, *};
+use itertools::Itertools;
+
+component! {
+    health: i32,
+}
+
+#[test]
+fn disable() {
+    let mut world = World::new();
+
+    let ids = (0..4)
+        .map(|i| Entity::builder().set(health(), i).spawn(&mut world))
+        .collect_vec();
+
+    let mut query = Query::new((entity_ids(), health().copied()));
+    let mut all = Query::new(entity_ids()).include_disabled();
+
+    world.disable(ids[1]).unwrap();
+    world.disable(ids[3]).unwrap();
+
+    assert_eq!(query.collect_sorted_vec(&world), [(ids[0], 0), (ids[2], 2)]);
+    assert_eq!(all.collect_sorted_vec(&world), ids);
+
+    // Components are kept
+    assert_eq!(world.get(ids[1], health()).as_deref(), Ok(&1));
+    assert!(world.has(ids[1], disabled()));
+
+    world.enable(ids[1]).unwrap();
+    // Enabling an already enabled entity is not an error
+    world.enable(ids[1]).unwrap();
+
+    assert_eq!(
+        query.collect_sorted_vec(&world),
+        [(ids[0], 0), (ids[1], 1), (ids[2], 2)]
+    );
+
+    world.despawn(ids[3]).unwrap();
+    assert!(world.disable(ids[3]).is_err());
+    assert!(world.enable(ids[3]).is_err());
+}
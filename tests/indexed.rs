@@ -0,0 +1,102 @@
+use flax::{components::name, metadata::Indexed, *};
+use itertools::Itertools;
+
+component! {
+    team: u32 => [ Indexed ],
+    health: i32,
+    speed: f32,
+}
+
+#[test]
+fn find_by() {
+    let mut world = World::new();
+
+    let ids = (0..8)
+        .map(|i| {
+            Entity::builder()
+                .set(team(), i % 3)
+                .set(health(), i as i32)
+                .spawn(&mut world)
+        })
+        .collect_vec();
+
+    assert_eq!(world.find_by(team(), &0), [ids[0], ids[3], ids[6]]);
+    assert_eq!(world.find_by(team(), &3), []);
+
+    // Modified through `set`
+    world.set(ids[0], team(), 3).unwrap();
+    assert_eq!(world.find_by(team(), &0), [ids[3], ids[6]]);
+    assert_eq!(world.find_by(team(), &3), [ids[0]]);
+
+    // Modified through a query
+    Query::new(team().as_mut())
+        .borrow(&world)
+        .for_each(|v| *v += 1);
+
+    assert_eq!(world.find_by(team(), &4), [ids[0]]);
+    assert_eq!(world.find_by(team(), &1), [ids[3], ids[6]]);
+
+    // Moving between archetypes keeps the entity indexed
+    world.set(ids[3], name(), "moved".into()).unwrap();
+    assert_eq!(world.find_by(team(), &1), [ids[3], ids[6]]);
+
+    world.remove(ids[6], team()).unwrap();
+    world.despawn(ids[3]).unwrap();
+    assert_eq!(world.find_by(team(), &1), []);
+
+    // Disabled entities are still found
+    world.disable(ids[1]).unwrap();
+    assert_eq!(world.find_by(team(), &2), [ids[1], ids[4], ids[7]]);
+
+    // Components without an index are scanned
+    assert_eq!(world.find_by(health(), &5), [ids[5]]);
+    assert_eq!(world.find_by(name(), &"moved".into()), Vec::<Entity>::new());
+
+    world.set(ids[2], speed(), 0.5).unwrap();
+    assert_eq!(world.find_by(speed(), &0.5), [ids[2]]);
+}
+
+#[test]
+fn indexed_cmp() {
+    let mut world = World::new();
+
+    let ids = (0..8)
+        .map(|i| {
+            let mut builder = Entity::builder();
+            builder.set(team(), i % 4);
+            // Spread the entities over several archetypes
+            if i % 2 == 0 {
+                builder.set(health(), i as i32);
+            }
+
+            builder.spawn(&mut world)
+        })
+        .collect_vec();
+
+    let mut query = Query::new(entity_ids()).filter(team().eq(1));
+    assert_eq!(query.collect_sorted_vec(&world), [ids[1], ids[5]]);
+
+    // Values modified since the last lookup are still matched
+    *world.get_mut(ids[0], team()).unwrap() = 1;
+    assert_eq!(query.collect_sorted_vec(&world), [ids[0], ids[1], ids[5]]);
+
+    Query::new(team().as_mut())
+        .borrow(&world)
+        .for_each(|v| *v = 2);
+
+    assert_eq!(query.collect_sorted_vec(&world), []);
+
+    let mut query = Query::new(entity_ids()).filter(team().eq(2));
+    assert_eq!(query.collect_sorted_vec(&world), ids);
+}
+
+#[test]
+fn index_existing_values() {
+    let mut src = World::new();
+    let id = Entity::builder().set(team(), 5).spawn(&mut src);
+
+    let mut world = World::new();
+    let migrated = world.merge_with(&mut src);
+
+    assert_eq!(world.find_by(team(), &5), [migrated.get(id)]);
+}
@@ -0,0 +1,98 @@
+use flax::{components::is_a, *};
+use itertools::Itertools;
+
+component! {
+    health: i32,
+    armor: u32,
+    speed: u32,
+}
+
+#[test]
+fn inherited() {
+    let mut world = World::new();
+
+    let base = Entity::builder()
+        .set(health(), 100)
+        .set(armor(), 5)
+        .spawn(&mut world);
+
+    let heavy = Entity::builder()
+        .set(armor(), 20)
+        .set(is_a(base), ())
+        .spawn(&mut world);
+
+    let a = Entity::builder().set(is_a(base), ()).spawn(&mut world);
+    let b = Entity::builder()
+        .set(is_a(heavy), ())
+        .set(speed(), 2)
+        .spawn(&mut world);
+    let c = Entity::builder()
+        .set(is_a(base), ())
+        .set(health(), 50)
+        .spawn(&mut world);
+
+    let mut query =
+        Query::new((entity_ids(), (health(), armor()).inherited())).filter(is_a.with_relation());
+
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, (&health, &armor))| (id, (health, armor)))
+            .sorted()
+            .collect_vec(),
+        [
+            (heavy, (100, 20)),
+            (a, (100, 5)),
+            (b, (100, 20)),
+            (c, (50, 5))
+        ]
+    );
+
+    // Overriding the inherited value leaves the prototype untouched
+    world.set(a, armor(), 7).unwrap();
+    *world.get_mut(base, health()).unwrap() = 80;
+
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, (&health, &armor))| (id, (health, armor)))
+            .sorted()
+            .collect_vec(),
+        [(heavy, (80, 20)), (a, (80, 7)), (b, (80, 20)), (c, (50, 5))]
+    );
+
+    // Components missing from the whole chain are not matched
+    let mut query = Query::new((entity_ids(), speed().inherited().opt_or(1).copied()))
+        .filter(is_a.with_relation());
+
+    assert_eq!(
+        query.borrow(&world).iter().sorted().collect_vec(),
+        [(heavy, 1), (a, 1), (b, 2), (c, 1)]
+    );
+}
+
+#[test]
+fn inherited_cycle() {
+    let mut world = World::new();
+
+    let a = world.spawn();
+    let b = Entity::builder()
+        .set(is_a(a), ())
+        .set(armor(), 3)
+        .spawn(&mut world);
+    world.set(a, is_a(b), ()).unwrap();
+
+    let c = Entity::builder().set(is_a(a), ()).spawn(&mut world);
+
+    // The cycle is visited once without finding the component
+    let mut query = Query::new((entity_ids(), health().inherited()));
+    assert_eq!(query.borrow(&world).iter().count(), 0);
+
+    let mut query = Query::new((entity_ids(), armor().inherited().copied()));
+    assert_eq!(
+        query.borrow(&world).iter().sorted().collect_vec(),
+        [(a, 3), (b, 3), (c, 3)]
+    );
+}
@@ -94,7 +94,7 @@ fn merge_empty() -> anyhow::Result<()> {
     let bytes = bincode::serialize(&serializer.serialize(&world, SerializeFormat::ColumnMajor))?;
 
     // Clear the world
-    world.despawn_many(All);
+    world.despawn_many(All).unwrap();
 
     let mut new_world = deserializer.deserialize(&mut bincode::de::Deserializer::from_slice(
         &bytes,
@@ -294,3 +294,56 @@ fn merge_custom() {
 
     pretty_assertions::assert_eq!(custom_children, ["child_custom.1"]);
 }
+
+#[test]
+fn merge_map_entities() {
+    component! {
+        resources,
+        target: Entity => [ EntityMapper ],
+        targets: Vec<Entity> => [ EntityMapper ],
+    }
+
+    let mut src_world = World::new();
+
+    let a = Entity::builder()
+        .set(name(), "a".into())
+        .spawn(&mut src_world);
+    let b = Entity::builder()
+        .set(name(), "b".into())
+        .set(target(), a)
+        .set(targets(), vec![a])
+        .spawn(&mut src_world);
+
+    Entity::builder()
+        .set(name(), "c".into())
+        .set(targets(), vec![a, b])
+        .spawn(&mut src_world);
+
+    src_world.set(resources(), target(), b).unwrap();
+
+    let mut world = World::new();
+    for i in 0..10 {
+        Entity::builder()
+            .set(name(), format!("other.{i}"))
+            .spawn(&mut world);
+    }
+
+    let migrated = world.merge_with(&mut src_world);
+
+    let (a, b) = (migrated.get(a), migrated.get(b));
+    assert_eq!(world.get(a, name()).as_deref(), Ok(&"a".to_string()));
+    assert_eq!(world.get(b, name()).as_deref(), Ok(&"b".to_string()));
+
+    assert_eq!(world.get(b, target()).as_deref(), Ok(&a));
+    assert_eq!(
+        Query::new(targets().cloned())
+            .borrow(&world)
+            .iter()
+            .sorted()
+            .collect_vec(),
+        [vec![a], vec![a, b]]
+    );
+
+    // Static entities are migrated as well
+    assert_eq!(world.get(resources(), target()).as_deref(), Ok(&b));
+}
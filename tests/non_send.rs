@@ -0,0 +1,112 @@
+#[test]
+#[cfg(feature = "std")]
+fn non_send_resources() {
+    use flax::{
+        schedule::ThreadScopeExecutor,
+        system::{AccessKind, MainThread, NonSend, NonSendMut},
+        *,
+    };
+    use std::{
+        cell::Cell,
+        rc::Rc,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// Emulates a handle which can not leave its thread
+    struct Window {
+        frames: Rc<Cell<u32>>,
+    }
+
+    let frames = Rc::new(Cell::new(0));
+
+    let mut world = World::new();
+    assert!(world
+        .insert_non_send(Window {
+            frames: frames.clone(),
+        })
+        .is_none());
+
+    let threads = Arc::new(Mutex::new(Vec::new()));
+
+    let render = System::builder()
+        .with_name("render")
+        .with_non_send_mut::<Window>()
+        .build(|window: NonSendMut<Window>| {
+            window.frames.set(window.frames.get() + 1);
+        })
+        .boxed();
+
+    let read = {
+        let threads = threads.clone();
+        (move |window: NonSend<Window>| {
+            assert!(window.frames.get() > 0);
+            threads.lock().unwrap().push(thread::current().id());
+        })
+        .boxed()
+    };
+
+    let audio = {
+        let threads = threads.clone();
+        System::builder()
+            .with_name("audio")
+            .with_main_thread()
+            .build(move |_: MainThread| {
+                threads.lock().unwrap().push(thread::current().id());
+            })
+            .boxed()
+    };
+
+    let mut accesses = Vec::new();
+    read.access(&world, &mut accesses);
+    assert!(accesses
+        .iter()
+        .any(|v| matches!(v.kind, AccessKind::NonSend(_)) && !v.mutable));
+
+    let mut schedule = Schedule::builder()
+        .with_system(render)
+        .with_system(read)
+        .with_system(audio)
+        .with_executor(ThreadScopeExecutor)
+        .build();
+
+    for _ in 0..3 {
+        schedule.execute_par(&mut world).unwrap();
+    }
+
+    assert_eq!(frames.get(), 3);
+    assert_eq!(world.non_send::<Window>().unwrap().frames.get(), 3);
+
+    let main_thread = thread::current().id();
+    let threads = threads.lock().unwrap();
+    assert_eq!(threads.len(), 6);
+    assert!(threads.iter().all(|&v| v == main_thread));
+
+    // Non-send resources can not be accessed from other threads
+    thread::scope(|s| {
+        assert!(s
+            .spawn(|| world.non_send::<Window>().is_some())
+            .join()
+            .is_err());
+    });
+
+    let window = world.remove_non_send::<Window>().unwrap();
+    assert_eq!(window.frames.get(), 3);
+    assert!(world.non_send::<Window>().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn non_send_drop_other_thread() {
+    use flax::World;
+    use std::{rc::Rc, thread};
+
+    let value = Rc::new(5);
+
+    let mut world = World::new();
+    world.insert_non_send(value.clone());
+
+    // The value is leaked rather than dropped on the wrong thread
+    thread::spawn(move || drop(world)).join().unwrap();
+    assert_eq!(Rc::strong_count(&value), 2);
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "registry")]
+
+use flax::{metadata::Registered, registry::ComponentRegistry, *};
+
+component! {
+    health: f32 => [ Registered, Debuggable ],
+    mana: f32 => [ Registered ],
+    unlisted: f32,
+}
+
+#[test]
+fn registry() {
+    // Not touched before being collected
+    let registry = ComponentRegistry::new();
+
+    assert!(registry.iter().any(|v| v.key() == health().key()));
+    assert_eq!(registry.get("mana").map(|v| v.key()), Some(mana().key()));
+    assert!(registry.iter().all(|v| v.key() != unlisted().key()));
+    assert!(registry.get("unlisted").is_none());
+
+    let health = registry.get("health").unwrap();
+    assert!(health.metadata().has(metadata::debuggable()));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serialize_registry() {
+    use flax::serialize::{SerdeBuilder, Serializable, SerializeFormat};
+
+    component! {
+        position: (f32, f32) => [ Registered, Serializable ],
+        velocity: (f32, f32) => [ Registered ],
+    }
+
+    let mut world = World::new();
+
+    let id = Entity::builder()
+        .set(position(), (1.0, 2.0))
+        .set(velocity(), (0.0, 1.0))
+        .spawn(&mut world);
+
+    let (ser, de) = SerdeBuilder::new()
+        .with_registry(ComponentRegistry::global())
+        .unwrap()
+        .build();
+
+    let json = serde_json::to_string(&ser.serialize(&world, SerializeFormat::RowMajor)).unwrap();
+
+    let new_world = de
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+
+    assert_eq!(*new_world.get(id, position()).unwrap(), (1.0, 2.0));
+    assert!(!new_world.has(id, velocity()));
+}
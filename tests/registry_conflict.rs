@@ -0,0 +1,38 @@
+#![cfg(all(feature = "registry", feature = "serde"))]
+
+use flax::{
+    metadata::Registered,
+    registry::ComponentRegistry,
+    serialize::{SerdeBuilder, Serializable},
+    *,
+};
+
+mod a {
+    use super::*;
+
+    component! {
+        pub position: (f32, f32) => [ Registered, Serializable ],
+    }
+}
+
+mod b {
+    use super::*;
+
+    component! {
+        pub position: [f32; 3] => [ Registered, Serializable ],
+    }
+}
+
+#[test]
+fn registry_name_conflict() {
+    let result = SerdeBuilder::new()
+        .with_registry(ComponentRegistry::global())
+        .map(|_| ());
+
+    let expected = [
+        Error::NameConflict(a::position().desc(), b::position().desc()),
+        Error::NameConflict(b::position().desc(), a::position().desc()),
+    ];
+
+    assert!(expected.contains(&result.unwrap_err()));
+}
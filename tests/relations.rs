@@ -56,17 +56,17 @@ fn relations() {
     assert!(!world.has(child1, child_of(parent)));
     world.despawn(parent2).unwrap();
 
-    assert!(!world.has(child1, child_of(parent2)));
+    // Children are despawned along with the parent
+    assert!(!world.is_alive(child1));
+    assert!(world.is_alive(child2));
 
     world.despawn_recursive(parent, child_of).unwrap();
 
-    // Moved and detached
-    assert!(world.is_alive(child1));
     assert!(!world.is_alive(child2));
 
     tracing::info!("World: {world:#?}");
 
-    world.despawn_many(All);
+    world.despawn_many(All).unwrap();
 
     assert_eq!(
         Query::new(()).borrow(&world).count(),
@@ -330,6 +330,68 @@ fn relation_target_search() {
     assert_eq!(query.get(), Some((id1, &())));
 }
 
+#[test]
+fn wildcard_targets() {
+    component! {
+        likes(target): i32,
+        health: i32,
+    }
+
+    let mut world = World::new();
+    let a = Entity::builder().set(health(), 1).spawn(&mut world);
+    let b = Entity::builder().set(health(), 2).spawn(&mut world);
+
+    let c = Entity::builder()
+        .set(likes(a), 3)
+        .set(likes(b), 4)
+        .spawn(&mut world);
+
+    let d = Entity::builder()
+        .set(health(), 3)
+        .set(likes(a), 5)
+        .spawn(&mut world);
+
+    let mut query = Query::new((entity_ids(), likes.any_target()));
+    let pairs = query
+        .borrow(&world)
+        .iter()
+        .flat_map(|(subject, targets)| {
+            targets.map(move |(target, &value)| (subject, target, value))
+        })
+        .sorted()
+        .collect_vec();
+
+    assert_eq!(pairs, [(c, a, 3), (c, b, 4), (d, a, 5)]);
+
+    let mut query = Query::new((entity_ids(), likes.targeting(a)));
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, (target, &value))| (id, target, value))
+            .sorted()
+            .collect_vec(),
+        [(c, a, 3), (d, a, 5)]
+    );
+
+    // Usable as filters
+    let mut query = Query::new(entity_ids()).filter(likes.any_target());
+    assert_eq!(query.collect_sorted_vec(&world), [c, d]);
+
+    let mut query = Query::new(entity_ids())
+        .with(health())
+        .filter(likes.any_target());
+    assert_eq!(query.collect_sorted_vec(&world), [d]);
+
+    let mut query = Query::new(entity_ids())
+        .with(health())
+        .filter(likes.targeting(a));
+    assert_eq!(query.collect_sorted_vec(&world), [d]);
+
+    let mut query = Query::new(entity_ids()).filter(likes.targeting(b));
+    assert_eq!(query.collect_vec(&world), [c]);
+}
+
 #[test]
 fn exclusive() {
     component! {
@@ -426,3 +488,140 @@ fn relations_mut() {
         ]
     );
 }
+
+#[test]
+fn on_target_despawn() {
+    use flax::{
+        commands::{CommandError, CommandErrorKind},
+        metadata::{despawn, OnTargetDespawn},
+    };
+
+    component! {
+        owned_by(owner): () => [ OnTargetDespawn<despawn::DespawnSubject> ],
+        guarded_by(guard): () => [ OnTargetDespawn<despawn::Error> ],
+        likes(other): (),
+        sentry: (),
+    }
+
+    let mut world = World::new();
+
+    let owner = world.spawn();
+    let item = Entity::builder().set(owned_by(owner), ()).spawn(&mut world);
+    let part = Entity::builder()
+        .set(owned_by(item), ())
+        .set(likes(owner), ())
+        .spawn(&mut world);
+    let fan = Entity::builder().set(likes(item), ()).spawn(&mut world);
+
+    world.despawn(owner).unwrap();
+
+    assert!(!world.is_alive(item));
+    assert!(!world.is_alive(part));
+    // Detached
+    assert!(world.is_alive(fan));
+    assert!(!world.has(fan, likes(item)));
+
+    let guard = world.spawn();
+    let guarded = Entity::builder()
+        .set(guarded_by(guard), ())
+        .spawn(&mut world);
+
+    assert_eq!(
+        world.despawn(guard),
+        Err(Error::TargetInUse(guard, guarded_by(guard).desc()))
+    );
+    assert!(world.is_alive(guard));
+
+    world.remove(guarded, guarded_by(guard)).unwrap();
+    world.despawn(guard).unwrap();
+
+    // Applied through the commandbuffer as well
+    let parent = world.spawn();
+    let child = Entity::builder()
+        .set(child_of(parent), ())
+        .spawn(&mut world);
+
+    let mut cmd = CommandBuffer::new();
+    cmd.despawn(parent);
+    cmd.apply(&mut world).unwrap();
+
+    assert!(!world.is_alive(child));
+
+    // Relations set by earlier commands are despawned with their target when validating
+    let parent = world.spawn();
+    let child = world.spawn();
+
+    cmd.set(child, child_of(parent), ())
+        .despawn(parent)
+        .set(child, name(), "child".into());
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 2,
+            kind: CommandErrorKind::Error(Error::NoSuchEntity(child)),
+        }])
+    );
+
+    assert!(world.is_alive(parent));
+    assert!(!world.has(child, child_of(parent)));
+
+    let guard = world.spawn();
+    cmd.set(child, guarded_by(guard), ()).despawn(guard);
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 1,
+            kind: CommandErrorKind::Error(Error::TargetInUse(guard, guarded_by(guard).desc())),
+        }])
+    );
+
+    // Relations removed by earlier commands no longer prevent despawning
+    world.set(child, guarded_by(guard), ()).unwrap();
+
+    cmd.remove(child, guarded_by(guard)).despawn(guard);
+    cmd.apply_atomic(&mut world).unwrap();
+
+    assert!(!world.is_alive(guard));
+    assert!(world.is_alive(child));
+
+    // Entities spawned by earlier commands hold relations as well, including attached children
+    let guard = world.spawn();
+
+    cmd.spawn(Entity::builder().set(guarded_by(guard), ()))
+        .spawn(Entity::builder().attach(owned_by, Entity::builder().set(guarded_by(guard), ())))
+        .despawn(guard);
+
+    assert_eq!(
+        cmd.apply_atomic(&mut world),
+        Err(vec![CommandError {
+            index: 2,
+            kind: CommandErrorKind::Error(Error::TargetInUse(guard, guarded_by(guard).desc())),
+        }])
+    );
+
+    assert!(world.is_alive(guard));
+    assert_eq!(Query::new(guarded_by(guard)).borrow(&world).count(), 0);
+
+    // Refused entities are retried once their subjects are despawned
+    let guarded = Entity::builder()
+        .set(guarded_by(guard), ())
+        .spawn(&mut world);
+    world.despawn_many(All).unwrap();
+
+    assert!(!world.is_alive(guard));
+    assert!(!world.is_alive(guarded));
+
+    let guard = Entity::builder().tag(sentry()).spawn(&mut world);
+    let guarded = Entity::builder()
+        .set(guarded_by(guard), ())
+        .spawn(&mut world);
+
+    assert_eq!(
+        world.despawn_many(sentry().with()),
+        Err(Error::TargetInUse(guard, guarded_by(guard).desc()))
+    );
+    assert!(world.is_alive(guard));
+    assert!(world.is_alive(guarded));
+}
@@ -1,6 +1,6 @@
 use flax::{
-    component, components::name, BoxedSystem, CommandBuffer, Entity, EntityBuilder, FetchExt,
-    Query, QueryBorrow, Schedule, System, World,
+    commands::ParCmd, component, components::name, entity_ids, BoxedSystem, CommandBuffer, Entity,
+    EntityBuilder, FetchExt, Query, QueryBorrow, Schedule, System, World,
 };
 use itertools::Itertools;
 
@@ -176,6 +176,48 @@ fn command_flushing_par() {
     }
 }
 
+#[test]
+#[cfg(feature = "rayon")]
+fn command_par_for_each() {
+    component! {
+        index: usize,
+        spawned_from: usize,
+    }
+
+    let mut world = World::new();
+    let ids = (0..256)
+        .map(|i| Entity::builder().set(index(), i).spawn(&mut world))
+        .collect_vec();
+
+    let mut schedule = Schedule::builder()
+        .with_system(
+            System::builder()
+                .with_query(Query::new((entity_ids(), index().copied())))
+                .with_par_cmd()
+                .build(|mut q: QueryBorrow<_>, cmd: &ParCmd| {
+                    q.par_for_each(|(id, i)| {
+                        cmd.record(id, |cmd| {
+                            cmd.spawn(Entity::builder().set(spawned_from(), i));
+                        });
+                    })
+                }),
+        )
+        .flush()
+        .build();
+
+    schedule.execute_par(&mut world).unwrap();
+
+    // Entities are spawned in the order of the key regardless of which thread recorded them
+    let spawned = Query::new((entity_ids(), spawned_from().copied()))
+        .borrow(&world)
+        .iter()
+        .sorted_by_key(|&(id, _)| id)
+        .map(|(_, i)| i)
+        .collect_vec();
+
+    assert_eq!(spawned, (0..ids.len()).collect_vec());
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn schedule_seq() {
@@ -212,7 +254,7 @@ fn schedule_seq() {
     schedule.execute_seq(&mut world).unwrap();
 
     world.despawn(id).unwrap();
-    let result: anyhow::Result<()> = schedule.execute_seq(&mut world).map_err(Into::into);
+    let result = schedule.execute_seq(&mut world);
 
     assert!(result.is_err());
 }
@@ -486,3 +528,509 @@ fn into_anyhow(v: flax::Error) -> anyhow::Error {
     #[cfg(feature = "std")]
     return anyhow::Error::new(v);
 }
+
+#[test]
+fn schedule_graph_export() {
+    component! {
+        position: f32,
+        velocity: f32,
+        health: f32,
+    }
+
+    let mut world = World::new();
+
+    Entity::builder()
+        .set(position(), 0.0)
+        .set(velocity(), 1.0)
+        .spawn(&mut world);
+
+    Entity::builder().set(health(), 100.0).spawn(&mut world);
+
+    let mut schedule = Schedule::builder()
+        .with_system(
+            System::builder()
+                .with_name("integrate")
+                .with_query(Query::new((position().as_mut(), velocity())))
+                .for_each(|(pos, vel)| *pos += *vel),
+        )
+        .with_system(
+            System::builder()
+                .with_name("regen")
+                .with_query(Query::new(health().as_mut()))
+                .for_each(|health| *health += 1.0),
+        )
+        .with_system(
+            System::builder()
+                .with_name("print_position")
+                .with_query(Query::new(position()))
+                .for_each(|pos| {
+                    let _ = pos;
+                }),
+        )
+        .build();
+
+    let batches = schedule.batch_info(&world);
+    assert_eq!(
+        batches.to_names(),
+        [vec!["integrate", "regen"], vec!["print_position"]]
+    );
+
+    let deps = batches.dependencies();
+    assert_eq!(deps.len(), 1);
+    assert_eq!((deps[0].src(), deps[0].dst()), (0, 2));
+    assert_eq!(
+        deps[0].reasons(),
+        ["position (write -> read) in archetype [position, velocity]"]
+    );
+
+    let dot = batches.to_dot();
+    assert!(dot.starts_with("digraph schedule {"));
+    assert!(dot.contains("subgraph cluster_1 {"));
+    assert!(dot.contains("s2 [label=\"print_position\"];"));
+    assert!(dot.contains(
+        "s0 -> s2 [label=\"position (write -> read) in archetype [position, velocity]\"];"
+    ));
+
+    let mermaid = batches.to_mermaid();
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("subgraph batch_0 [\"batch 0\"]"));
+    assert!(mermaid
+        .contains("s0 -->|\"position (write -> read) in archetype [position, velocity]\"| s2"));
+}
+
+#[test]
+fn schedule_ambiguities() {
+    component! {
+        position: f32,
+        velocity: f32,
+    }
+
+    let mut world = World::new();
+
+    Entity::builder()
+        .set(position(), 0.0)
+        .set(velocity(), 1.0)
+        .spawn(&mut world);
+
+    let integrate = || {
+        System::builder()
+            .with_name("integrate")
+            .with_query(Query::new((position().as_mut(), velocity())))
+            .for_each(|(pos, vel)| *pos += *vel)
+            .boxed()
+    };
+
+    let clamp = || {
+        System::builder()
+            .with_name("clamp")
+            .with_query(Query::new(position().as_mut()))
+            .for_each(|pos| *pos = pos.clamp(-10.0, 10.0))
+            .boxed()
+    };
+
+    let print = || {
+        System::builder()
+            .with_name("print")
+            .with_query(Query::new(position()))
+            .for_each(|pos| {
+                let _ = pos;
+            })
+            .boxed()
+    };
+
+    let schedule = Schedule::builder()
+        .with_system(integrate())
+        .with_system(print())
+        .with_system(clamp())
+        .build();
+
+    // Reads are ordered against writes as well
+    let report = schedule.check_ambiguities(&world);
+    assert_eq!(
+        report
+            .ambiguities()
+            .iter()
+            .map(|v| (v.first(), v.second(), v.reasons()))
+            .collect_vec(),
+        [
+            (
+                "integrate",
+                "print",
+                &["position (write -> read) in archetype [position, velocity]".to_string()][..]
+            ),
+            (
+                "integrate",
+                "clamp",
+                &["position (write -> write) in archetype [position, velocity]".to_string()][..]
+            ),
+            (
+                "print",
+                "clamp",
+                &[
+                    "position (read -> write) in archetype [position]".to_string(),
+                    "position (read -> write) in archetype [position, velocity]".to_string(),
+                ][..]
+            ),
+        ]
+    );
+
+    let report = schedule.check_write_ambiguities(&world);
+    assert_eq!(
+        report
+            .ambiguities()
+            .iter()
+            .map(|v| (v.first(), v.second()))
+            .collect_vec(),
+        [("integrate", "clamp")]
+    );
+
+    let result = std::panic::catch_unwind(|| report.assert_empty());
+    assert!(result.is_err());
+
+    let schedule = Schedule::builder()
+        .with_system(integrate())
+        .with_system(print())
+        .with_system(clamp())
+        .allow_ambiguity("clamp", "integrate")
+        .build();
+
+    schedule.check_write_ambiguities(&world).assert_empty();
+
+    let schedule = Schedule::builder()
+        .with_system(integrate())
+        .with_system(print())
+        .with_system(clamp())
+        .allow_ambiguity("clamp", "integrate")
+        .allow_ambiguity("integrate", "print")
+        .allow_ambiguity("print", "clamp")
+        .build();
+
+    schedule.check_ambiguities(&world).assert_empty();
+
+    // Writes to the world are ambiguous as well
+    let spawn = |name: &'static str| {
+        System::builder()
+            .with_name(name)
+            .with_world_mut()
+            .build(|world: &mut World| {
+                world.spawn();
+            })
+            .boxed()
+    };
+
+    let schedule = Schedule::builder()
+        .with_system(spawn("spawn_a"))
+        .with_system(spawn("spawn_b"))
+        .build();
+
+    let report = schedule.check_ambiguities(&world);
+    assert_eq!(
+        report
+            .ambiguities()
+            .iter()
+            .map(|v| (v.first(), v.second(), v.reasons()))
+            .collect_vec(),
+        [(
+            "spawn_a",
+            "spawn_b",
+            &["world (write -> write)".to_string()][..]
+        )]
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn schedule_stats() {
+    component! {
+        counter: u32,
+    }
+
+    let mut world = World::new();
+
+    let id = Entity::builder().set(counter(), 0).spawn(&mut world);
+
+    let increment = System::builder()
+        .with_name("increment")
+        .with_query(Query::new(counter().as_mut()))
+        .for_each(|v| *v += 1);
+
+    let read = System::builder()
+        .with_name("read")
+        .with_query(Query::new(counter()))
+        .for_each(|v| {
+            let _ = v;
+        });
+
+    let schedule = Schedule::new().with_system(increment).with_system(read);
+    assert!(schedule.stats().is_none());
+
+    let mut schedule = schedule.with_stats();
+
+    for _ in 0..3 {
+        schedule.execute_seq(&mut world).unwrap();
+    }
+
+    let stats = schedule.stats().unwrap();
+    assert_eq!(
+        stats
+            .systems()
+            .map(|(name, v)| (name, v.count()))
+            .collect_vec(),
+        [("increment", 3), ("read", 3)]
+    );
+    assert_eq!(stats.system("read").unwrap().count(), 3);
+    assert!(stats.system("missing").is_none());
+    // Sequential execution does not batch the systems
+    assert!(stats.batches().is_empty());
+
+    let table = stats.to_string();
+    assert!(table.contains("system"));
+    assert!(table.contains("increment"));
+
+    schedule.reset_stats();
+    assert_eq!(schedule.stats().unwrap().systems().count(), 0);
+    assert_eq!(*world.get(id, counter()).unwrap(), 3);
+
+    for _ in 0..3 {
+        schedule.execute_par(&mut world).unwrap();
+    }
+
+    let stats = schedule.stats().unwrap();
+    assert_eq!(stats.system("increment").unwrap().count(), 3);
+    assert_eq!(
+        stats.batches().iter().map(|v| v.count()).collect_vec(),
+        [3, 3]
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn schedule_executor() {
+    use flax::schedule::{Job, ScheduleExecutor, SequentialExecutor, ThreadScopeExecutor};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, ThreadId},
+    };
+
+    component! {
+        a: i32,
+        b: i32,
+        c: i32,
+    }
+
+    /// Counts the dispatched batches
+    struct CountingExecutor(Arc<AtomicUsize>);
+
+    impl ScheduleExecutor for CountingExecutor {
+        fn execute_batch(&self, jobs: &mut [Job<'_>]) -> anyhow::Result<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            SequentialExecutor.execute_batch(jobs)
+        }
+    }
+
+    let mut world = World::new();
+
+    Entity::builder()
+        .set(a(), 1)
+        .set(b(), 2)
+        .set(c(), 3)
+        .spawn(&mut world);
+
+    let threads = Arc::new(Mutex::new(Vec::new()));
+
+    let schedule = || {
+        let system = |name: &'static str, component: flax::Component<i32>| {
+            let threads = threads.clone();
+            System::builder()
+                .with_name(name)
+                .with_query(Query::new(component.as_mut()))
+                .for_each(move |v| {
+                    *v += 1;
+                    threads.lock().unwrap().push((name, thread::current().id()));
+                })
+                .boxed()
+        };
+
+        Schedule::builder()
+            .with_system(system("a", a()))
+            .with_system(system("b", b()))
+            .with_system(system("c", c()))
+            .pin_to_main_thread("b")
+            .build()
+    };
+
+    let main_thread = thread::current().id();
+    let assert_pinned = |threads: &Mutex<Vec<(&str, ThreadId)>>| {
+        let mut threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 3);
+        assert!(threads
+            .iter()
+            .filter(|v| v.0 == "b")
+            .all(|v| v.1 == main_thread));
+        threads.clear();
+    };
+
+    let batches = Arc::new(AtomicUsize::new(0));
+    let mut counting = schedule().with_executor(CountingExecutor(batches.clone()));
+    counting.execute_par(&mut world).unwrap();
+    assert_eq!(batches.load(Ordering::Relaxed), 1);
+    assert_pinned(&threads);
+
+    let mut scoped = schedule().with_executor(ThreadScopeExecutor);
+    scoped.execute_par(&mut world).unwrap();
+    assert_pinned(&threads);
+
+    #[cfg(feature = "rayon")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+
+        let mut pooled =
+            schedule().with_executor(flax::schedule::RayonExecutor::with_pool(Arc::new(pool)));
+        pooled.execute_par(&mut world).unwrap();
+        assert_pinned(&threads);
+    }
+
+    let runs = if cfg!(feature = "rayon") { 3 } else { 2 };
+
+    assert_eq!(
+        Query::new((a(), b(), c()))
+            .borrow(&world)
+            .iter()
+            .map(|(&a, &b, &c)| (a, b, c))
+            .collect_vec(),
+        [(1 + runs, 2 + runs, 3 + runs)]
+    );
+
+    let mut failing = Schedule::builder()
+        .with_system(
+            System::builder()
+                .with_name("fail")
+                .build(|| -> anyhow::Result<()> { anyhow::bail!("failure") }),
+        )
+        .with_executor(ThreadScopeExecutor)
+        .build();
+
+    assert!(failing.execute_par(&mut world).is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn schedule_error_policies() {
+    use flax::schedule::ErrorPolicy;
+
+    component! {
+        health: i32,
+        ai: i32,
+    }
+
+    let mut world = World::new();
+    let id = Entity::builder()
+        .set(health(), 0)
+        .set(ai(), 0)
+        .spawn(&mut world);
+
+    let physics = || {
+        System::builder()
+            .with_name("physics")
+            .with_query(Query::new(health().as_mut()))
+            .for_each(|v| *v += 1)
+            .boxed()
+    };
+
+    let ai_system = |name: &'static str| {
+        System::builder()
+            .with_name(name)
+            .with_query(Query::new(ai().as_mut()))
+            .build(|mut query: QueryBorrow<flax::fetch::Mutable<i32>>| {
+                for v in &mut query {
+                    *v += 1;
+                }
+
+                anyhow::bail!("ai failed")
+            })
+            .boxed()
+    };
+
+    // The default policy aborts the schedule
+    let mut schedule = Schedule::builder()
+        .with_system(ai_system("ai"))
+        .with_system(physics())
+        .build();
+
+    assert!(schedule.execute_seq(&mut world).is_err());
+    assert_eq!(*world.get(id, health()).unwrap(), 0);
+
+    let mut schedule = Schedule::builder()
+        .with_system(ai_system("ai"))
+        .with_system(ai_system("retry"))
+        .with_system(physics())
+        .with_error_policy(ErrorPolicy::Log)
+        .with_system_error_policy("ai", ErrorPolicy::Disable { after: 2 })
+        .with_system_error_policy("retry", ErrorPolicy::Retry { max_delay: 4 })
+        .build();
+
+    let report = schedule.execute_seq(&mut world).unwrap();
+    assert_eq!(
+        report
+            .failures()
+            .iter()
+            .map(|v| (v.name(), v.disabled()))
+            .collect_vec(),
+        [("ai", false), ("retry", false)]
+    );
+    assert!(report.failures()[0].error().to_string().contains("ai"));
+
+    #[cfg(feature = "rayon")]
+    let report = schedule.execute_par(&mut world).unwrap();
+    #[cfg(not(feature = "rayon"))]
+    let report = schedule.execute_seq(&mut world).unwrap();
+
+    assert_eq!(
+        report
+            .failures()
+            .iter()
+            .map(|v| (v.name(), v.disabled()))
+            .collect_vec(),
+        [("ai", true), ("retry", false)]
+    );
+    assert_eq!(schedule.disabled_systems().collect_vec(), ["ai"]);
+
+    // `retry` backs off after failing twice in a row
+    let report = schedule.execute_seq(&mut world).unwrap();
+    assert!(report.is_ok());
+
+    // 1 for `ai` in the aborted schedule, 2 before `ai` was disabled, 2 for `retry`
+    assert_eq!(*world.get(id, ai()).unwrap(), 5);
+    assert_eq!(*world.get(id, health()).unwrap(), 3);
+
+    assert!(schedule.enable_system("ai"));
+    assert!(!schedule.enable_system("ai"));
+    assert_eq!(
+        schedule.execute_seq(&mut world).unwrap().failures().len(),
+        2
+    );
+
+    // Failures of other systems are attached to the error of an aborting system
+    let mut schedule = Schedule::builder()
+        .with_system(ai_system("log"))
+        .with_system(ai_system("abort"))
+        .with_error_policy(ErrorPolicy::Log)
+        .with_system_error_policy("abort", ErrorPolicy::Abort)
+        .build();
+
+    #[cfg(feature = "rayon")]
+    let err = schedule.execute_par(&mut world).unwrap_err();
+    #[cfg(not(feature = "rayon"))]
+    let err = schedule.execute_seq(&mut world).unwrap_err();
+
+    assert_eq!(err.root_cause().to_string(), "ai failed");
+    assert!(err
+        .to_string()
+        .starts_with("Aborted after 1 systems failed:\n  log: "));
+}
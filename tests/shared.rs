@@ -0,0 +1,150 @@
+use flax::{metadata::Shared, *};
+use itertools::Itertools;
+
+component! {
+    material: &'static str => [ Shared ],
+    mesh: u32 => [ Shared ],
+    health: i32,
+}
+
+#[test]
+fn shared_values() {
+    let mut world = World::new();
+
+    let ids = (0..8)
+        .map(|i| {
+            let id = Entity::builder().set(health(), i).spawn(&mut world);
+            world
+                .set_shared(id, material(), if i % 2 == 0 { "stone" } else { "wood" })
+                .unwrap();
+            id
+        })
+        .collect_vec();
+
+    assert_eq!(*world.get_shared(ids[0], material()).unwrap(), "stone");
+    assert_eq!(*world.get_shared(ids[1], material()).unwrap(), "wood");
+
+    // Shared values are not stored per entity
+    assert!(!world.has(ids[0], material()));
+    assert_eq!(Query::new(material()).borrow(&world).count(), 0);
+
+    let mut query = Query::new((entity_ids(), health(), material().shared()));
+
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, &health, &material)| (id, health, material))
+            .sorted()
+            .collect_vec(),
+        ids.iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i as i32, if i % 2 == 0 { "stone" } else { "wood" }))
+            .collect_vec()
+    );
+
+    // Entities with equal values are grouped together
+    let mut batches = Query::new(material().shared());
+    let mut borrow = batches.borrow(&world);
+    assert_eq!(
+        borrow
+            .iter_batched()
+            .map(|v| v.collect_vec())
+            .sorted()
+            .collect_vec(),
+        [vec![&"stone"; 4], vec![&"wood"; 4]]
+    );
+    drop(borrow);
+
+    // Moves the entity to the archetype of the new value
+    world.set_shared(ids[0], material(), "wood").unwrap();
+    world.set_shared(ids[2], material(), "glass").unwrap();
+
+    assert_eq!(*world.get_shared(ids[0], material()).unwrap(), "wood");
+    assert_eq!(
+        batches
+            .borrow(&world)
+            .iter_batched()
+            .map(|v| v.count())
+            .sorted()
+            .collect_vec(),
+        [1, 2, 5]
+    );
+
+    world.remove_shared(ids[1], material()).unwrap();
+    assert!(world.get_shared(ids[1], material()).is_err());
+    assert!(world.remove_shared(ids[1], material()).is_err());
+    assert_eq!(*world.get(ids[1], health()).unwrap(), 1);
+
+    assert_eq!(batches.borrow(&world).count(), 7);
+
+    // Values held by the entity itself are fetched as well
+    world.set(ids[1], material(), "paper").unwrap();
+    assert_eq!(
+        batches
+            .borrow(&world)
+            .iter()
+            .copied()
+            .sorted()
+            .collect_vec(),
+        ["glass", "paper", "stone", "stone", "wood", "wood", "wood", "wood"]
+    );
+
+    // ... and replaced by a shared value
+    world.set_shared(ids[1], material(), "stone").unwrap();
+    assert!(!world.has(ids[1], material()));
+    assert_eq!(*world.get_shared(ids[1], material()).unwrap(), "stone");
+}
+
+#[test]
+fn shared_value_lifetime() {
+    let mut world = World::new();
+
+    // The values are stored on component entities
+    let values = |world: &World| {
+        Query::new(material())
+            .with_components()
+            .borrow(world)
+            .iter()
+            .copied()
+            .sorted()
+            .collect_vec()
+    };
+
+    let ids = world.spawn_many().take(4).collect_vec();
+    for &id in &ids {
+        world.set_shared(id, material(), "stone").unwrap();
+    }
+
+    world.set_shared(ids[0], material(), "wood").unwrap();
+    assert_eq!(values(&world), ["stone", "wood"]);
+
+    // Despawned once the last user moves to another value
+    world.set_shared(ids[0], material(), "stone").unwrap();
+    assert_eq!(values(&world), ["stone"]);
+
+    world.remove_shared(ids[1], material()).unwrap();
+    world.despawn(ids[2]).unwrap();
+    world.clear(ids[3]).unwrap();
+    assert_eq!(values(&world), ["stone"]);
+
+    world.despawn(ids[0]).unwrap();
+    assert_eq!(values(&world), [""; 0]);
+
+    // Values are created again as needed
+    world.set_shared(ids[1], material(), "stone").unwrap();
+    world.set_shared(ids[1], mesh(), 5).unwrap();
+    assert_eq!(values(&world), ["stone"]);
+
+    let mut query = Query::new((material().shared(), mesh().shared()));
+    assert_eq!(query.borrow(&world).iter().collect_vec(), [(&"stone", &5)]);
+}
+
+#[test]
+#[should_panic(expected = "health is not a shared component")]
+fn shared_value_not_shared() {
+    let mut world = World::new();
+    let id = world.spawn();
+
+    world.set_shared(id, health(), 5).unwrap();
+}
@@ -0,0 +1,317 @@
+use flax::{
+    components::name,
+    metadata::{despawn, OnTargetDespawn, Sparse},
+    *,
+};
+use itertools::Itertools;
+
+component! {
+    link(target): f32 => [ Sparse ],
+    parent_of(child): () => [ Sparse, OnTargetDespawn<despawn::DespawnSubject> ],
+    depends_on(target): () => [ Sparse ],
+}
+
+#[test]
+fn sparse_pairs() {
+    let mut world = World::new();
+
+    let targets = world.spawn_many().take(64).collect_vec();
+    let subjects = world.spawn_many().take(16).collect_vec();
+
+    let mut archetypes = None;
+
+    for (i, &subject) in subjects.iter().enumerate() {
+        for &target in targets.iter().skip(i).step_by(4) {
+            world.set(subject, link(target), i as f32).unwrap();
+        }
+
+        // All subjects share a single archetype regardless of their targets
+        let count = world.archetype_info().len();
+        assert_eq!(*archetypes.get_or_insert(count), count);
+    }
+
+    assert!(world.has(subjects[0], link(targets[4])));
+    assert!(!world.has(subjects[0], link(targets[1])));
+    assert_eq!(*world.get(subjects[1], link(targets[5])).unwrap(), 1.0);
+    assert!(world.get(subjects[1], link(targets[4])).is_err());
+
+    *world.get_mut(subjects[1], link(targets[5])).unwrap() = 5.0;
+    assert_eq!(
+        world
+            .entity(subjects[1])
+            .unwrap()
+            .relations(link)
+            .map(|(target, value)| (target, *value))
+            .take(2)
+            .collect_vec(),
+        [(targets[1], 1.0), (targets[5], 5.0)]
+    );
+
+    let mut query = Query::new((entity_ids(), link.nth_relation(1)));
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, (target, _))| (id, target))
+            .take(2)
+            .collect_vec(),
+        [(subjects[0], targets[4]), (subjects[1], targets[5])]
+    );
+
+    let mut query = Query::new((entity_ids(), link.targeting(targets[6])));
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|(id, (_, &value))| (id, value))
+            .collect_vec(),
+        [(subjects[2], 2.0), (subjects[6], 6.0)]
+    );
+
+    let mut query = Query::new(relations_like(link));
+    assert_eq!(
+        query
+            .borrow(&world)
+            .iter()
+            .map(|v| v.count())
+            .sum::<usize>(),
+        (0..16)
+            .map(|i| (64 - i as usize).div_ceil(4))
+            .sum::<usize>()
+    );
+
+    // Removing the last pair removes the subject from the shared archetype
+    let targets_of_last = world
+        .entity(subjects[15])
+        .unwrap()
+        .relations(link)
+        .map(|v| v.0)
+        .collect_vec();
+
+    for target in targets_of_last {
+        world.remove(subjects[15], link(target)).unwrap();
+    }
+
+    assert!(!world.has(subjects[15], link.sparse_pairs()));
+    assert!(world.remove(subjects[15], link(targets[15])).is_err());
+
+    // Despawning a target removes the pairs
+    world.despawn(targets[4]).unwrap();
+    assert!(!world.has(subjects[0], link(targets[4])));
+    assert!(world.has(subjects[0], link(targets[0])));
+}
+
+#[test]
+fn sparse_change_detection() {
+    let mut world = World::new();
+
+    let a = world.spawn();
+    let b = world.spawn();
+    let subject = world.spawn();
+
+    world.set(subject, link(a), 1.0).unwrap();
+
+    let mut query = Query::new(entity_ids()).filter(link.sparse_pairs().modified());
+    assert_eq!(query.borrow(&world).iter().collect_vec(), [subject]);
+    assert_eq!(query.borrow(&world).iter().collect_vec(), []);
+
+    world.set(subject, link(b), 2.0).unwrap();
+    assert_eq!(query.borrow(&world).iter().collect_vec(), [subject]);
+
+    *world.get_mut(subject, link(a)).unwrap() += 1.0;
+    assert_eq!(query.borrow(&world).iter().collect_vec(), [subject]);
+
+    world.remove(subject, link(b)).unwrap();
+    assert_eq!(query.borrow(&world).iter().collect_vec(), [subject]);
+    assert_eq!(query.borrow(&world).iter().collect_vec(), []);
+}
+
+#[test]
+fn sparse_pair_change_detection() {
+    let mut world = World::new();
+
+    let a = world.spawn();
+    let b = world.spawn();
+    let subjects = world.spawn_many().take(4).collect_vec();
+
+    for &subject in &subjects {
+        world.set(subject, link(a), 1.0).unwrap();
+    }
+
+    let mut modified = Query::new((entity_ids(), link.targeting(a).modified()));
+    let mut added = Query::new(entity_ids()).filter(link.targeting(b).added());
+
+    assert_eq!(
+        modified.borrow(&world).iter().collect_vec(),
+        subjects.iter().map(|&v| (v, (a, &1.0))).collect_vec()
+    );
+    assert_eq!(modified.borrow(&world).iter().collect_vec(), []);
+    assert_eq!(added.borrow(&world).iter().collect_vec(), []);
+
+    // Changes to another pair of the same subject are not reported
+    world.set(subjects[1], link(b), 2.0).unwrap();
+    assert_eq!(modified.borrow(&world).iter().collect_vec(), []);
+    assert_eq!(added.borrow(&world).iter().collect_vec(), [subjects[1]]);
+
+    world.set(subjects[2], link(a), 3.0).unwrap();
+    *world.get_mut(subjects[3], link(a)).unwrap() += 1.0;
+
+    assert_eq!(
+        modified.borrow(&world).iter().collect_vec(),
+        [(subjects[2], (a, &3.0)), (subjects[3], (a, &2.0))]
+    );
+
+    // Replacing a pair does not count as adding it
+    world.set(subjects[1], link(b), 4.0).unwrap();
+    assert_eq!(added.borrow(&world).iter().collect_vec(), []);
+}
+
+#[test]
+#[should_panic(expected = "sparse relation")]
+fn sparse_pair_fetch() {
+    let mut world = World::new();
+
+    let target = world.spawn();
+    let subject = world.spawn();
+    world.set(subject, link(target), 1.0).unwrap();
+
+    Query::new(link(target)).borrow(&world).for_each(|_| {});
+}
+
+#[test]
+#[should_panic(expected = "sparse relation")]
+fn sparse_pair_change_filter() {
+    let mut world = World::new();
+
+    let target = world.spawn();
+    let subject = world.spawn();
+    world.set(subject, link(target), 1.0).unwrap();
+
+    Query::new(entity_ids())
+        .filter(link(target).modified())
+        .borrow(&world)
+        .for_each(|_| {});
+}
+
+#[test]
+fn sparse_hierarchy() {
+    let mut world = World::new();
+
+    let root = Entity::builder()
+        .set(name(), "root".into())
+        .spawn(&mut world);
+
+    let ids = (0..6)
+        .map(|i| {
+            Entity::builder()
+                .set(name(), i.to_string())
+                .spawn(&mut world)
+        })
+        .collect_vec();
+
+    // root -> 0 -> 1 -> 2
+    //      -> 3 -> 4
+    //           -> 5
+    let edges = [
+        (ids[0], root),
+        (ids[1], ids[0]),
+        (ids[2], ids[1]),
+        (ids[3], root),
+        (ids[4], ids[3]),
+        (ids[5], ids[3]),
+    ];
+
+    for (child, parent) in edges {
+        world.set(child, depends_on(parent), ()).unwrap();
+    }
+
+    let mut query = Query::new(entity_ids()).with_strategy(Dfs::new(depends_on));
+    assert_eq!(
+        query.borrow(&world).iter().collect_vec(),
+        [root, ids[3], ids[5], ids[4], ids[0], ids[1], ids[2]]
+    );
+
+    let mut query = Query::new(entity_ids()).with_strategy(Bfs::new(depends_on));
+    assert_eq!(
+        query.borrow(&world).iter().collect_vec(),
+        [root, ids[0], ids[3], ids[1], ids[4], ids[5], ids[2]]
+    );
+
+    let mut query = Query::new(entity_ids()).with_strategy(Topo::new(depends_on));
+    let order = query.borrow(&world).iter().collect_vec();
+    assert_eq!(order.iter().sorted().collect_vec(), {
+        let mut all = ids.iter().chain([&root]).collect_vec();
+        all.sort();
+        all
+    });
+
+    for (child, parent) in edges {
+        let pos = |id| order.iter().position(|&v| v == id).unwrap();
+        assert!(pos(parent) < pos(child));
+    }
+
+    // Rebuilt when the pairs change, even without new archetypes
+    world.remove(ids[3], depends_on(root)).unwrap();
+    world.set(ids[3], depends_on(ids[2]), ()).unwrap();
+
+    let mut paths = Vec::new();
+    Query::new(name())
+        .with_strategy(Dfs::new(depends_on))
+        .borrow(&world)
+        .traverse(&String::new(), |name, _, prefix| {
+            let path = format!("{prefix}/{name}");
+            paths.push(path.clone());
+            path
+        });
+
+    assert_eq!(
+        paths.into_iter().sorted().collect_vec(),
+        [
+            "/root",
+            "/root/0",
+            "/root/0/1",
+            "/root/0/1/2",
+            "/root/0/1/2/3",
+            "/root/0/1/2/3/4",
+            "/root/0/1/2/3/5",
+        ]
+    );
+}
+
+#[test]
+fn sparse_despawn_subject() {
+    let mut world = World::new();
+
+    let a = world.spawn();
+    let b = world.spawn();
+    let c = world.spawn();
+
+    world.set(a, parent_of(b), ()).unwrap();
+    world.set(b, parent_of(c), ()).unwrap();
+
+    // Despawning `c` despawns its parents in turn
+    world.despawn(c).unwrap();
+    assert!(!world.is_alive(b));
+    assert!(!world.is_alive(a));
+}
+
+#[test]
+fn sparse_commands() {
+    let mut world = World::new();
+    let mut cmd = CommandBuffer::new();
+
+    let target = world.spawn();
+    let subject = Entity::builder().set(link(target), 1.0).spawn(&mut world);
+
+    cmd.set(subject, link(subject), 2.0)
+        .remove(subject, link(target));
+
+    cmd.apply_atomic(&mut world).unwrap();
+
+    assert_eq!(*world.get(subject, link(subject)).unwrap(), 2.0);
+    assert!(!world.has(subject, link(target)));
+
+    // The pair was removed by the previous command
+    cmd.remove(subject, link(target));
+    assert!(cmd.apply_atomic(&mut world).is_err());
+}
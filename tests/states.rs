@@ -0,0 +1,182 @@
+use std::sync::{Arc, Mutex};
+
+use flax::{
+    components::resources,
+    schedule::{state, ErrorPolicy, InState, OnEnter, OnExit, State, States},
+    BoxedSystem, EntityBorrow, Query, Schedule, System, World,
+};
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GameState {
+    Menu,
+    Loading,
+    Playing,
+}
+
+#[test]
+fn states() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let log = |event: &'static str| -> BoxedSystem {
+        let events = events.clone();
+        System::builder()
+            .with_name(event)
+            .build(move || events.lock().unwrap().push(event))
+            .boxed()
+    };
+
+    // Queue a transition from within a system
+    let transition = |next: GameState| -> BoxedSystem {
+        System::builder()
+            .with_name("transition")
+            .with_query(Query::new(state::<GameState>().as_mut()).entity(resources()))
+            .build(move |mut query: EntityBorrow<_>| {
+                let state: &mut State<GameState> = query.get().unwrap();
+                state.queue(next);
+            })
+            .boxed()
+    };
+
+    let mut states = States::new(GameState::Menu)
+        .with_schedule(
+            OnEnter(GameState::Menu),
+            Schedule::new().with_system(log("enter menu")),
+        )
+        .with_schedule(
+            OnExit(GameState::Menu),
+            Schedule::new().with_system(log("exit menu")),
+        )
+        .with_schedule(
+            InState(GameState::Menu),
+            Schedule::new()
+                .with_system(log("menu"))
+                .with_system(transition(GameState::Loading)),
+        )
+        .with_schedule(
+            // Loading finishes immediately
+            OnEnter(GameState::Loading),
+            Schedule::new()
+                .with_system(log("enter loading"))
+                .with_system(transition(GameState::Playing)),
+        )
+        .with_schedule(
+            OnExit(GameState::Loading),
+            Schedule::new().with_system(log("exit loading")),
+        )
+        .with_schedule(
+            OnEnter(GameState::Playing),
+            Schedule::new().with_system(log("enter playing")),
+        )
+        .with_schedule(
+            InState(GameState::Playing),
+            Schedule::new().with_system(log("playing")),
+        );
+
+    let mut world = World::new();
+
+    let report = states.execute_seq(&mut world).unwrap();
+    assert!(report.is_ok());
+    assert_eq!(
+        *world
+            .get(resources(), state::<GameState>())
+            .unwrap()
+            .current(),
+        GameState::Menu
+    );
+    assert_eq!(
+        world
+            .get(resources(), state::<GameState>())
+            .unwrap()
+            .queued()
+            .copied()
+            .collect_vec(),
+        [GameState::Loading]
+    );
+
+    states.execute_par(&mut world).unwrap();
+    assert_eq!(
+        *world
+            .get(resources(), state::<GameState>())
+            .unwrap()
+            .current(),
+        GameState::Playing
+    );
+
+    // Transitioning to the current state does nothing
+    world
+        .get_mut(resources(), state::<GameState>())
+        .unwrap()
+        .queue(GameState::Playing);
+
+    states.execute_seq(&mut world).unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "enter menu",
+            "menu",
+            "exit menu",
+            "enter loading",
+            "exit loading",
+            "enter playing",
+            "playing",
+            "playing",
+        ]
+    );
+}
+
+#[test]
+fn states_cycle() {
+    let toggle = |next: GameState| -> BoxedSystem {
+        System::builder()
+            .with_name("toggle")
+            .with_query(Query::new(state::<GameState>().as_mut()).entity(resources()))
+            .build(move |mut query: EntityBorrow<_>| {
+                let state: &mut State<GameState> = query.get().unwrap();
+                state.queue(next);
+            })
+            .boxed()
+    };
+
+    let fail = System::builder()
+        .with_name("fail")
+        .build(|| -> anyhow::Result<()> { anyhow::bail!("failed") })
+        .boxed();
+
+    // Entering either state immediately transitions to the other
+    let mut states = States::new(GameState::Menu)
+        .with_schedule(
+            OnEnter(GameState::Menu),
+            Schedule::builder()
+                .with_system(toggle(GameState::Loading))
+                .with_system(fail)
+                .with_error_policy(ErrorPolicy::Log)
+                .build(),
+        )
+        .with_schedule(
+            OnEnter(GameState::Loading),
+            Schedule::new().with_system(toggle(GameState::Menu)),
+        )
+        .with_max_transitions(8);
+
+    let mut world = World::new();
+
+    let err = states.execute_seq(&mut world).unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("Exceeded 8 state transitions"));
+
+    // Failures collected before aborting are kept
+    assert!(err
+        .to_string()
+        .starts_with("Aborted after 5 systems failed:"));
+
+    // The transition which exceeded the limit is applied by the next execution
+    {
+        let state = world.get(resources(), state::<GameState>()).unwrap();
+        assert_eq!(state.current(), &GameState::Menu);
+        assert_eq!(state.queued().collect_vec(), [&GameState::Loading]);
+    }
+}
@@ -0,0 +1,59 @@
+#[test]
+#[cfg(feature = "derive")]
+fn derive_system_param() {
+    use flax::*;
+    use std::sync::{Arc, Mutex};
+
+    component! {
+        health: f32,
+        regen: f32,
+    }
+
+    #[derive(SystemParam)]
+    struct RegenParams<'w> {
+        query: QueryBorrow<'w, (Mutable<f32>, Component<f32>)>,
+        cmd: &'w mut CommandBuffer,
+        ticks: Local<'w, usize>,
+    }
+
+    let mut world = World::new();
+
+    let id = Entity::builder()
+        .set(health(), 50.0)
+        .set(regen(), 5.0)
+        .spawn(&mut world);
+
+    let ticks = Arc::new(Mutex::new(0));
+
+    let mut system = {
+        let ticks = ticks.clone();
+        (move |mut params: RegenParams| {
+            for (health, regen) in &mut params.query {
+                *health += *regen;
+            }
+
+            *params.ticks += 1;
+            *ticks.lock().unwrap() = *params.ticks;
+
+            if *params.ticks == 2 {
+                params.cmd.remove(id, regen());
+            }
+        })
+        .with_input(Query::new((health().as_mut(), regen())))
+        .boxed()
+    };
+
+    for _ in 0..3 {
+        system.run(&mut world).unwrap();
+    }
+
+    assert_eq!(*world.get(id, health()).unwrap(), 60.0);
+    assert_eq!(*ticks.lock().unwrap(), 3);
+    assert!(!world.has(id, regen()));
+
+    let mut accesses = Vec::new();
+    system.access(&world, &mut accesses);
+    assert!(accesses.iter().any(|v| v.mutable));
+
+    assert!(format!("{system:?}").contains("RegenParams"));
+}
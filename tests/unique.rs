@@ -0,0 +1,168 @@
+use flax::{
+    archetype::BatchSpawn,
+    buffer::ComponentBuffer,
+    metadata::{unique, Unique, UniqueValue},
+    *,
+};
+
+component! {
+    main_camera: () => [ Unique ],
+    player_controller: () => [ Unique<unique::Steal> ],
+    network_id: u64 => [ UniqueValue ],
+    slot: u32 => [ UniqueValue<unique::Steal> ],
+}
+
+#[test]
+fn unique_component() {
+    let mut world = World::new();
+
+    assert!(world.single(main_camera()).is_none());
+
+    let a = Entity::builder()
+        .set(main_camera(), ())
+        .set(player_controller(), ())
+        .spawn(&mut world);
+
+    let b = world.spawn();
+
+    assert_eq!(
+        world.set(b, main_camera(), ()),
+        Err(Error::NotUnique(main_camera().desc(), Some(a)))
+    );
+    assert!(!world.has(b, main_camera()));
+
+    // Setting it again for the holder is allowed
+    world.set(a, main_camera(), ()).unwrap();
+    assert_eq!(world.single(main_camera()).map(|v| v.0), Some(a));
+
+    world.set(b, player_controller(), ()).unwrap();
+    assert!(!world.has(a, player_controller()));
+    assert_eq!(world.single(player_controller()).map(|v| v.0), Some(b));
+
+    world.despawn(a).unwrap();
+    world.set(b, main_camera(), ()).unwrap();
+    assert_eq!(world.single(main_camera()).map(|v| v.0), Some(b));
+}
+
+#[test]
+fn unique_value() {
+    let mut world = World::new();
+
+    let a = Entity::builder()
+        .set(network_id(), 1)
+        .set(slot(), 1)
+        .spawn(&mut world);
+
+    let b = Entity::builder()
+        .set(network_id(), 2)
+        .set(slot(), 2)
+        .spawn(&mut world);
+
+    assert_eq!(
+        world.set(b, network_id(), 1),
+        Err(Error::NotUnique(network_id().desc(), Some(a)))
+    );
+
+    // The failed set does not modify anything
+    let mut buffer = ComponentBuffer::new();
+    buffer.set(slot(), 3);
+    buffer.set(network_id(), 1);
+    assert!(world.set_with(b, &mut buffer).is_err());
+    assert_eq!(*world.get(b, slot()).unwrap(), 2);
+
+    world.set(b, network_id(), 3).unwrap();
+    assert_eq!(*world.get(b, network_id()).unwrap(), 3);
+
+    world.set(b, slot(), 1).unwrap();
+    assert!(!world.has(a, slot()));
+    assert_eq!(*world.get(b, slot()).unwrap(), 1);
+
+    let c = world.spawn();
+    world.despawn(c).unwrap();
+
+    assert_eq!(
+        Entity::builder()
+            .set(network_id(), 3)
+            .spawn_at(&mut world, c),
+        Err(Error::NotUnique(network_id().desc(), Some(b)))
+    );
+    assert!(!world.is_alive(c));
+
+    assert_eq!(
+        Entity::builder().set(network_id(), 3).try_spawn(&mut world),
+        Err(Error::NotUnique(network_id().desc(), Some(b)))
+    );
+
+    // Values are looked up through the index, which picks up values modified in place
+    *world.get_mut(b, network_id()).unwrap() = 7;
+    assert_eq!(world.find_by(network_id(), &7), [b]);
+    assert_eq!(
+        world.set(a, network_id(), 7),
+        Err(Error::NotUnique(network_id().desc(), Some(b)))
+    );
+    world.set(a, network_id(), 3).unwrap();
+}
+
+#[test]
+fn unique_batch() {
+    let mut batch = BatchSpawn::new(2);
+
+    assert_eq!(
+        batch.set(main_camera(), [(), ()]).err(),
+        Some(Error::UniqueInBatch(main_camera().desc()))
+    );
+    assert_eq!(
+        batch.set(network_id(), [1, 2]).err(),
+        Some(Error::UniqueInBatch(network_id().desc()))
+    );
+    assert_eq!(batch.components().count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn unique_entity_ref() {
+    let mut world = World::new();
+
+    Entity::builder().set(main_camera(), ()).spawn(&mut world);
+    world.spawn_ref().set(main_camera(), ());
+}
+
+#[test]
+fn unique_commands() {
+    let mut world = World::new();
+    let mut cmd = CommandBuffer::new();
+
+    let a = Entity::builder().set(network_id(), 1).spawn(&mut world);
+    let b = world.spawn();
+
+    cmd.set(b, network_id(), 1);
+    assert!(cmd.apply(&mut world).is_err());
+    assert!(!world.has(b, network_id()));
+
+    cmd.set(b, slot(), 1).set(a, slot(), 1);
+    cmd.apply(&mut world).unwrap();
+    assert!(!world.has(b, slot()));
+    assert!(world.has(a, slot()));
+
+    // Validated against the values set by previous commands
+    cmd.set(b, network_id(), 2)
+        .spawn(Entity::builder().set(network_id(), 2));
+    let errors = cmd.apply_atomic(&mut world).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].index, 1);
+    assert!(!world.has(b, network_id()));
+
+    // Removing the previous holder first frees the value
+    cmd.remove(a, network_id()).set(b, network_id(), 1);
+    cmd.apply_atomic(&mut world).unwrap();
+    assert_eq!(
+        world.single(network_id()).map(|v| (v.0, *v.1)),
+        Some((b, 1))
+    );
+
+    // Stolen values can not be removed again
+    cmd.set(a, slot(), 2).set(b, slot(), 2).remove(a, slot());
+    let errors = cmd.apply_atomic(&mut world).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].index, 2);
+}